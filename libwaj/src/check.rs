@@ -0,0 +1,238 @@
+use crate::common::Entry;
+use crate::error::WajError;
+use crate::walk::{Operator, Walker};
+use crate::{Alias, CommonEntry, Content, FullBuilder, Redirect, Waj, Whiteout};
+use std::collections::HashSet;
+
+/// A problem found by [`crate::Waj::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// Jubako checksums of the packs don't match.
+    InvalidChecksum,
+
+    /// The content is stored in a missing pack.
+    MissingContent { path: String },
+
+    /// The content cannot be read.
+    UnreadableContent { path: String, error: String },
+
+    /// The target of the redirect doesn't exist.
+    DanglingRedirect { path: String, target: String },
+
+    /// The target of the alias doesn't lead to a content (through at most 10 aliases).
+    DanglingAlias { path: String, target: String },
+
+    /// Following the redirects from `path` leads back to a already visited redirect.
+    RedirectLoop { path: String },
+
+    /// Following the redirects from `path` needs more than the maximum number of redirects.
+    RedirectChainTooLong { path: String, length: usize },
+
+    /// `path` is sorted before the previous entry in the `waj_entries` index.
+    UnsortedEntry { path: String, previous: String },
+
+    /// Several entries have the path `path`.
+    DuplicatePath { path: String },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidChecksum => write!(f, "Invalid checksum"),
+            Self::MissingContent { path } => write!(f, "{path}: content is in a missing pack"),
+            Self::UnreadableContent { path, error } => {
+                write!(f, "{path}: cannot read content ({error})")
+            }
+            Self::DanglingRedirect { path, target } => {
+                write!(f, "{path}: redirect to non existing {target}")
+            }
+            Self::DanglingAlias { path, target } => {
+                write!(f, "{path}: alias to {target} which is not a content")
+            }
+            Self::RedirectLoop { path } => write!(f, "{path}: redirect loop"),
+            Self::RedirectChainTooLong { path, length } => {
+                write!(f, "{path}: chain of more than {length} redirects")
+            }
+            Self::UnsortedEntry { path, previous } => {
+                write!(f, "{path}: entry is sorted after {previous}")
+            }
+            Self::DuplicatePath { path } => write!(f, "{path}: duplicated path"),
+        }
+    }
+}
+
+/// The result of [`crate::Waj::check`].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Number of entries checked.
+    pub entries: u64,
+
+    pub issues: Vec<Issue>,
+}
+
+impl CheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct CheckContext {
+    report: CheckReport,
+    previous_path: Option<Vec<u8>>,
+    redirects: Vec<(String, String, Option<String>)>,
+    aliases: Vec<(String, String)>,
+}
+
+impl CheckContext {
+    fn check_order(&mut self, path: &[u8]) {
+        self.report.entries += 1;
+        if let Some(previous) = &self.previous_path {
+            match previous.as_slice().cmp(path) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => self.report.issues.push(Issue::DuplicatePath {
+                    path: String::from_utf8_lossy(path).into_owned(),
+                }),
+                std::cmp::Ordering::Greater => self.report.issues.push(Issue::UnsortedEntry {
+                    path: String::from_utf8_lossy(path).into_owned(),
+                    previous: String::from_utf8_lossy(previous).into_owned(),
+                }),
+            }
+        }
+        self.previous_path = Some(path.to_vec());
+    }
+}
+
+struct Checker<'a> {
+    waj: &'a Waj,
+}
+
+impl Operator<CheckContext, FullBuilder> for Checker<'_> {
+    fn on_start(&self, _context: &mut CheckContext) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_stop(&self, _context: &mut CheckContext) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_content(&self, context: &mut CheckContext, entry: &Content) -> jbk::Result<()> {
+        context.check_order(entry.path());
+        let path = String::from_utf8_lossy(entry.path()).into_owned();
+        match self
+            .waj
+            .get_bytes(entry.content())?
+            .and_then(|m| m.transpose())
+        {
+            None => context.report.issues.push(Issue::UnreadableContent {
+                path,
+                error: "invalid content address".into(),
+            }),
+            Some(jbk::reader::MayMissPack::MISSING(_)) => {
+                context.report.issues.push(Issue::MissingContent { path })
+            }
+            Some(jbk::reader::MayMissPack::FOUND(bytes)) => {
+                if let Err(e) = std::io::copy(&mut bytes.stream(), &mut std::io::sink()) {
+                    context.report.issues.push(Issue::UnreadableContent {
+                        path,
+                        error: e.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_redirect(&self, context: &mut CheckContext, entry: &Redirect) -> jbk::Result<()> {
+        context.check_order(entry.path());
+        context.redirects.push((
+            String::from_utf8_lossy(entry.path()).into_owned(),
+            String::from_utf8_lossy(entry.target()).into_owned(),
+            entry.target_path(),
+        ));
+        Ok(())
+    }
+
+    fn on_alias(&self, context: &mut CheckContext, entry: &Alias) -> jbk::Result<()> {
+        context.check_order(entry.path());
+        context.aliases.push((
+            String::from_utf8_lossy(entry.path()).into_owned(),
+            String::from_utf8_lossy(entry.target()).into_owned(),
+        ));
+        Ok(())
+    }
+
+    fn on_whiteout(&self, context: &mut CheckContext, entry: &Whiteout) -> jbk::Result<()> {
+        context.check_order(entry.path());
+        Ok(())
+    }
+}
+
+impl Waj {
+    /// Check the integrity and the consistency of the archive.
+    ///
+    /// Checksums of the packs are verified and all contents are read.
+    /// Redirects must lead to a existing entry in less than `max_redirects` redirects.
+    pub fn check(&self, max_redirects: usize) -> Result<CheckReport, WajError> {
+        let mut walker = Walker::new(self, CheckContext::default());
+        walker.run(&Checker { waj: self })?;
+        let mut context = walker.into_context();
+        let report = &mut context.report;
+        if !jbk::reader::Container::check(self)? {
+            report.issues.insert(0, Issue::InvalidChecksum);
+        }
+
+        for (path, target) in context.aliases {
+            if self.follow_aliases(target.clone())?.is_none() {
+                report.issues.push(Issue::DanglingAlias { path, target });
+            }
+        }
+
+        for (path, target, target_path) in context.redirects {
+            // External redirects cannot be checked.
+            let mut target_path = match target_path {
+                None => continue,
+                Some(target_path) => target_path,
+            };
+            let mut visited = HashSet::from([path.clone()]);
+            let mut length = 1;
+            loop {
+                let (found_path, entry) = match self.get_entry_from_url::<FullBuilder>(&target_path)
+                {
+                    // A whiteout is served as not found.
+                    Err(WajError::PathNotFound(_)) | Ok((_, Entry::Whiteout(_))) => {
+                        report.issues.push(Issue::DanglingRedirect {
+                            path: path.clone(),
+                            target: target.clone(),
+                        });
+                        break;
+                    }
+                    result => result?,
+                };
+                let redirect = match entry {
+                    Entry::Redirect(redirect) => redirect,
+                    _ => break,
+                };
+                if !visited.insert(found_path) {
+                    report
+                        .issues
+                        .push(Issue::RedirectLoop { path: path.clone() });
+                    break;
+                }
+                length += 1;
+                if length > max_redirects {
+                    report.issues.push(Issue::RedirectChainTooLong {
+                        path: path.clone(),
+                        length: max_redirects,
+                    });
+                    break;
+                }
+                target_path = match redirect.target_path() {
+                    None => break,
+                    Some(target_path) => target_path,
+                };
+            }
+        }
+        Ok(context.report)
+    }
+}
@@ -34,8 +34,19 @@ pub trait FullBuilderTrait {
         idx: jbk::EntryIdx,
         reader: &ByteSlice,
     ) -> jbk::Result<<Self::Entry as EntryDef>::Redirect>;
+    fn create_alias(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Alias>;
+    fn create_whiteout(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Whiteout>;
 }
 
+// Builders without alias nor whiteout builders, see the matching `EntryDef` impls.
 impl<C, R> FullBuilderTrait for (C, R)
 where
     C: Builder,
@@ -64,6 +75,125 @@ where
     ) -> jbk::Result<<Self::Entry as EntryDef>::Redirect> {
         self.1.create_entry(idx, reader)
     }
+
+    fn create_alias(
+        &self,
+        _idx: jbk::EntryIdx,
+        _reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Alias> {
+        Ok(())
+    }
+
+    fn create_whiteout(
+        &self,
+        _idx: jbk::EntryIdx,
+        _reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Whiteout> {
+        Ok(())
+    }
+}
+
+impl<C, R, A> FullBuilderTrait for (C, R, A)
+where
+    C: Builder,
+    R: Builder,
+    A: Builder,
+{
+    type Entry = (C::Entry, R::Entry, A::Entry);
+
+    fn new(properties: &AllProperties) -> Self {
+        let content_builder = C::new(properties);
+        let redirect_builder = R::new(properties);
+        let alias_builder = A::new(properties);
+        (content_builder, redirect_builder, alias_builder)
+    }
+
+    fn create_content(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Content> {
+        self.0.create_entry(idx, reader)
+    }
+
+    fn create_redirect(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Redirect> {
+        self.1.create_entry(idx, reader)
+    }
+
+    fn create_alias(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Alias> {
+        self.2.create_entry(idx, reader)
+    }
+
+    fn create_whiteout(
+        &self,
+        _idx: jbk::EntryIdx,
+        _reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Whiteout> {
+        Ok(())
+    }
+}
+
+impl<C, R, A, W> FullBuilderTrait for (C, R, A, W)
+where
+    C: Builder,
+    R: Builder,
+    A: Builder,
+    W: Builder,
+{
+    type Entry = (C::Entry, R::Entry, A::Entry, W::Entry);
+
+    fn new(properties: &AllProperties) -> Self {
+        let content_builder = C::new(properties);
+        let redirect_builder = R::new(properties);
+        let alias_builder = A::new(properties);
+        let whiteout_builder = W::new(properties);
+        (
+            content_builder,
+            redirect_builder,
+            alias_builder,
+            whiteout_builder,
+        )
+    }
+
+    fn create_content(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Content> {
+        self.0.create_entry(idx, reader)
+    }
+
+    fn create_redirect(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Redirect> {
+        self.1.create_entry(idx, reader)
+    }
+
+    fn create_alias(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Alias> {
+        self.2.create_entry(idx, reader)
+    }
+
+    fn create_whiteout(
+        &self,
+        idx: jbk::EntryIdx,
+        reader: &ByteSlice,
+    ) -> jbk::Result<<Self::Entry as EntryDef>::Whiteout> {
+        self.3.create_entry(idx, reader)
+    }
 }
 
 pub(crate) struct RealBuilder<B: FullBuilderTrait> {
@@ -107,6 +237,14 @@ where
                         let entry = self.builder.create_redirect(idx, &reader)?;
                         Entry::Redirect(entry)
                     }
+                    Some(EntryType::Alias) => {
+                        let entry = self.builder.create_alias(idx, &reader)?;
+                        Entry::Alias(entry)
+                    }
+                    Some(EntryType::Whiteout) => {
+                        let entry = self.builder.create_whiteout(idx, &reader)?;
+                        Entry::Whiteout(entry)
+                    }
                     None => {
                         return Err(error::BaseError::WajFormatError(WajFormatError(
                             "Unknow variant",
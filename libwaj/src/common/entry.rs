@@ -1,14 +1,36 @@
 pub trait EntryDef {
     type Content;
     type Redirect;
+    type Alias;
+    type Whiteout;
 }
 
+// Definitions without alias nor whiteout, kept for entry types written before
+// those variants existed. Missing variants are built as `()`.
 impl<C, R> EntryDef for (C, R) {
     type Content = C;
     type Redirect = R;
+    type Alias = ();
+    type Whiteout = ();
+}
+
+impl<C, R, A> EntryDef for (C, R, A) {
+    type Content = C;
+    type Redirect = R;
+    type Alias = A;
+    type Whiteout = ();
+}
+
+impl<C, R, A, W> EntryDef for (C, R, A, W) {
+    type Content = C;
+    type Redirect = R;
+    type Alias = A;
+    type Whiteout = W;
 }
 
 pub enum Entry<E: EntryDef> {
     Content(E::Content),
     Redirect(E::Redirect),
+    Alias(E::Alias),
+    Whiteout(E::Whiteout),
 }
@@ -1,7 +1,9 @@
 jbk::variants! {
     EntryType {
         Content => "content",
-        Redirect => "redirect"
+        Redirect => "redirect",
+        Alias => "alias",
+        Whiteout => "whiteout"
     }
 }
 
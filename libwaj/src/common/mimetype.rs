@@ -0,0 +1,24 @@
+/// The essence of `mimetype` (the type without its parameters), lowercase.
+pub(crate) fn mime_essence(mimetype: &str) -> String {
+    mimetype
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Whether `mimetype` is a `type/subtype` made of visible ascii characters,
+/// optionally followed by parameters (`text/html; charset=iso-8859-1`).
+///
+/// Mimetypes are served as is in the `Content-Type` header.
+pub fn is_valid_mimetype(mimetype: &str) -> bool {
+    let essence = mimetype.split(';').next().unwrap_or_default().trim();
+    let has_subtype = essence
+        .split_once('/')
+        .is_some_and(|(type_, subtype)| !type_.is_empty() && !subtype.is_empty());
+    has_subtype
+        && mimetype
+            .bytes()
+            .all(|b| b.is_ascii_graphic() || b == b' ' || b == b'\t')
+}
@@ -1,7 +1,9 @@
 mod builder;
 mod entry;
 mod entry_type;
+mod mimetype;
 mod properties;
+mod redirect_status;
 
 pub(crate) use builder::RealBuilder;
 pub use builder::{Builder, FullBuilderTrait};
@@ -9,7 +11,15 @@ pub use entry::{Entry, EntryDef};
 pub use entry_type::EntryType;
 use jbk::reader::builder::{BuilderTrait, PropertyBuilderTrait};
 use jbk::reader::Range;
-pub use properties::{AllProperties, Property};
+pub use mimetype::is_valid_mimetype;
+pub(crate) use mimetype::mime_essence;
+pub use properties::{
+    AllProperties, EncodingProperties, EncodingProperty, FtsDocumentProperties,
+    FtsDocumentProperty, FtsWordProperties, FtsWordProperty, MetadataProperties, MetadataProperty,
+    Property, TitleProperties, TitleProperty,
+};
+pub use redirect_status::RedirectStatus;
+use std::borrow::Cow;
 
 pub const VENDOR_ID: jbk::VendorId = jbk::VendorId::new([0x77, 0x61, 0x6a, 0x00]);
 
@@ -54,6 +64,92 @@ impl jbk::reader::CompareTrait for EntryCompare<'_> {
     }
 }
 
+/// The paths to search for a requested `url`, in order.
+///
+/// The url as is, without the query string, and with `index.html` appended if it is a directory.
+pub(crate) fn url_variants(url: &str) -> Vec<Cow<'_, str>> {
+    let mut vec: Vec<Cow<str>> = vec![];
+    vec.push(url.into());
+    let query_string_idx = url.find('?');
+    if let Some(idx) = query_string_idx {
+        vec.push(url[..idx].into())
+    }
+    let end_idx = match query_string_idx {
+        Some(idx) => idx,
+        None => url.len(),
+    };
+    if url[..end_idx].ends_with('/') {
+        let mut new_url = String::from(&url[..end_idx]);
+        new_url.push_str("index.html");
+        vec.push(new_url.into());
+    }
+    vec
+}
+
+/// Resolve `target` against the entry at `path`, as a browser would resolve a link.
+///
+/// `target` is relative to the directory of `path`, or to the root of the archive if it starts with a `/`.
+/// `.` and `..` components are removed, a trailing `/` is kept.
+pub(crate) fn resolve_path(path: &str, target: &str) -> String {
+    let mut components: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => vec![],
+        None => path.split('/').collect(),
+    };
+    // Remove the name of the entry itself.
+    components.pop();
+    let target = target.strip_prefix('/').unwrap_or(target);
+    let target_components: Vec<&str> = target.split('/').collect();
+    let last = target_components.len() - 1;
+    for (idx, component) in target_components.into_iter().enumerate() {
+        match component {
+            ".." => {
+                components.pop();
+            }
+            "." => {}
+            "" if idx != last => {}
+            _ => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+/// Search the first entry in `[start, end[` for which `is_before` returns false.
+///
+/// Entries must be partitioned: all entries for which `is_before` is true must be
+/// before the ones for which it is false.
+pub(crate) fn partition_point<F>(
+    start: jbk::EntryIdx,
+    end: jbk::EntryIdx,
+    mut is_before: F,
+) -> jbk::Result<jbk::EntryIdx>
+where
+    F: FnMut(jbk::EntryIdx) -> jbk::Result<bool>,
+{
+    let mut low = start.into_u32();
+    let mut high = end.into_u32();
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if is_before(middle.into())? {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    Ok(low.into())
+}
+
+/// Read a content hash property.
+///
+/// Returns None if the hash has not been stored for this entry.
+pub(crate) fn read_hash(
+    hash_property: &jbk::reader::builder::ArrayProperty,
+    reader: &jbk::reader::ByteSlice,
+) -> jbk::Result<Option<[u8; 32]>> {
+    let mut hash = jbk::SmallBytes::new();
+    hash_property.create(reader)?.resolve_to_vec(&mut hash)?;
+    Ok(hash.as_slice().try_into().ok())
+}
+
 pub struct ReadEntry<'builder, Builder: BuilderTrait> {
     builder: &'builder Builder,
     current: jbk::EntryIdx,
@@ -69,6 +165,18 @@ impl<'builder, Builder: BuilderTrait> ReadEntry<'builder, Builder> {
             end,
         }
     }
+
+    pub fn new_with_bounds(
+        start: jbk::EntryIdx,
+        end: jbk::EntryIdx,
+        builder: &'builder Builder,
+    ) -> Self {
+        Self {
+            builder,
+            current: start,
+            end,
+        }
+    }
 }
 
 impl<'builder, Builder: BuilderTrait> Iterator for ReadEntry<'builder, Builder> {
@@ -10,17 +10,79 @@ properties! {
         Path:"array" => "path",
         Mimetype:"array" => "mimetype",
         Content:"content" => "content",
-        Target:"array" => "target"
+        Mtime:"uint" => "mtime",
+        Hash:"array" => "hash",
+        Target:"array" => "target",
+        Status:"uint" => "status",
+        External:"uint" => "external"
     }
 }
 
+properties! {
+    MetadataProperty {
+        Key:"array" => "key",
+        Value:"array" => "value"
+    }
+}
+
+properties! {
+    TitleProperty {
+        Key:"array" => "key",
+        Title:"array" => "title",
+        Path:"array" => "path"
+    }
+}
+
+properties! {
+    FtsWordProperty {
+        Word:"array" => "word",
+        Postings:"content" => "postings",
+        DocCount:"uint" => "doc_count"
+    }
+}
+
+properties! {
+    FtsDocumentProperty {
+        Path:"array" => "path",
+        Length:"uint" => "length"
+    }
+}
+
+properties! {
+    EncodingProperty {
+        Path:"array" => "path",
+        Encoding:"array" => "encoding",
+        Content:"content" => "content"
+    }
+}
+
+/// Same as `layout_builder` but returns None if the property is not in the layout.
+///
+/// This is used for properties added after the first version of the format.
+macro_rules! optional_layout_builder {
+    ($($args:tt)*) => {
+        (|| -> Result<_, BaseError> { Ok(layout_builder!($($args)*)) })().ok()
+    };
+}
+
 pub struct AllProperties {
     pub store: jbk::reader::EntryStore,
     pub path_property: jbk::reader::builder::ArrayProperty,
     pub variant_id_property: jbk::reader::builder::VariantIdBuilder<EntryType>,
     pub content_mimetype_property: jbk::reader::builder::ArrayProperty,
     pub content_address_property: jbk::reader::builder::ContentProperty,
+    // Not present in archives created before mtime was stored.
+    pub content_mtime_property: Option<jbk::reader::builder::IntProperty>,
+    // Not present in archives created before content hash was stored.
+    pub content_hash_property: Option<jbk::reader::builder::ArrayProperty>,
     pub redirect_target_property: jbk::reader::builder::ArrayProperty,
+    // Not present in archives created before redirect status was stored.
+    pub redirect_status_property: Option<jbk::reader::builder::IntProperty>,
+    pub redirect_external_property: Option<jbk::reader::builder::IntProperty>,
+    // Not present in archives created before alias variant was added.
+    pub alias_target_property: Option<jbk::reader::builder::ArrayProperty>,
+    // Not present in archives created before alias mtime was stored.
+    pub alias_mtime_property: Option<jbk::reader::builder::IntProperty>,
 }
 
 impl AllProperties {
@@ -29,9 +91,13 @@ impl AllProperties {
         value_storage: &jbk::reader::ValueStorage,
     ) -> Result<Self, BaseError> {
         let layout = store.layout();
-        if layout.variant_len() != 2 {
-            return Err(WajFormatError("Layout must contain 3 variants").into());
-        }
+        // Archives created before alias (and whiteout) variant was added have less variants.
+        // Whiteout variant has no specific property.
+        let has_alias = match layout.variant_len() {
+            2 => false,
+            3 | 4 => true,
+            _ => return Err(WajFormatError("Layout must contain 2 to 4 variants").into()),
+        };
         let path_property = layout_builder!(
             layout[common][Property::Path],
             value_storage,
@@ -48,18 +114,231 @@ impl AllProperties {
             value_storage,
             WajFormatError
         );
+        let content_mtime_property = optional_layout_builder!(
+            layout[EntryType::Content][Property::Mtime],
+            value_storage,
+            WajFormatError
+        );
+        let content_hash_property = optional_layout_builder!(
+            layout[EntryType::Content][Property::Hash],
+            value_storage,
+            WajFormatError
+        );
         let redirect_target_property = layout_builder!(
             layout[EntryType::Redirect][Property::Target],
             value_storage,
             WajFormatError
         );
+        let redirect_status_property = optional_layout_builder!(
+            layout[EntryType::Redirect][Property::Status],
+            value_storage,
+            WajFormatError
+        );
+        let redirect_external_property = optional_layout_builder!(
+            layout[EntryType::Redirect][Property::External],
+            value_storage,
+            WajFormatError
+        );
+        let (alias_target_property, alias_mtime_property) = if has_alias {
+            (
+                Some(layout_builder!(
+                    layout[EntryType::Alias][Property::Target],
+                    value_storage,
+                    WajFormatError
+                )),
+                optional_layout_builder!(
+                    layout[EntryType::Alias][Property::Mtime],
+                    value_storage,
+                    WajFormatError
+                ),
+            )
+        } else {
+            (None, None)
+        };
         Ok(Self {
             store,
             path_property,
             variant_id_property,
             content_mimetype_property,
             content_address_property,
+            content_mtime_property,
+            content_hash_property,
             redirect_target_property,
+            redirect_status_property,
+            redirect_external_property,
+            alias_target_property,
+            alias_mtime_property,
+        })
+    }
+}
+
+pub struct MetadataProperties {
+    pub store: jbk::reader::EntryStore,
+    pub key_property: jbk::reader::builder::ArrayProperty,
+    pub value_property: jbk::reader::builder::ArrayProperty,
+}
+
+impl MetadataProperties {
+    pub fn new(
+        store: jbk::reader::EntryStore,
+        value_storage: &jbk::reader::ValueStorage,
+    ) -> Result<Self, BaseError> {
+        let layout = store.layout();
+        let key_property = layout_builder!(
+            layout[common][MetadataProperty::Key],
+            value_storage,
+            WajFormatError
+        );
+        let value_property = layout_builder!(
+            layout[common][MetadataProperty::Value],
+            value_storage,
+            WajFormatError
+        );
+        Ok(Self {
+            store,
+            key_property,
+            value_property,
+        })
+    }
+}
+
+pub struct TitleProperties {
+    pub store: jbk::reader::EntryStore,
+    pub key_property: jbk::reader::builder::ArrayProperty,
+    pub title_property: jbk::reader::builder::ArrayProperty,
+    pub path_property: jbk::reader::builder::ArrayProperty,
+}
+
+impl TitleProperties {
+    pub fn new(
+        store: jbk::reader::EntryStore,
+        value_storage: &jbk::reader::ValueStorage,
+    ) -> Result<Self, BaseError> {
+        let layout = store.layout();
+        let key_property = layout_builder!(
+            layout[common][TitleProperty::Key],
+            value_storage,
+            WajFormatError
+        );
+        let title_property = layout_builder!(
+            layout[common][TitleProperty::Title],
+            value_storage,
+            WajFormatError
+        );
+        let path_property = layout_builder!(
+            layout[common][TitleProperty::Path],
+            value_storage,
+            WajFormatError
+        );
+        Ok(Self {
+            store,
+            key_property,
+            title_property,
+            path_property,
+        })
+    }
+}
+
+pub struct FtsWordProperties {
+    pub store: jbk::reader::EntryStore,
+    pub word_property: jbk::reader::builder::ArrayProperty,
+    pub postings_property: jbk::reader::builder::ContentProperty,
+    pub doc_count_property: jbk::reader::builder::IntProperty,
+}
+
+impl FtsWordProperties {
+    pub fn new(
+        store: jbk::reader::EntryStore,
+        value_storage: &jbk::reader::ValueStorage,
+    ) -> Result<Self, BaseError> {
+        let layout = store.layout();
+        let word_property = layout_builder!(
+            layout[common][FtsWordProperty::Word],
+            value_storage,
+            WajFormatError
+        );
+        let postings_property = layout_builder!(
+            layout[common][FtsWordProperty::Postings],
+            value_storage,
+            WajFormatError
+        );
+        let doc_count_property = layout_builder!(
+            layout[common][FtsWordProperty::DocCount],
+            value_storage,
+            WajFormatError
+        );
+        Ok(Self {
+            store,
+            word_property,
+            postings_property,
+            doc_count_property,
+        })
+    }
+}
+
+pub struct FtsDocumentProperties {
+    pub store: jbk::reader::EntryStore,
+    pub path_property: jbk::reader::builder::ArrayProperty,
+    pub length_property: jbk::reader::builder::IntProperty,
+}
+
+impl FtsDocumentProperties {
+    pub fn new(
+        store: jbk::reader::EntryStore,
+        value_storage: &jbk::reader::ValueStorage,
+    ) -> Result<Self, BaseError> {
+        let layout = store.layout();
+        let path_property = layout_builder!(
+            layout[common][FtsDocumentProperty::Path],
+            value_storage,
+            WajFormatError
+        );
+        let length_property = layout_builder!(
+            layout[common][FtsDocumentProperty::Length],
+            value_storage,
+            WajFormatError
+        );
+        Ok(Self {
+            store,
+            path_property,
+            length_property,
+        })
+    }
+}
+
+pub struct EncodingProperties {
+    pub store: jbk::reader::EntryStore,
+    pub path_property: jbk::reader::builder::ArrayProperty,
+    pub encoding_property: jbk::reader::builder::ArrayProperty,
+    pub content_property: jbk::reader::builder::ContentProperty,
+}
+
+impl EncodingProperties {
+    pub fn new(
+        store: jbk::reader::EntryStore,
+        value_storage: &jbk::reader::ValueStorage,
+    ) -> Result<Self, BaseError> {
+        let layout = store.layout();
+        let path_property = layout_builder!(
+            layout[common][EncodingProperty::Path],
+            value_storage,
+            WajFormatError
+        );
+        let encoding_property = layout_builder!(
+            layout[common][EncodingProperty::Encoding],
+            value_storage,
+            WajFormatError
+        );
+        let content_property = layout_builder!(
+            layout[common][EncodingProperty::Content],
+            value_storage,
+            WajFormatError
+        );
+        Ok(Self {
+            store,
+            path_property,
+            encoding_property,
+            content_property,
         })
     }
 }
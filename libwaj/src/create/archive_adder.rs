@@ -0,0 +1,427 @@
+use super::content_entry::{AliasEntry, ContentEntry, RedirectEntry};
+use super::sniff::{sniff_mime_type, SNIFF_LEN};
+use crate::common::resolve_path;
+use crate::create::{EntryStoreCreator, Namer, PathFilter, Void};
+use crate::error::CreatorError;
+use jbk::creator::ContentAdder;
+use log::{debug, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+/// The content of a member, spooled in a anonymous temporary file to be streamed
+/// to the content adder.
+struct SpooledMember {
+    file: File,
+    size: u64,
+}
+
+impl SpooledMember {
+    fn new(mut reader: impl Read) -> std::io::Result<Self> {
+        let mut file = tempfile::tempfile()?;
+        let size = std::io::copy(&mut reader, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self { file, size })
+    }
+}
+
+impl Read for SpooledMember {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Seek for SpooledMember {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl jbk::creator::InputReader for SpooledMember {
+    fn size(&self) -> jbk::Size {
+        jbk::Size::from(self.size)
+    }
+}
+
+/// A member of a archive, added to the creator once all the members are read.
+enum Member {
+    File(ContentEntry),
+    Symlink(String),
+    HardLink(String),
+}
+
+/// Add the members of a archive file (tar, zip), named as `FsAdder` names the files.
+///
+/// The archive is read twice. The first pass records the directories and the members
+/// to add: if several members have the same path, only the last one is added.
+/// The second pass adds the contents of those members.
+struct MemberAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+    namer: &'a dyn Namer,
+    path_filter: Option<&'a PathFilter>,
+    // Directories of the archive, to skip the symlinks to directories (as `FsAdder` does).
+    dirs: HashSet<String>,
+    // The names of the members (files and links) accepted at first pass, by index in the archive.
+    names: HashMap<usize, String>,
+    // The index of the last member of each name.
+    last_members: HashMap<String, usize>,
+    members: BTreeMap<String, Member>,
+}
+
+impl<'a> MemberAdder<'a> {
+    fn new(
+        creator: &'a mut EntryStoreCreator,
+        namer: &'a dyn Namer,
+        path_filter: Option<&'a PathFilter>,
+    ) -> Self {
+        Self {
+            creator,
+            namer,
+            path_filter,
+            dirs: HashSet::new(),
+            names: HashMap::new(),
+            last_members: HashMap::new(),
+            members: BTreeMap::new(),
+        }
+    }
+
+    /// The name of the member at `path`, or None if the member must not be added.
+    fn name(&self, path: &Path) -> Option<String> {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(c) => normalized.push(c),
+                Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+                Component::ParentDir => {
+                    warn!("Skip {} (path outside of the archive)", path.display());
+                    return None;
+                }
+            }
+        }
+        if normalized.as_os_str().is_empty() {
+            return None;
+        }
+        match self.namer.try_rename(&normalized) {
+            None => {
+                warn!("Skip {} (cannot be named)", path.display());
+                None
+            }
+            Some(name) if name.is_empty() => None,
+            Some(name) => Some(name),
+        }
+    }
+
+    /// The name of the member at `path`, or None if the member must not be added
+    /// (including members rejected by the path filter).
+    fn accepted_name(&self, path: &Path) -> Option<String> {
+        let name = self.name(path)?;
+        match self.path_filter {
+            Some(path_filter) if !path_filter.accept_name(&name) => {
+                debug!("Skip {name} (filtered out)");
+                None
+            }
+            _ => Some(name),
+        }
+    }
+
+    /// First pass, record the member `index` (a file or a link) at `path`.
+    fn scan_member(&mut self, index: usize, path: &Path) {
+        if let Some(name) = self.accepted_name(path) {
+            if self.last_members.insert(name.clone(), index).is_some() {
+                debug!("{name} is present several times, keep the last one");
+            }
+            self.names.insert(index, name);
+        }
+    }
+
+    /// First pass, record the directory at `path`.
+    fn scan_dir(&mut self, path: &Path) {
+        if let Some(name) = self.name(path) {
+            self.dirs.insert(name);
+        }
+    }
+
+    /// Second pass, the name of the member `index` if it must be added.
+    fn name_to_add(&self, index: usize) -> Option<String> {
+        let name = self.names.get(&index)?;
+        (self.last_members.get(name) == Some(&index)).then(|| name.clone())
+    }
+
+    fn add_file(
+        &mut self,
+        name: String,
+        mut reader: SpooledMember,
+        mtime: Option<u64>,
+        adder: &mut impl ContentAdder,
+    ) -> Void {
+        let mime_type = match self.creator.mime_type(&name) {
+            Some(mime_type) => mime_type,
+            None => {
+                let mut head = Vec::with_capacity(SNIFF_LEN);
+                (&mut reader)
+                    .take(SNIFF_LEN as u64)
+                    .read_to_end(&mut head)?;
+                reader.seek(SeekFrom::Start(0))?;
+                sniff_mime_type(&head)
+            }
+        };
+        let mut parent = name.as_str();
+        while let Some((dir, _)) = parent.rsplit_once('/') {
+            self.dirs.insert(dir.to_owned());
+            parent = dir;
+        }
+        let entry =
+            ContentEntry::from_reader(self.creator, name.clone(), reader, mime_type, mtime, adder)?;
+        self.members.insert(name, Member::File(entry));
+        Ok(())
+    }
+
+    fn add_symlink(&mut self, name: String, target: &Path) {
+        match target.to_str() {
+            None => warn!("Skip {name} (target is not utf8)"),
+            Some(target) => {
+                self.members
+                    .insert(name, Member::Symlink(target.to_owned()));
+            }
+        }
+    }
+
+    /// Add a hard link to the member at `target`, as a alias.
+    fn add_hard_link(&mut self, name: String, target: &Path) {
+        if let Some(target) = self.name(target) {
+            self.members.insert(name, Member::HardLink(target));
+        }
+    }
+
+    /// Add the members, once all the directories are known.
+    fn finish(mut self) -> Void {
+        for (path, member) in &self.members {
+            match member {
+                Member::File(entry) => self.creator.add_entry(entry)?,
+                Member::Symlink(target) => {
+                    let resolved = resolve_path(path, target);
+                    if self.dirs.contains(resolved.trim_end_matches('/')) {
+                        continue;
+                    }
+                    let redirect = RedirectEntry {
+                        path: path.clone(),
+                        target: target.clone(),
+                        status: Default::default(),
+                        external: false,
+                    };
+                    self.creator.add_entry(&redirect)?;
+                }
+                Member::HardLink(target) => {
+                    if !matches!(self.members.get(target), Some(Member::File(_))) {
+                        warn!("Skip {path} (hard link to {target} which is not a file)");
+                        continue;
+                    }
+                    // A hard link shares the modification time of its target.
+                    let alias = AliasEntry {
+                        path: path.clone(),
+                        target: target.clone(),
+                        mtime: None,
+                    };
+                    self.creator.add_entry(&alias)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add the members of a tar archive.
+///
+/// Regular files are added as contents, symlinks as redirects and hard links as aliases.
+pub struct TarAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+    namer: &'a dyn Namer,
+    path_filter: Option<&'a PathFilter>,
+}
+
+impl<'a> TarAdder<'a> {
+    pub fn new(creator: &'a mut EntryStoreCreator, namer: &'a dyn Namer) -> Self {
+        Self {
+            creator,
+            namer,
+            path_filter: None,
+        }
+    }
+
+    /// Only add the members accepted by `path_filter`.
+    pub fn set_path_filter(&mut self, path_filter: &'a PathFilter) {
+        self.path_filter = Some(path_filter);
+    }
+
+    /// Add the members of the tar file at `path`. The file may be gzip compressed.
+    pub fn add_from_path(&mut self, path: &Path, adder: &mut impl ContentAdder) -> Void {
+        self.add_from_file(&File::open(path)?, adder)
+    }
+
+    /// Add the members of a tar stream (as stdin). The stream may be gzip compressed.
+    ///
+    /// The stream is spooled in a temporary file, as the archive is read twice.
+    pub fn add_from_reader(
+        &mut self,
+        mut reader: impl Read,
+        adder: &mut impl ContentAdder,
+    ) -> Void {
+        let mut file = tempfile::tempfile()?;
+        std::io::copy(&mut reader, &mut file)?;
+        self.add_from_file(&file, adder)
+    }
+
+    fn add_from_file(&mut self, file: &File, adder: &mut impl ContentAdder) -> Void {
+        let mut member_adder = MemberAdder::new(self.creator, self.namer, self.path_filter);
+        let mut archive = open_tar(file)?;
+        for (index, member) in archive.entries()?.enumerate() {
+            let member = member?;
+            let path = member.path()?;
+            let entry_type = member.header().entry_type();
+            if entry_type.is_dir() {
+                member_adder.scan_dir(&path);
+            } else if entry_type.is_file() || entry_type.is_symlink() || entry_type.is_hard_link() {
+                member_adder.scan_member(index, &path);
+            }
+        }
+        let mut archive = open_tar(file)?;
+        for (index, member) in archive.entries()?.enumerate() {
+            let mut member = member?;
+            let name = match member_adder.name_to_add(index) {
+                None => continue,
+                Some(name) => name,
+            };
+            let entry_type = member.header().entry_type();
+            if entry_type.is_file() {
+                let mtime = member.header().mtime().ok();
+                member_adder.add_file(name, SpooledMember::new(&mut member)?, mtime, adder)?;
+            } else if entry_type.is_symlink() {
+                if let Some(target) = member.link_name()? {
+                    member_adder.add_symlink(name, &target);
+                }
+            } else if entry_type.is_hard_link() {
+                if let Some(target) = member.link_name()? {
+                    member_adder.add_hard_link(name, &target);
+                }
+            }
+        }
+        member_adder.finish()
+    }
+}
+
+/// The tar archive in `file`, read from its start. The file may be gzip compressed.
+fn open_tar(mut file: &File) -> std::io::Result<tar::Archive<Box<dyn Read + '_>>> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let reader: Box<dyn Read> = if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::bufread::MultiGzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Seconds since epoch of a (utc) zip date.
+fn zip_mtime(date: zip::DateTime) -> u64 {
+    // Days since epoch of the civil date (Howard Hinnant's algorithm).
+    let (year, month, day) = (date.year() as i64, date.month() as i64, date.day() as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    let seconds = date.hour() as i64 * 3600 + date.minute() as i64 * 60 + date.second() as i64;
+    (days * 86400 + seconds).max(0) as u64
+}
+
+/// Add the members of a zip archive.
+///
+/// Regular files are added as contents and symlinks as redirects.
+pub struct ZipAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+    namer: &'a dyn Namer,
+    path_filter: Option<&'a PathFilter>,
+}
+
+impl<'a> ZipAdder<'a> {
+    pub fn new(creator: &'a mut EntryStoreCreator, namer: &'a dyn Namer) -> Self {
+        Self {
+            creator,
+            namer,
+            path_filter: None,
+        }
+    }
+
+    /// Only add the members accepted by `path_filter`.
+    pub fn set_path_filter(&mut self, path_filter: &'a PathFilter) {
+        self.path_filter = Some(path_filter);
+    }
+
+    /// Add the members of the zip file at `path`.
+    pub fn add_from_path(&mut self, path: &Path, adder: &mut impl ContentAdder) -> Void {
+        self.add_from_reader(std::fs::File::open(path)?, adder)
+    }
+
+    pub fn add_from_reader(
+        &mut self,
+        reader: impl Read + Seek,
+        adder: &mut impl ContentAdder,
+    ) -> Void {
+        let mut member_adder = MemberAdder::new(self.creator, self.namer, self.path_filter);
+        let mut archive = zip::ZipArchive::new(reader)?;
+        // The central directory is read, not the contents.
+        for idx in 0..archive.len() {
+            let member = archive.by_index_raw(idx)?;
+            match member.enclosed_name() {
+                None => warn!("Skip {} (path outside of the archive)", member.name()),
+                Some(path) if member.is_dir() => member_adder.scan_dir(&path),
+                Some(path) => member_adder.scan_member(idx, &path),
+            }
+        }
+        for idx in 0..archive.len() {
+            let name = match member_adder.name_to_add(idx) {
+                None => continue,
+                Some(name) => name,
+            };
+            let mut member = archive.by_index(idx)?;
+            let is_symlink = member
+                .unix_mode()
+                .is_some_and(|mode| mode & S_IFMT == S_IFLNK);
+            if is_symlink {
+                // The target of a symlink is stored as the content of the member.
+                let mut data = Vec::new();
+                member.read_to_end(&mut data)?;
+                let target = String::from_utf8(data).map_err(|_| {
+                    CreatorError::InvalidZip(zip::result::ZipError::InvalidArchive(
+                        "Symlink target must be utf8".into(),
+                    ))
+                })?;
+                member_adder.add_symlink(name, Path::new(&target));
+            } else {
+                let mtime = member.last_modified().map(zip_mtime);
+                member_adder.add_file(name, SpooledMember::new(&mut member)?, mtime, adder)?;
+            }
+        }
+        member_adder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_zip_mtime() -> Result {
+        let date = zip::DateTime::from_date_and_time(2024, 3, 1, 12, 30, 15).unwrap();
+        assert_eq!(zip_mtime(date), 1709296215);
+        let date = zip::DateTime::from_date_and_time(1980, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(zip_mtime(date), 315532800);
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use crate::common::{is_valid_mimetype, mime_essence};
+use crate::create::fs_adder::TITLE_SEARCH_SIZE;
+use crate::create::{EntryKind, EntryStoreCreator, EntryTrait};
+use crate::error::CreatorError;
+use crate::{html, is_precompressible, links, Encoding};
+use jbk::creator::{CompHint, ContentAdder, InputReader};
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A content entry whose content is in memory or read from a seekable reader.
+///
+/// Used by the adders reading their contents from a stream (warc, tar, ...) and by applications
+/// generating their contents.
+pub struct ContentEntry {
+    path: String,
+    content_address: jbk::ContentAddress,
+    mime_type: String,
+    title: Option<String>,
+    text: Option<String>,
+    mtime: Option<u64>,
+    hash: [u8; 32],
+    precompressed: Vec<(Encoding, jbk::ContentAddress)>,
+    links: Vec<String>,
+}
+
+impl ContentEntry {
+    /// Add `data` with `adder` and extract the information the `creator` needs.
+    ///
+    /// Fails if `mime_type` is not a valid mimetype (see [`is_valid_mimetype`]).
+    pub fn new(
+        creator: &EntryStoreCreator,
+        path: String,
+        data: Vec<u8>,
+        mime_type: String,
+        mtime: Option<u64>,
+        adder: &mut impl ContentAdder,
+    ) -> Result<Self, CreatorError> {
+        Self::from_reader(
+            creator,
+            path,
+            std::io::Cursor::new(data),
+            mime_type,
+            mtime,
+            adder,
+        )
+    }
+
+    /// Add the content of `reader` with `adder` and extract the information the `creator` needs.
+    ///
+    /// The content is streamed. It is read in memory only if the full text, the links or
+    /// precompressed variants are needed.
+    pub fn from_reader(
+        creator: &EntryStoreCreator,
+        path: String,
+        mut reader: impl InputReader + 'static,
+        mime_type: String,
+        mtime: Option<u64>,
+        adder: &mut impl ContentAdder,
+    ) -> Result<Self, CreatorError> {
+        if !is_valid_mimetype(&mime_type) {
+            return Err(CreatorError::InvalidMimetype(mime_type));
+        }
+        let essence = mime_essence(&mime_type);
+        let is_html = essence == "text/html";
+        let with_text = is_html && creator.full_text_index_enabled();
+        let with_links = creator.link_check_enabled() && (is_html || essence == "text/css");
+        let precompress: &[Encoding] = if is_precompressible(&essence) {
+            creator.precompress()
+        } else {
+            &[]
+        };
+        let data = if with_text || with_links || !precompress.is_empty() {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            Some(data)
+        } else {
+            None
+        };
+        let title = match (is_html, &data) {
+            (false, _) => None,
+            (true, Some(data)) => html::extract_title(data),
+            (true, None) => {
+                let mut head = Vec::new();
+                (&mut reader)
+                    .take(TITLE_SEARCH_SIZE)
+                    .read_to_end(&mut head)?;
+                html::extract_title(&head)
+            }
+        };
+        let text = match &data {
+            Some(data) if with_text => Some(html::extract_text(data)),
+            _ => None,
+        };
+        let links = match &data {
+            Some(data) if with_links && is_html => links::extract_html_links(data),
+            Some(data) if with_links => links::extract_css_links(data),
+            _ => vec![],
+        };
+        let hash = match &data {
+            Some(data) => *blake3::hash(data).as_bytes(),
+            None => {
+                reader.seek(SeekFrom::Start(0))?;
+                let mut hasher = blake3::Hasher::new();
+                std::io::copy(&mut reader, &mut hasher)?;
+                *hasher.finalize().as_bytes()
+            }
+        };
+        let mut precompressed = vec![];
+        if let Some(data) = &data {
+            for encoding in precompress {
+                let compressed = encoding.compress(data)?;
+                // Don't store useless variants.
+                if compressed.len() < data.len() {
+                    let content_address = adder
+                        .add_content(Box::new(std::io::Cursor::new(compressed)), CompHint::No)?;
+                    precompressed.push((*encoding, content_address));
+                }
+            }
+        }
+        reader.seek(SeekFrom::Start(0))?;
+        let content_address = adder.add_content(Box::new(reader), CompHint::Detect)?;
+        Ok(Self {
+            path,
+            content_address,
+            mime_type,
+            title,
+            text,
+            mtime,
+            hash,
+            precompressed,
+            links,
+        })
+    }
+}
+
+impl EntryTrait for ContentEntry {
+    fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
+        Ok(Some(EntryKind::Content(
+            self.content_address,
+            self.mime_type.clone(),
+        )))
+    }
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.title.as_deref().map(Cow::Borrowed)
+    }
+    fn text(&self) -> Option<Cow<'_, str>> {
+        self.text.as_deref().map(Cow::Borrowed)
+    }
+    fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+    fn hash(&self) -> Option<[u8; 32]> {
+        Some(self.hash)
+    }
+    fn precompressed(&self) -> Vec<(Encoding, jbk::ContentAddress)> {
+        self.precompressed.clone()
+    }
+    fn links(&self) -> Vec<String> {
+        self.links.clone()
+    }
+}
+
+/// A redirect entry.
+pub struct RedirectEntry {
+    pub path: String,
+    pub target: String,
+    pub status: crate::RedirectStatus,
+    pub external: bool,
+}
+
+impl EntryTrait for RedirectEntry {
+    fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
+        Ok(Some(EntryKind::Redirect {
+            target: self.target.clone(),
+            status: self.status,
+            external: self.external,
+        }))
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+}
+
+/// A alias entry, sharing the content of the entry at `target`.
+pub struct AliasEntry {
+    pub path: String,
+    pub target: String,
+    /// The modification time of the alias, the one of the target is used if None.
+    pub mtime: Option<u64>,
+}
+
+impl EntryTrait for AliasEntry {
+    fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
+        Ok(Some(EntryKind::Alias(self.target.clone())))
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+
+    fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+}
@@ -1,22 +1,28 @@
 use std::borrow::Cow;
+use std::io::Read;
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use jbk::creator::{BasicCreator, CachedContentAdder, ConcatMode};
+use log::warn;
 
 use crate::error::CreatorError;
+use crate::{Encoding, RedirectStatus, Waj};
 
-use super::{EntryKind, EntryStoreCreator, EntryTrait, FsAdder, Namer, Void};
+use super::{
+    BrokenLink, ConflictPolicy, ContentEntry, EntryKind, EntryStoreCreator, EntryTrait, FsAdder,
+    MimeRules, Namer, PathFilter, RedirectEntry, TarAdder, Void, WajAdder, WarcAdder, ZimAdder,
+    ZipAdder,
+};
 
-struct Redirect {
+struct Whiteout {
     path: String,
-    target: String,
 }
 
-impl EntryTrait for Redirect {
+impl EntryTrait for Whiteout {
     fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
-        Ok(Some(EntryKind::Redirect(self.target.clone())))
+        Ok(Some(EntryKind::Whiteout))
     }
 
     fn name(&self) -> Cow<'_, str> {
@@ -28,6 +34,24 @@ pub struct FsCreator {
     cached_content_creator: CachedContentAdder<BasicCreator>,
     entry_store_creator: Box<EntryStoreCreator>,
     namer: Box<dyn Namer>,
+    fail_on_broken_links: bool,
+    path_filter: Option<PathFilter>,
+    // A redirect from the root (the main page) has been added.
+    has_main_page: bool,
+    compression: Option<&'static str>,
+}
+
+/// The name of the compression algorithm, as accepted on the command line.
+///
+/// Algorithms unknown to waj are not recorded.
+fn compression_name(compression: &jbk::creator::Compression) -> Option<&'static str> {
+    use jbk::creator::Compression;
+    match compression {
+        Compression::None => Some("none"),
+        Compression::Zstd(..) => Some("zstd"),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
 }
 
 impl FsCreator {
@@ -39,6 +63,7 @@ impl FsCreator {
         cache_progress: Rc<dyn jbk::creator::CacheProgress>,
         compression: jbk::creator::Compression,
     ) -> jbk::creator::Result<Self> {
+        let compression_name = compression_name(&compression);
         let basic_creator = BasicCreator::new(
             outfile,
             concat_mode,
@@ -55,26 +80,279 @@ impl FsCreator {
             cached_content_creator,
             entry_store_creator,
             namer,
+            fail_on_broken_links: false,
+            path_filter: None,
+            has_main_page: false,
+            compression: compression_name,
         })
     }
 
-    pub fn finalize(self) -> Void {
+    /// Build a full text index of the html content.
+    ///
+    /// Must be called before adding content.
+    pub fn enable_full_text_index(&mut self) {
+        self.entry_store_creator.enable_full_text_index()
+    }
+
+    /// Store precompressed variants of the text contents (html, css, js, ...) in `encodings`.
+    ///
+    /// Must be called before adding content.
+    pub fn set_precompress(&mut self, encodings: Vec<Encoding>) {
+        self.entry_store_creator.set_precompress(encodings)
+    }
+
+    /// Check the references (`href`, `src`, css `url()`, ...) of the html and css contents.
+    ///
+    /// References to paths without entry are reported by `broken_links`.
+    /// If `fail_on_broken_links` is true, `finalize` fails if there is a broken link.
+    /// Must be called before adding content.
+    pub fn enable_link_check(&mut self, fail_on_broken_links: bool) {
+        self.entry_store_creator.enable_link_check();
+        self.fail_on_broken_links = fail_on_broken_links;
+    }
+
+    /// The references of the added contents to paths without entry.
+    ///
+    /// Must be called after all entries have been added.
+    pub fn broken_links(&self) -> Vec<BrokenLink> {
+        self.entry_store_creator.broken_links()
+    }
+
+    pub fn finalize(mut self) -> Void {
+        if self.fail_on_broken_links {
+            let broken_links = self.entry_store_creator.broken_links();
+            if !broken_links.is_empty() {
+                return Err(CreatorError::BrokenLinks(broken_links.len()));
+            }
+        }
+        if let Some(compression) = self.compression {
+            self.entry_store_creator
+                .add_creation_info(crate::metadata::creation_info::COMPRESSION, compression);
+        }
+        self.entry_store_creator
+            .build_full_text_index(&mut self.cached_content_creator)?;
         Ok(self
             .cached_content_creator
             .into_inner()
             .finalize(self.entry_store_creator, vec![])?)
     }
 
+    /// Use the user defined mimetypes of `mime_rules` for the added contents.
+    pub fn set_mime_rules(&mut self, mime_rules: MimeRules) {
+        self.entry_store_creator.set_mime_rules(mime_rules);
+    }
+
+    /// Select the entries added by `add_from_path` and the tar, zip, zim and warc adders.
+    /// All entries are added by default.
+    pub fn set_path_filter(&mut self, path_filter: PathFilter) {
+        self.path_filter = Some(path_filter);
+    }
+
     pub fn add_from_path(&mut self, path: &Path) -> Void {
         let mut fs_adder = FsAdder::new(&mut self.entry_store_creator, self.namer.as_ref());
-        fs_adder.add_from_path(path, &mut self.cached_content_creator)
+        match &mut self.path_filter {
+            None => fs_adder.add_from_path(path, &mut self.cached_content_creator),
+            Some(path_filter) => {
+                let namer = self.namer.as_ref();
+                fs_adder.add_from_path_with_filter(
+                    path,
+                    |entry| path_filter.accept(entry, &namer.rename(entry.path())),
+                    &mut self.cached_content_creator,
+                )
+            }
+        }
+    }
+
+    /// Add the members of the tar file at `path` (may be gzip compressed).
+    ///
+    /// Members are named with the namer of the creator, as files added with `add_from_path`.
+    pub fn add_from_tar(&mut self, path: &Path) -> Void {
+        let mut tar_adder = TarAdder::new(&mut self.entry_store_creator, self.namer.as_ref());
+        if let Some(path_filter) = &self.path_filter {
+            tar_adder.set_path_filter(path_filter);
+        }
+        tar_adder.add_from_path(path, &mut self.cached_content_creator)
+    }
+
+    /// Add the members of a tar stream (may be gzip compressed).
+    pub fn add_from_tar_reader(&mut self, reader: impl Read) -> Void {
+        let mut tar_adder = TarAdder::new(&mut self.entry_store_creator, self.namer.as_ref());
+        if let Some(path_filter) = &self.path_filter {
+            tar_adder.set_path_filter(path_filter);
+        }
+        tar_adder.add_from_reader(reader, &mut self.cached_content_creator)
+    }
+
+    /// Add the members of the zip file at `path`.
+    ///
+    /// Members are named with the namer of the creator, as files added with `add_from_path`.
+    pub fn add_from_zip(&mut self, path: &Path) -> Void {
+        let mut zip_adder = ZipAdder::new(&mut self.entry_store_creator, self.namer.as_ref());
+        if let Some(path_filter) = &self.path_filter {
+            zip_adder.set_path_filter(path_filter);
+        }
+        zip_adder.add_from_path(path, &mut self.cached_content_creator)
+    }
+
+    /// Add the http responses stored in the warc file at `path` (may be gzip compressed).
+    pub fn add_from_warc(&mut self, path: &Path) -> Void {
+        let mut warc_adder = WarcAdder::new(&mut self.entry_store_creator);
+        if let Some(path_filter) = &self.path_filter {
+            warc_adder.set_path_filter(path_filter);
+        }
+        warc_adder.add_from_path(path, &mut self.cached_content_creator)
     }
 
-    pub fn add_redirect(&mut self, path: &str, target: &str) -> Void {
-        let redirect = Redirect {
+    /// Add the entries of the archives `inputs`, each one under its prefix.
+    ///
+    /// `policy` decides which entry (and metadata) is kept when several archives contain the same
+    /// (prefixed) path (or metadata key). Entries and metadata added before are always kept.
+    pub fn merge(&mut self, inputs: &[(&Waj, &str)], policy: ConflictPolicy) -> Void {
+        let mut waj_adder = WajAdder::new(&mut self.entry_store_creator);
+        let fail_on_conflict = policy == ConflictPolicy::Error;
+        // Keeping the last entry is keeping the first one when walking the inputs backward.
+        let inputs: Box<dyn Iterator<Item = &(&Waj, &str)>> = match policy {
+            ConflictPolicy::LastWins => Box::new(inputs.iter().rev()),
+            _ => Box::new(inputs.iter()),
+        };
+        for (waj, prefix) in inputs {
+            waj_adder.add_from_waj(
+                waj,
+                prefix,
+                fail_on_conflict,
+                &mut self.cached_content_creator,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add a content read from `reader` at `path`.
+    ///
+    /// The content is processed as the files added with `add_from_path` (title, full text,
+    /// precompressed variants, links). It is buffered in memory, use
+    /// [`ContentEntry::from_reader`] and `add_entry` to stream a seekable reader.
+    pub fn add_content(&mut self, path: &str, reader: impl Read, mime_type: &str) -> Void {
+        self.add_content_entry(path, reader, mime_type, None)
+    }
+
+    /// Same as `add_content`, with the content modified at `mtime` (in seconds since epoch).
+    pub fn add_content_with_mtime(
+        &mut self,
+        path: &str,
+        reader: impl Read,
+        mime_type: &str,
+        mtime: u64,
+    ) -> Void {
+        self.add_content_entry(path, reader, mime_type, Some(mtime))
+    }
+
+    fn add_content_entry(
+        &mut self,
+        path: &str,
+        mut reader: impl Read,
+        mime_type: &str,
+        mtime: Option<u64>,
+    ) -> Void {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let entry = ContentEntry::new(
+            &self.entry_store_creator,
+            path.into(),
+            data,
+            mime_type.into(),
+            mtime,
+            &mut self.cached_content_creator,
+        )?;
+        self.entry_store_creator.add_entry(&entry)
+    }
+
+    /// Add the content `data` at `path`.
+    pub fn add_content_bytes(
+        &mut self,
+        path: &str,
+        data: impl Into<Vec<u8>>,
+        mime_type: &str,
+    ) -> Void {
+        let entry = ContentEntry::new(
+            &self.entry_store_creator,
+            path.into(),
+            data.into(),
+            mime_type.into(),
+            None,
+            &mut self.cached_content_creator,
+        )?;
+        self.entry_store_creator.add_entry(&entry)
+    }
+
+    /// Add a entry implemented outside of this crate.
+    ///
+    /// Contents of the entry must be added with `content_adder`.
+    pub fn add_entry(&mut self, entry: &dyn EntryTrait) -> Void {
+        self.entry_store_creator.add_entry(entry)
+    }
+
+    /// The adder to use to add the contents of the entries added with `add_entry`.
+    pub fn content_adder(&mut self) -> &mut impl jbk::creator::ContentAdder {
+        &mut self.cached_content_creator
+    }
+
+    /// Add a redirect from `path` to `target`.
+    ///
+    /// If `external` is true, `target` is a absolute url outside of the archive,
+    /// else it is a path in the archive.
+    /// Fails with `CreatorError::Conflict` if a entry has already been added at `path`.
+    pub fn add_redirect(
+        &mut self,
+        path: &str,
+        target: &str,
+        status: RedirectStatus,
+        external: bool,
+    ) -> Void {
+        let redirect = RedirectEntry {
             path: path.into(),
             target: target.into(),
+            status,
+            external,
         };
+        if path.is_empty() {
+            self.has_main_page = true;
+        }
         self.entry_store_creator.add_entry(&redirect)
     }
+
+    /// Add a whiteout at `path`, hiding the entry `path` of the lower layers when served in a overlay.
+    ///
+    /// Fails with `CreatorError::Conflict` if a entry has already been added at `path`.
+    pub fn add_whiteout(&mut self, path: &str) -> Void {
+        let whiteout = Whiteout { path: path.into() };
+        self.entry_store_creator.add_entry(&whiteout)
+    }
+
+    /// Add the entries of the zim file at `path`.
+    ///
+    /// The metadata of the zim file are added as archive metadata and its main page
+    /// as a redirect from the root, if the archive has no main page yet.
+    pub fn add_from_zim(&mut self, path: &Path) -> Void {
+        let mut zim_adder = ZimAdder::new(&mut self.entry_store_creator);
+        if let Some(path_filter) = &self.path_filter {
+            zim_adder.set_path_filter(path_filter);
+        }
+        zim_adder.add_from_path(path, &mut self.cached_content_creator)?;
+        let main_page = zim_adder.main_page().map(str::to_owned);
+        match main_page {
+            Some(_) if self.has_main_page => {
+                warn!(
+                    "Main page of {} not added, the archive already has one",
+                    path.display()
+                );
+                Ok(())
+            }
+            Some(target) => self.add_redirect("", &target, RedirectStatus::Found, false),
+            None => Ok(()),
+        }
+    }
+
+    pub fn add_metadata(&mut self, key: &str, value: &str) {
+        self.entry_store_creator.add_metadata(key, value)
+    }
 }
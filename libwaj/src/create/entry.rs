@@ -7,8 +7,26 @@ use crate::common::*;
 pub struct Content {
     mimetype: Intern<SmallBytes>,
     content: jbk::ContentAddress,
+    // Seconds since epoch. 0 if unknown.
+    mtime: u64,
+    // Blake3 hash of the content. All zero if unknown.
+    hash: [u8; 32],
+}
+static_assertions::assert_eq_size!(Content, [u8; 56]);
+
+#[derive(Debug)]
+pub struct Redirect {
+    target: SmallBytes,
+    status: u16,
+    external: bool,
+}
+
+#[derive(Debug)]
+pub struct Alias {
+    target: SmallBytes,
+    // Seconds since epoch. 0 if unknown.
+    mtime: u64,
 }
-static_assertions::assert_eq_size!(Content, [u8; 16]);
 
 #[derive(Debug)]
 pub struct Entry {
@@ -22,29 +40,65 @@ pub struct Entry {
 #[derive(Debug)]
 pub enum EntryKind {
     Content(Content),
-    Redirect(SmallBytes),
+    Redirect(Redirect),
+    Alias(Alias),
+    Whiteout,
 }
-static_assertions::assert_eq_size!(Entry, [u8; 56]);
+static_assertions::assert_eq_size!(Entry, [u8; 80]);
 
 impl Entry {
     pub fn new_content(
         path: SmallBytes,
         mimetype: SmallBytes,
         content: jbk::ContentAddress,
+        mtime: Option<u64>,
+        hash: Option<[u8; 32]>,
     ) -> Self {
         Self {
             path,
             kind: EntryKind::Content(Content {
                 mimetype: Intern::new(mimetype),
                 content,
+                mtime: mtime.unwrap_or(0),
+                hash: hash.unwrap_or([0; 32]),
+            }),
+        }
+    }
+
+    pub fn new_redirect(
+        path: SmallBytes,
+        target: SmallBytes,
+        status: RedirectStatus,
+        external: bool,
+    ) -> Self {
+        Self {
+            path,
+            kind: EntryKind::Redirect(Redirect {
+                target,
+                status: status.code(),
+                external,
             }),
         }
     }
 
-    pub fn new_redirect(path: SmallBytes, target: SmallBytes) -> Self {
+    pub fn new_alias(path: SmallBytes, target: SmallBytes, mtime: Option<u64>) -> Self {
         Self {
             path,
-            kind: EntryKind::Redirect(target),
+            kind: EntryKind::Alias(Alias {
+                target,
+                mtime: mtime.unwrap_or(0),
+            }),
+        }
+    }
+
+    pub fn is_whiteout(&self) -> bool {
+        matches!(self.kind, EntryKind::Whiteout)
+    }
+
+    pub fn new_whiteout(path: SmallBytes) -> Self {
+        Self {
+            path,
+            kind: EntryKind::Whiteout,
         }
     }
 }
@@ -54,13 +108,17 @@ impl jbk::creator::EntryTrait<Property, EntryType> for Entry {
         Some(match self.kind {
             EntryKind::Content(_) => EntryType::Content,
             EntryKind::Redirect(_) => EntryType::Redirect,
+            EntryKind::Alias(_) => EntryType::Alias,
+            EntryKind::Whiteout => EntryType::Whiteout,
         })
     }
 
     fn value_count(&self) -> jbk::PropertyCount {
         match self.kind {
-            EntryKind::Content(_) => 3.into(),
-            EntryKind::Redirect(_) => 2.into(),
+            EntryKind::Content(_) => 5.into(),
+            EntryKind::Redirect(_) => 4.into(),
+            EntryKind::Alias(_) => 3.into(),
+            EntryKind::Whiteout => 1.into(),
         }
     }
 
@@ -81,9 +139,37 @@ impl jbk::creator::EntryTrait<Property, EntryType> for Entry {
                     unreachable!()
                 }
             }
-            Property::Target => {
-                if let EntryKind::Redirect(target) = &self.kind {
-                    Value::Array(target.clone())
+            Property::Mtime => match &self.kind {
+                EntryKind::Content(content) => Value::Unsigned(content.mtime),
+                EntryKind::Alias(alias) => Value::Unsigned(alias.mtime),
+                EntryKind::Redirect(_) | EntryKind::Whiteout => unreachable!(),
+            },
+            Property::Hash => {
+                if let EntryKind::Content(content) = &self.kind {
+                    if content.hash == [0; 32] {
+                        Value::Array(SmallBytes::new())
+                    } else {
+                        Value::Array(content.hash.as_slice().into())
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            Property::Target => match &self.kind {
+                EntryKind::Redirect(redirect) => Value::Array(redirect.target.clone()),
+                EntryKind::Alias(alias) => Value::Array(alias.target.clone()),
+                EntryKind::Content(_) | EntryKind::Whiteout => unreachable!(),
+            },
+            Property::Status => {
+                if let EntryKind::Redirect(redirect) = &self.kind {
+                    Value::Unsigned(redirect.status as u64)
+                } else {
+                    unreachable!()
+                }
+            }
+            Property::External => {
+                if let EntryKind::Redirect(redirect) = &self.kind {
+                    Value::Unsigned(redirect.external as u64)
                 } else {
                     unreachable!()
                 }
@@ -91,3 +177,162 @@ impl jbk::creator::EntryTrait<Property, EntryType> for Entry {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct MetadataEntry {
+    key: SmallBytes,
+    value: SmallBytes,
+}
+
+impl MetadataEntry {
+    pub fn new(key: SmallBytes, value: SmallBytes) -> Self {
+        Self { key, value }
+    }
+}
+
+impl jbk::creator::EntryTrait<MetadataProperty, EntryType> for MetadataEntry {
+    fn variant_name(&self) -> Option<EntryType> {
+        None
+    }
+
+    fn value_count(&self) -> jbk::PropertyCount {
+        2.into()
+    }
+
+    fn value(&self, name: &MetadataProperty) -> Value {
+        match name {
+            MetadataProperty::Key => Value::Array(self.key.clone()),
+            MetadataProperty::Value => Value::Array(self.value.clone()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TitleEntry {
+    pub(crate) key: SmallBytes,
+    title: SmallBytes,
+    pub(crate) path: SmallBytes,
+}
+
+impl TitleEntry {
+    pub fn new(key: SmallBytes, title: SmallBytes, path: SmallBytes) -> Self {
+        Self { key, title, path }
+    }
+}
+
+impl jbk::creator::EntryTrait<TitleProperty, EntryType> for TitleEntry {
+    fn variant_name(&self) -> Option<EntryType> {
+        None
+    }
+
+    fn value_count(&self) -> jbk::PropertyCount {
+        3.into()
+    }
+
+    fn value(&self, name: &TitleProperty) -> Value {
+        match name {
+            TitleProperty::Key => Value::Array(self.key.clone()),
+            TitleProperty::Title => Value::Array(self.title.clone()),
+            TitleProperty::Path => Value::Array(self.path.clone()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FtsWordEntry {
+    word: SmallBytes,
+    postings: jbk::ContentAddress,
+    doc_count: u64,
+}
+
+impl FtsWordEntry {
+    pub fn new(word: SmallBytes, postings: jbk::ContentAddress, doc_count: u64) -> Self {
+        Self {
+            word,
+            postings,
+            doc_count,
+        }
+    }
+}
+
+impl jbk::creator::EntryTrait<FtsWordProperty, EntryType> for FtsWordEntry {
+    fn variant_name(&self) -> Option<EntryType> {
+        None
+    }
+
+    fn value_count(&self) -> jbk::PropertyCount {
+        3.into()
+    }
+
+    fn value(&self, name: &FtsWordProperty) -> Value {
+        match name {
+            FtsWordProperty::Word => Value::Array(self.word.clone()),
+            FtsWordProperty::Postings => Value::Content(self.postings),
+            FtsWordProperty::DocCount => Value::Unsigned(self.doc_count),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FtsDocumentEntry {
+    path: SmallBytes,
+    length: u64,
+}
+
+impl FtsDocumentEntry {
+    pub fn new(path: SmallBytes, length: u64) -> Self {
+        Self { path, length }
+    }
+}
+
+impl jbk::creator::EntryTrait<FtsDocumentProperty, EntryType> for FtsDocumentEntry {
+    fn variant_name(&self) -> Option<EntryType> {
+        None
+    }
+
+    fn value_count(&self) -> jbk::PropertyCount {
+        2.into()
+    }
+
+    fn value(&self, name: &FtsDocumentProperty) -> Value {
+        match name {
+            FtsDocumentProperty::Path => Value::Array(self.path.clone()),
+            FtsDocumentProperty::Length => Value::Unsigned(self.length),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EncodedEntry {
+    pub(crate) path: SmallBytes,
+    pub(crate) encoding: SmallBytes,
+    content: jbk::ContentAddress,
+}
+
+impl EncodedEntry {
+    pub fn new(path: SmallBytes, encoding: SmallBytes, content: jbk::ContentAddress) -> Self {
+        Self {
+            path,
+            encoding,
+            content,
+        }
+    }
+}
+
+impl jbk::creator::EntryTrait<EncodingProperty, EntryType> for EncodedEntry {
+    fn variant_name(&self) -> Option<EntryType> {
+        None
+    }
+
+    fn value_count(&self) -> jbk::PropertyCount {
+        3.into()
+    }
+
+    fn value(&self, name: &EncodingProperty) -> Value {
+        match name {
+            EncodingProperty::Path => Value::Array(self.path.clone()),
+            EncodingProperty::Encoding => Value::Array(self.encoding.clone()),
+            EncodingProperty::Content => Value::Content(self.content),
+        }
+    }
+}
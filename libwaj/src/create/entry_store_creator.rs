@@ -1,20 +1,44 @@
-use super::entry::Entry;
-use crate::common::{EntryType, Property};
-use jbk::creator::{schema, EntryStore};
+use super::entry::{EncodedEntry, Entry, MetadataEntry, TitleEntry};
+use super::fts::{FullTextIndex, FullTextIndexer};
+use super::link_checker::{BrokenLink, LinkChecker};
+use crate::common::{
+    EncodingProperty, EntryType, FtsDocumentProperty, FtsWordProperty, MetadataProperty, Property,
+    TitleProperty,
+};
+use crate::error::CreatorError;
+use crate::title::normalize_title;
+use crate::Encoding;
+use jbk::creator::{schema, ContentAdder, EntryStore};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use super::{EntryKind, EntryTrait, Void};
+use super::{EntryKind, EntryTrait, MimeRules, Void};
 
 pub struct EntryStoreCreator {
     schema: schema::Schema<Property, EntryType>,
     entry_store: Vec<Entry>,
     path_store: jbk::creator::StoreHandle,
     mime_store: jbk::creator::StoreHandle,
+    hash_store: jbk::creator::StoreHandle,
+    metadata: BTreeMap<String, String>,
+    creation_info: BTreeMap<String, String>,
+    titles: Vec<TitleEntry>,
+    full_text_indexer: Option<FullTextIndexer>,
+    full_text_index: Option<FullTextIndex>,
+    precompress: Vec<Encoding>,
+    encoded: Vec<EncodedEntry>,
+    // Path of the first entry added for each (hash, mimetype).
+    contents: HashMap<([u8; 32], jbk::SmallBytes), jbk::SmallBytes>,
+    paths: HashSet<String>,
+    link_checker: Option<LinkChecker>,
+    mime_rules: MimeRules,
 }
 
 impl EntryStoreCreator {
     pub fn new(size_hint: Option<usize>) -> Self {
         let path_store = jbk::creator::ValueStore::new_plain(size_hint.map(|s| s * 2));
         let mime_store = jbk::creator::ValueStore::new_indexed();
+        // Hashes are fully stored in the entries, the store should stay empty.
+        let hash_store = jbk::creator::ValueStore::new_plain(None);
 
         let schema = schema::Schema::new(
             // Common part
@@ -28,6 +52,8 @@ impl EntryStoreCreator {
                     schema::VariantProperties::new(vec![
                         schema::Property::new_array(0, mime_store.clone(), Property::Mimetype), // the mimetype
                         schema::Property::new_content_address(Property::Content),
+                        schema::Property::new_uint(Property::Mtime), // 0 if unknown
+                        schema::Property::new_array(32, hash_store.clone(), Property::Hash), // empty if unknown
                     ]),
                 ),
                 // Redirect
@@ -35,8 +61,20 @@ impl EntryStoreCreator {
                     EntryType::Redirect,
                     schema::VariantProperties::new(vec![
                         schema::Property::new_array(1, path_store.clone(), Property::Target), // Id of the linked entry
+                        schema::Property::new_uint(Property::Status), // The http status code
+                        schema::Property::new_uint(Property::External), // 1 if target is a absolute url
                     ]),
                 ),
+                // Alias
+                (
+                    EntryType::Alias,
+                    schema::VariantProperties::new(vec![
+                        schema::Property::new_array(1, path_store.clone(), Property::Target), // Path of the content entry
+                        schema::Property::new_uint(Property::Mtime), // 0 if unknown
+                    ]),
+                ),
+                // Whiteout
+                (EntryType::Whiteout, schema::VariantProperties::new(vec![])),
             ],
             Some(vec![Property::Path]),
         );
@@ -48,12 +86,280 @@ impl EntryStoreCreator {
             schema,
             path_store,
             mime_store,
+            hash_store,
+            metadata: BTreeMap::new(),
+            creation_info: BTreeMap::new(),
+            titles: Vec::new(),
+            full_text_indexer: None,
+            full_text_index: None,
+            precompress: Vec::new(),
+            encoded: Vec::new(),
+            contents: HashMap::new(),
+            paths: HashSet::new(),
+            link_checker: None,
+            mime_rules: MimeRules::new(),
+        }
+    }
+
+    /// Set a archive level metadata.
+    ///
+    /// Setting a key twice replace the previous value.
+    pub fn add_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.into(), value.into());
+    }
+
+    pub fn has_metadata(&self, key: &str) -> bool {
+        self.metadata.contains_key(key)
+    }
+
+    /// Record a info about the archive creation, kept apart from the user metadata.
+    pub(crate) fn add_creation_info(&mut self, key: &str, value: &str) {
+        self.creation_info.insert(key.into(), value.into());
+    }
+
+    /// Whether a entry has already been added at `path`.
+    pub fn has_entry(&self, path: &str) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Index the text of the entries in a full text index.
+    ///
+    /// Must be called before adding entries.
+    pub fn enable_full_text_index(&mut self) {
+        self.full_text_indexer.get_or_insert_with(Default::default);
+    }
+
+    pub fn full_text_index_enabled(&self) -> bool {
+        self.full_text_indexer.is_some()
+    }
+
+    /// Store precompressed variants of the text contents in `encodings`.
+    ///
+    /// Must be called before adding entries.
+    pub fn set_precompress(&mut self, encodings: Vec<Encoding>) {
+        self.precompress = encodings;
+    }
+
+    pub fn precompress(&self) -> &[Encoding] {
+        &self.precompress
+    }
+
+    /// Collect the references of the contents to check them with `broken_links`.
+    ///
+    /// Must be called before adding entries.
+    pub fn enable_link_check(&mut self) {
+        self.link_checker.get_or_insert_with(Default::default);
+    }
+
+    pub fn link_check_enabled(&self) -> bool {
+        self.link_checker.is_some()
+    }
+
+    /// Use the user defined mimetypes of `mime_rules`.
+    ///
+    /// Must be called before adding entries.
+    pub fn set_mime_rules(&mut self, mime_rules: MimeRules) {
+        self.mime_rules = mime_rules;
+    }
+
+    /// The mimetype of the content at `path`, guessed from the path.
+    ///
+    /// User defined mimetypes take precedence over the mimetypes of known extensions.
+    pub fn mime_type(&self, path: &str) -> Option<String> {
+        match self.mime_rules.mime_type(path) {
+            Some(mime_type) => Some(mime_type.to_owned()),
+            None => mime_guess::from_path(path).first().map(|m| m.to_string()),
+        }
+    }
+
+    /// The references of the added contents to paths without entry (or hidden by a whiteout).
+    ///
+    /// Must be called after all entries have been added.
+    pub fn broken_links(&self) -> Vec<BrokenLink> {
+        match &self.link_checker {
+            None => vec![],
+            Some(link_checker) => {
+                let paths: HashSet<&[u8]> = self
+                    .entry_store
+                    .iter()
+                    .filter(|e| !e.is_whiteout())
+                    .map(|e| &e.path[..])
+                    .collect();
+                link_checker.broken_links(|path| paths.contains(path))
+            }
+        }
+    }
+
+    /// Write the full text index postings using `adder`.
+    ///
+    /// Must be called after all entries have been added and before finalizing.
+    pub fn build_full_text_index(&mut self, adder: &mut impl ContentAdder) -> Void {
+        if let Some(indexer) = self.full_text_indexer.take() {
+            self.full_text_index = Some(indexer.finalize(adder)?);
+        }
+        Ok(())
+    }
+
+    fn finalize_metadata(
+        metadata: BTreeMap<String, String>,
+        index_name: &str,
+        directory_pack: &mut jbk::creator::DirectoryPackCreator,
+    ) {
+        if metadata.is_empty() {
+            return;
+        }
+        let metadata_store = jbk::creator::ValueStore::new_plain(None);
+        let schema = schema::Schema::new(
+            schema::CommonProperties::new(vec![
+                schema::Property::new_array(1, metadata_store.clone(), MetadataProperty::Key),
+                schema::Property::new_array(0, metadata_store.clone(), MetadataProperty::Value),
+            ]),
+            vec![],
+            Some(vec![MetadataProperty::Key]),
+        );
+        let entry_count = metadata.len();
+        // BTreeMap is already sorted by key.
+        let entries = metadata
+            .into_iter()
+            .map(|(k, v)| MetadataEntry::new(k.as_bytes().into(), v.as_bytes().into()));
+        directory_pack.add_value_store(metadata_store);
+        let entry_store_id = directory_pack.add_entry_store(EntryStore::new(schema, entries));
+        directory_pack.create_index(
+            index_name,
+            Default::default(),
+            jbk::PropertyIdx::from(0),
+            entry_store_id,
+            jbk::EntryCount::from(entry_count as u32),
+            jbk::EntryIdx::from(0),
+        );
+    }
+
+    fn finalize_titles(
+        mut titles: Vec<TitleEntry>,
+        directory_pack: &mut jbk::creator::DirectoryPackCreator,
+    ) {
+        if titles.is_empty() {
+            return;
+        }
+        let title_store = jbk::creator::ValueStore::new_plain(Some(titles.len() * 3));
+        let schema = schema::Schema::new(
+            schema::CommonProperties::new(vec![
+                schema::Property::new_array(1, title_store.clone(), TitleProperty::Key),
+                schema::Property::new_array(0, title_store.clone(), TitleProperty::Title),
+                schema::Property::new_array(0, title_store.clone(), TitleProperty::Path),
+            ]),
+            vec![],
+            Some(vec![TitleProperty::Key]),
+        );
+        let entry_count = titles.len();
+        titles.sort_unstable_by(|a, b| a.key.cmp(&b.key).then_with(|| a.path.cmp(&b.path)));
+        directory_pack.add_value_store(title_store);
+        let entry_store_id =
+            directory_pack.add_entry_store(EntryStore::new(schema, titles.into_iter()));
+        directory_pack.create_index(
+            "waj_titles",
+            Default::default(),
+            jbk::PropertyIdx::from(0),
+            entry_store_id,
+            jbk::EntryCount::from(entry_count as u32),
+            jbk::EntryIdx::from(0),
+        );
+    }
+
+    fn finalize_encodings(
+        mut encoded: Vec<EncodedEntry>,
+        directory_pack: &mut jbk::creator::DirectoryPackCreator,
+    ) {
+        if encoded.is_empty() {
+            return;
         }
+        let encoding_store = jbk::creator::ValueStore::new_plain(Some(encoded.len()));
+        let schema = schema::Schema::new(
+            schema::CommonProperties::new(vec![
+                schema::Property::new_array(1, encoding_store.clone(), EncodingProperty::Path),
+                schema::Property::new_array(4, encoding_store.clone(), EncodingProperty::Encoding),
+                schema::Property::new_content_address(EncodingProperty::Content),
+            ]),
+            vec![],
+            Some(vec![EncodingProperty::Path]),
+        );
+        let entry_count = encoded.len();
+        encoded.sort_unstable_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then_with(|| a.encoding.cmp(&b.encoding))
+        });
+        directory_pack.add_value_store(encoding_store);
+        let entry_store_id =
+            directory_pack.add_entry_store(EntryStore::new(schema, encoded.into_iter()));
+        directory_pack.create_index(
+            "waj_encodings",
+            Default::default(),
+            jbk::PropertyIdx::from(0),
+            entry_store_id,
+            jbk::EntryCount::from(entry_count as u32),
+            jbk::EntryIdx::from(0),
+        );
     }
 
+    fn finalize_full_text(
+        full_text_index: FullTextIndex,
+        directory_pack: &mut jbk::creator::DirectoryPackCreator,
+    ) {
+        let FullTextIndex { words, documents } = full_text_index;
+        let fts_store = jbk::creator::ValueStore::new_plain(Some(words.len() + documents.len()));
+        let word_schema = schema::Schema::new(
+            schema::CommonProperties::new(vec![
+                schema::Property::new_array(1, fts_store.clone(), FtsWordProperty::Word),
+                schema::Property::new_content_address(FtsWordProperty::Postings),
+                schema::Property::new_uint(FtsWordProperty::DocCount),
+            ]),
+            vec![],
+            Some(vec![FtsWordProperty::Word]),
+        );
+        // Documents are indexed by their id (insertion order), not sorted.
+        let document_schema = schema::Schema::new(
+            schema::CommonProperties::new(vec![
+                schema::Property::new_array(1, fts_store.clone(), FtsDocumentProperty::Path),
+                schema::Property::new_uint(FtsDocumentProperty::Length),
+            ]),
+            vec![],
+            None,
+        );
+        directory_pack.add_value_store(fts_store);
+
+        // Words are already sorted.
+        let word_count = words.len();
+        let word_store_id =
+            directory_pack.add_entry_store(EntryStore::new(word_schema, words.into_iter()));
+        directory_pack.create_index(
+            "waj_fts_words",
+            Default::default(),
+            jbk::PropertyIdx::from(0),
+            word_store_id,
+            jbk::EntryCount::from(word_count as u32),
+            jbk::EntryIdx::from(0),
+        );
+
+        let document_count = documents.len();
+        let document_store_id =
+            directory_pack.add_entry_store(EntryStore::new(document_schema, documents.into_iter()));
+        directory_pack.create_index(
+            "waj_fts_documents",
+            Default::default(),
+            jbk::PropertyIdx::from(0),
+            document_store_id,
+            jbk::EntryCount::from(document_count as u32),
+            jbk::EntryIdx::from(0),
+        );
+    }
+
+    /// Add `entry` to the archive.
+    ///
+    /// Fails with `CreatorError::Conflict` if a entry has already been added at the same path.
     pub fn add_entry<E>(&mut self, entry: &E) -> Void
     where
-        E: EntryTrait,
+        E: EntryTrait + ?Sized,
     {
         let entry_kind = match entry.kind()? {
             Some(k) => k,
@@ -61,15 +367,73 @@ impl EntryStoreCreator {
                 return Ok(());
             }
         };
-        let path = entry.name().as_bytes().into();
-        let entry = match entry_kind {
-            EntryKind::Content(content_address, mimetype) => {
-                Entry::new_content(path, mimetype.as_ref().as_bytes().into(), content_address)
+        if !self.paths.insert(entry.name().into_owned()) {
+            return Err(CreatorError::Conflict(entry.name().into_owned()));
+        }
+        let path: jbk::SmallBytes = entry.name().as_bytes().into();
+        // Same content (and mimetype) than a previous entry, share it with a alias.
+        let first_path = match &entry_kind {
+            EntryKind::Content(_, mimetype) => entry.hash().and_then(|hash| {
+                match self.contents.entry((hash, mimetype.as_bytes().into())) {
+                    std::collections::hash_map::Entry::Occupied(e) => Some(e.get().clone()),
+                    std::collections::hash_map::Entry::Vacant(e) => {
+                        e.insert(path.clone());
+                        None
+                    }
+                }
+            }),
+            _ => None,
+        };
+        // The first entry of a shared content is already indexed, its aliases are not.
+        if first_path.is_none() {
+            if let Some(indexer) = self.full_text_indexer.as_mut() {
+                if let Some(text) = entry.text() {
+                    indexer.add_document(&entry.name(), &text);
+                }
             }
-            EntryKind::Redirect(target) => {
+            if let Some(title) = entry.title() {
+                self.titles.push(TitleEntry::new(
+                    normalize_title(&title).as_bytes().into(),
+                    title.as_bytes().into(),
+                    path.clone(),
+                ));
+            }
+        }
+        if let Some(link_checker) = self.link_checker.as_mut() {
+            link_checker.add_links(&entry.name(), entry.links());
+        }
+        let entry = match entry_kind {
+            EntryKind::Content(content_address, mimetype) => match first_path {
+                Some(first_path) => Entry::new_alias(path, first_path, entry.mtime()),
+                None => {
+                    for (encoding, content_address) in entry.precompressed() {
+                        self.encoded.push(EncodedEntry::new(
+                            path.clone(),
+                            encoding.name().as_bytes().into(),
+                            content_address,
+                        ));
+                    }
+                    Entry::new_content(
+                        path,
+                        mimetype.as_bytes().into(),
+                        content_address,
+                        entry.mtime(),
+                        entry.hash(),
+                    )
+                }
+            },
+            EntryKind::Redirect {
+                target,
+                status,
+                external,
+            } => {
                 let target = target.as_bytes().into();
-                Entry::new_redirect(path, target)
+                Entry::new_redirect(path, target, status, external)
             }
+            EntryKind::Alias(target) => {
+                Entry::new_alias(path, target.as_bytes().into(), entry.mtime())
+            }
+            EntryKind::Whiteout => Entry::new_whiteout(path),
         };
         self.entry_store.push(entry);
         Ok(())
@@ -81,6 +445,7 @@ impl jbk::creator::EntryStoreCreatorTrait for EntryStoreCreator {
         let entry_count = self.entry_store.len();
         directory_pack.add_value_store(self.path_store);
         directory_pack.add_value_store(self.mime_store);
+        directory_pack.add_value_store(self.hash_store);
         self.entry_store
             .sort_unstable_by(|a, b| a.path.cmp(&b.path));
         let jbk_entry_store = EntryStore::new(self.schema, self.entry_store.into_iter());
@@ -93,6 +458,13 @@ impl jbk::creator::EntryStoreCreatorTrait for EntryStoreCreator {
             jbk::EntryCount::from(entry_count as u32),
             jbk::EntryIdx::from(0),
         );
+        Self::finalize_metadata(self.metadata, "waj_metadata", directory_pack);
+        Self::finalize_metadata(self.creation_info, "waj_creation_info", directory_pack);
+        Self::finalize_titles(self.titles, directory_pack);
+        Self::finalize_encodings(self.encoded, directory_pack);
+        if let Some(full_text_index) = self.full_text_index {
+            Self::finalize_full_text(full_text_index, directory_pack);
+        }
     }
 }
 
@@ -101,7 +473,6 @@ mod tests {
     use super::super::*;
     use super::*;
     use jbk::creator::EntryStoreCreatorTrait;
-    use mime_guess::mime;
     use rustest::{test, Result};
 
     #[test]
@@ -127,7 +498,7 @@ mod tests {
         fn kind(&self) -> std::result::Result<Option<EntryKind>, CreatorError> {
             Ok(Some(EntryKind::Content(
                 jbk::ContentAddress::new(1.into(), 0.into()),
-                mime::APPLICATION_OCTET_STREAM,
+                "application/octet-stream".into(),
             )))
         }
     }
@@ -158,4 +529,30 @@ mod tests {
         assert!(!index.unwrap().is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_metadata(waj_file: rustest_fixtures::TempFile) -> Result {
+        let waj_name = waj_file.path();
+        let mut creator = jbk::creator::DirectoryPackCreator::new(
+            jbk::PackId::from(0),
+            crate::VENDOR_ID,
+            Default::default(),
+        );
+
+        let mut entry_store_creator = Box::new(EntryStoreCreator::new(None));
+        entry_store_creator.add_metadata("title", "A title");
+        entry_store_creator.add_metadata("language", "fra");
+        entry_store_creator.finalize(&mut creator);
+        {
+            let mut waj_file = waj_file.reopen()?;
+            creator.finalize()?.write(&mut waj_file)?;
+        }
+
+        let directory_pack =
+            jbk::reader::DirectoryPack::new(jbk::creator::FileSource::open(waj_name)?.into())?;
+        let index = directory_pack.get_index_from_name("waj_metadata")?;
+        assert!(index.is_some());
+        assert!(!index.unwrap().is_empty());
+        Ok(())
+    }
 }
@@ -1,15 +1,19 @@
+use super::sniff::{sniff_mime_type, SNIFF_LEN};
+use crate::common::mime_essence;
 use crate::create::{EntryKind, EntryStoreCreator, EntryTrait, Void};
 use crate::error::CreatorError;
+use crate::html;
+use crate::links;
+use crate::{is_precompressible, Encoding};
 use core::option::Option::None;
 use jbk::creator::{CompHint, ContentAdder, InputReader};
-use mime_guess::mime;
 use std::borrow::Cow;
 use std::fs;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
 pub enum FsEntryKind {
-    File(jbk::ContentAddress, mime::Mime),
+    File(jbk::ContentAddress, String),
     Link,
     Other,
 }
@@ -18,34 +22,94 @@ pub struct FsEntry {
     pub kind: FsEntryKind,
     pub path: PathBuf,
     pub name: String,
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub mtime: Option<u64>,
+    pub hash: Option<[u8; 32]>,
+    pub precompressed: Vec<(Encoding, jbk::ContentAddress)>,
+    pub links: Vec<String>,
 }
 
+// The `<title>` is expected in the head of the page.
+pub(super) const TITLE_SEARCH_SIZE: u64 = 64 * 1024;
+
 impl FsEntry {
     pub fn new_from_walk_entry(
         dir_entry: walkdir::DirEntry,
         name: String,
+        mime_type: Option<String>,
         adder: &mut impl ContentAdder,
+        with_text: bool,
+        with_links: bool,
+        precompress: &[Encoding],
     ) -> Result<Box<Self>, CreatorError> {
         let fs_path = dir_entry.path().to_path_buf();
         let attr = dir_entry.metadata().unwrap();
+        let mtime = attr
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let mut title = None;
+        let mut text = None;
+        let mut hash = None;
+        let mut precompressed = vec![];
+        let mut links = vec![];
         let kind = if attr.is_file() {
             let mut reader = jbk::creator::InputFile::open(&fs_path)?;
-            let mime_type = match mime_guess::from_path(&fs_path).first() {
+            let mime_type = match mime_type {
                 Some(m) => m,
                 None => {
-                    let mut buf = [0u8; 100];
-                    let size = std::cmp::min(100, reader.size().into_u64() as usize);
+                    let mut buf = [0u8; SNIFF_LEN];
+                    let size = std::cmp::min(SNIFF_LEN, reader.size().into_u64() as usize);
                     reader.read_exact(&mut buf[..size])?;
-                    (|| {
-                        for window in buf[..size].windows(4) {
-                            if window == b"html" {
-                                return mime::TEXT_HTML;
-                            }
-                        }
-                        mime::APPLICATION_OCTET_STREAM
-                    })()
+                    sniff_mime_type(&buf[..size])
                 }
             };
+            let essence = mime_essence(&mime_type);
+            if essence == "text/html" {
+                reader.seek(SeekFrom::Start(0))?;
+                let mut head = Vec::new();
+                if with_text || with_links {
+                    reader.read_to_end(&mut head)?;
+                    if with_text {
+                        text = Some(html::extract_text(&head));
+                    }
+                    if with_links {
+                        links = links::extract_html_links(&head);
+                    }
+                } else {
+                    (&mut reader)
+                        .take(TITLE_SEARCH_SIZE)
+                        .read_to_end(&mut head)?;
+                }
+                title = html::extract_title(&head);
+            } else if with_links && essence == "text/css" {
+                reader.seek(SeekFrom::Start(0))?;
+                let mut css = Vec::new();
+                reader.read_to_end(&mut css)?;
+                links = links::extract_css_links(&css);
+            }
+            reader.seek(SeekFrom::Start(0))?;
+            let mut hasher = blake3::Hasher::new();
+            std::io::copy(&mut reader, &mut hasher)?;
+            hash = Some(*hasher.finalize().as_bytes());
+            if !precompress.is_empty() && is_precompressible(&essence) {
+                reader.seek(SeekFrom::Start(0))?;
+                let mut data = Vec::new();
+                reader.read_to_end(&mut data)?;
+                for encoding in precompress {
+                    let compressed = encoding.compress(&data)?;
+                    // Don't store useless variants.
+                    if compressed.len() < data.len() {
+                        let content_address = adder.add_content(
+                            Box::new(std::io::Cursor::new(compressed)),
+                            CompHint::No,
+                        )?;
+                        precompressed.push((*encoding, content_address));
+                    }
+                }
+            }
             reader.seek(SeekFrom::Start(0))?;
             let content_address = adder.add_content(Box::new(reader), CompHint::Detect)?;
             FsEntryKind::File(content_address, mime_type)
@@ -58,6 +122,12 @@ impl FsEntry {
             kind,
             path: fs_path,
             name,
+            title,
+            text,
+            mtime,
+            hash,
+            precompressed,
+            links,
         }))
     }
 }
@@ -75,12 +145,14 @@ impl EntryTrait for FsEntry {
                 if abs_target.is_dir() {
                     None
                 } else {
-                    Some(EntryKind::Redirect(
-                        target
+                    Some(EntryKind::Redirect {
+                        target: target
                             .to_str()
                             .unwrap_or_else(|| panic!("{path:?} must be a utf8"))
                             .to_owned(),
-                    ))
+                        status: Default::default(),
+                        external: false,
+                    })
                 }
             }
             FsEntryKind::Other => None,
@@ -89,10 +161,35 @@ impl EntryTrait for FsEntry {
     fn name(&self) -> Cow<'_, str> {
         Cow::Borrowed(&self.name)
     }
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.title.as_deref().map(Cow::Borrowed)
+    }
+    fn text(&self) -> Option<Cow<'_, str>> {
+        self.text.as_deref().map(Cow::Borrowed)
+    }
+    fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+    fn hash(&self) -> Option<[u8; 32]> {
+        self.hash
+    }
+    fn precompressed(&self) -> Vec<(Encoding, jbk::ContentAddress)> {
+        self.precompressed.clone()
+    }
+    fn links(&self) -> Vec<String> {
+        self.links.clone()
+    }
 }
 
 pub trait Namer {
     fn rename(&self, path: &Path) -> String;
+
+    /// Rename `path`, or None if `path` cannot be named (as a path outside the stripped prefix).
+    ///
+    /// Used for paths not coming from the walked directories (archive members).
+    fn try_rename(&self, path: &Path) -> Option<String> {
+        Some(self.rename(path))
+    }
 }
 
 pub struct StripPrefix {
@@ -113,6 +210,10 @@ impl Namer for StripPrefix {
             .unwrap_or_else(|| panic!("{path:?} must be a utf8"))
             .to_owned()
     }
+
+    fn try_rename(&self, path: &Path) -> Option<String> {
+        Some(path.strip_prefix(&self.prefix).ok()?.to_str()?.to_owned())
+    }
 }
 
 pub struct FsAdder<'a> {
@@ -142,6 +243,9 @@ impl<'a> FsAdder<'a> {
         P: AsRef<std::path::Path>,
         F: FnMut(&walkdir::DirEntry) -> bool,
     {
+        let with_text = self.creator.full_text_index_enabled();
+        let with_links = self.creator.link_check_enabled();
+        let precompress = self.creator.precompress().to_vec();
         let walker = walkdir::WalkDir::new(path);
         let walker = walker.into_iter();
         for entry in walker.filter_entry(filter) {
@@ -151,7 +255,16 @@ impl<'a> FsAdder<'a> {
             if waj_path.is_empty() {
                 continue;
             }
-            let entry = FsEntry::new_from_walk_entry(entry, waj_path, adder)?;
+            let mime_type = self.creator.mime_type(&waj_path);
+            let entry = FsEntry::new_from_walk_entry(
+                entry,
+                waj_path,
+                mime_type,
+                adder,
+                with_text,
+                with_links,
+                &precompress,
+            )?;
             self.creator.add_entry(entry.as_ref())?;
         }
         Ok(())
@@ -0,0 +1,66 @@
+use super::entry::{FtsDocumentEntry, FtsWordEntry};
+use crate::error::CreatorError;
+use crate::fts::{tokenize, write_varint};
+use jbk::creator::{CompHint, ContentAdder};
+use std::collections::{BTreeMap, HashMap};
+
+/// Collect the words of the documents to build the full text index.
+#[derive(Default)]
+pub struct FullTextIndexer {
+    documents: Vec<FtsDocumentEntry>,
+    // Postings are (document id, term frequency), sorted by document id.
+    postings: BTreeMap<String, Vec<(u32, u32)>>,
+}
+
+pub struct FullTextIndex {
+    pub words: Vec<FtsWordEntry>,
+    pub documents: Vec<FtsDocumentEntry>,
+}
+
+impl FullTextIndexer {
+    pub fn add_document(&mut self, path: &str, text: &str) {
+        let doc_id = self.documents.len() as u32;
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        let mut length = 0;
+        for word in tokenize(text) {
+            *frequencies.entry(word).or_default() += 1;
+            length += 1;
+        }
+        if length == 0 {
+            return;
+        }
+        self.documents
+            .push(FtsDocumentEntry::new(path.as_bytes().into(), length));
+        for (word, frequency) in frequencies {
+            self.postings
+                .entry(word)
+                .or_default()
+                .push((doc_id, frequency));
+        }
+    }
+
+    /// Write the postings lists as content and return the entries to store in the directory pack.
+    pub fn finalize(self, adder: &mut impl ContentAdder) -> Result<FullTextIndex, CreatorError> {
+        let mut words = Vec::with_capacity(self.postings.len());
+        for (word, postings) in self.postings {
+            let mut data = vec![];
+            let mut previous_doc_id = 0;
+            for &(doc_id, frequency) in &postings {
+                write_varint(&mut data, (doc_id - previous_doc_id) as u64);
+                write_varint(&mut data, frequency as u64);
+                previous_doc_id = doc_id;
+            }
+            let content_address =
+                adder.add_content(Box::new(std::io::Cursor::new(data)), CompHint::Detect)?;
+            words.push(FtsWordEntry::new(
+                word.as_bytes().into(),
+                content_address,
+                postings.len() as u64,
+            ));
+        }
+        Ok(FullTextIndex {
+            words,
+            documents: self.documents,
+        })
+    }
+}
@@ -0,0 +1,70 @@
+use crate::links::resolve_link;
+use std::collections::HashSet;
+
+/// A reference of a content to a path which is not in the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The path of the entry containing the reference.
+    pub source: String,
+
+    /// The reference, as written in the content.
+    pub link: String,
+
+    /// The path the reference resolves to.
+    pub target: String,
+}
+
+impl std::fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: broken link to {}", self.source, self.link)
+    }
+}
+
+/// Collect the references of the added contents, to check them once all entries are added.
+#[derive(Default)]
+pub(crate) struct LinkChecker {
+    links: Vec<BrokenLink>,
+    seen: HashSet<(String, String)>,
+}
+
+impl LinkChecker {
+    /// Add the references `links` found in the entry `source`.
+    ///
+    /// References outside of the archive are ignored.
+    pub fn add_links(&mut self, source: &str, links: Vec<String>) {
+        for link in links {
+            let target = match resolve_link(source, &link) {
+                None => continue,
+                Some(target) => target,
+            };
+            // Report a missing target only once per source.
+            if self.seen.insert((source.to_owned(), target.clone())) {
+                self.links.push(BrokenLink {
+                    source: source.to_owned(),
+                    link,
+                    target,
+                });
+            }
+        }
+    }
+
+    /// The references whose target is not present, as `waj serve` would search it.
+    ///
+    /// Broken links are sorted by source path.
+    pub fn broken_links(&self, is_present: impl Fn(&[u8]) -> bool) -> Vec<BrokenLink> {
+        let mut broken_links: Vec<_> = self
+            .links
+            .iter()
+            .filter(|l| {
+                let target = l.target.as_bytes();
+                let is_directory = target.is_empty() || target.ends_with(b"/");
+                !is_present(target)
+                    && !(is_directory && is_present(format!("{}index.html", l.target).as_bytes()))
+            })
+            .cloned()
+            .collect();
+        // Entries are added in file system order.
+        broken_links.sort_by(|a, b| a.source.cmp(&b.source));
+        broken_links
+    }
+}
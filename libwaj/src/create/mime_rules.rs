@@ -0,0 +1,98 @@
+use crate::common::is_valid_mimetype;
+use crate::create::Void;
+use crate::error::CreatorError;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// User defined mimetypes of the contents, by extension or by pattern.
+///
+/// Mimetypes may have parameters (`text/html; charset=iso-8859-1`), they are checked
+/// with [`is_valid_mimetype`].
+#[derive(Default)]
+pub struct MimeRules {
+    extensions: HashMap<String, String>,
+    patterns: Vec<(Gitignore, String)>,
+}
+
+impl MimeRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `mime_type` for the entries with the extension `extension` (without the leading `.`,
+    /// case insensitive).
+    pub fn add_extension(&mut self, extension: &str, mime_type: &str) -> Void {
+        check_mimetype(mime_type)?;
+        self.extensions.insert(
+            extension.trim_start_matches('.').to_ascii_lowercase(),
+            mime_type.to_owned(),
+        );
+        Ok(())
+    }
+
+    /// Use `mime_type` for the entries matching `pattern` (gitignore syntax, matched against
+    /// the paths in the archive).
+    ///
+    /// Patterns take precedence over extensions. If several patterns match, the last added wins.
+    pub fn add_pattern(&mut self, pattern: &str, mime_type: &str) -> Void {
+        check_mimetype(mime_type)?;
+        let mut builder = GitignoreBuilder::new("");
+        builder.add_line(None, pattern)?;
+        self.patterns.push((builder.build()?, mime_type.to_owned()));
+        Ok(())
+    }
+
+    /// The user defined mimetype of the entry at `path`, if any.
+    pub fn mime_type(&self, path: &str) -> Option<&str> {
+        let by_pattern = self
+            .patterns
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern.matched_path_or_any_parents(path, false).is_ignore());
+        if let Some((_, mime_type)) = by_pattern {
+            return Some(mime_type);
+        }
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        self.extensions.get(&extension).map(String::as_str)
+    }
+}
+
+fn check_mimetype(mime_type: &str) -> Void {
+    if is_valid_mimetype(mime_type) {
+        Ok(())
+    } else {
+        Err(CreatorError::InvalidMimetype(mime_type.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_mime_rules() -> Result {
+        let mut rules = MimeRules::new();
+        rules.add_extension(".HTM", "text/html; charset=iso-8859-1")?;
+        rules.add_extension("md", "text/markdown")?;
+        rules.add_pattern("legacy/", "text/html; charset=windows-1252")?;
+        rules.add_pattern("legacy/*.md", "text/plain")?;
+        assert_eq!(
+            rules.mime_type("page.htm"),
+            Some("text/html; charset=iso-8859-1")
+        );
+        assert_eq!(rules.mime_type("docs/README.MD"), Some("text/markdown"));
+        assert_eq!(
+            rules.mime_type("legacy/old/page"),
+            Some("text/html; charset=windows-1252")
+        );
+        assert_eq!(rules.mime_type("legacy/notes.md"), Some("text/plain"));
+        assert_eq!(rules.mime_type("style.css"), None);
+        assert!(rules
+            .add_extension("txt", "text/plain\r\nX-Injected: 1")
+            .is_err());
+        assert!(rules.add_pattern("*.bin", "binary").is_err());
+        Ok(())
+    }
+}
@@ -1,17 +1,49 @@
+mod archive_adder;
+mod content_entry;
 mod creator;
 mod entry;
 mod entry_store_creator;
 mod fs_adder;
+mod fts;
+mod link_checker;
+mod mime_rules;
+mod path_filter;
+mod sniff;
+mod waj_adder;
+mod warc_adder;
+mod zim_adder;
 
+pub use crate::common::is_valid_mimetype;
 use crate::error::CreatorError;
+use crate::{Encoding, RedirectStatus};
+pub use archive_adder::{TarAdder, ZipAdder};
+pub use content_entry::{AliasEntry, ContentEntry, RedirectEntry};
 pub use creator::FsCreator;
 pub use entry_store_creator::EntryStoreCreator;
 pub use fs_adder::{FsAdder, Namer, StripPrefix};
+pub use link_checker::BrokenLink;
+pub use mime_rules::MimeRules;
+pub use path_filter::{PathFilter, IGNORE_FILENAME};
 use std::borrow::Cow;
+pub use waj_adder::{ConflictPolicy, WajAdder};
+pub use warc_adder::WarcAdder;
+pub use zim_adder::ZimAdder;
 
+/// The kind of a entry, as stored in the archive.
 pub enum EntryKind {
-    Content(jbk::ContentAddress, mime_guess::Mime),
-    Redirect(String),
+    /// A content, stored at the given address (as returned by a `ContentAdder`), and its
+    /// mimetype. The mimetype is stored as is and may have parameters (`text/html; charset=iso-8859-1`).
+    Content(jbk::ContentAddress, String),
+    Redirect {
+        target: String,
+        status: RedirectStatus,
+        /// The target is a absolute url, not a path in the archive.
+        external: bool,
+    },
+    /// Share the content of the entry at the given path, without http redirect.
+    Alias(String),
+    /// Hide the entry of the same path in the lower layers of a overlay.
+    Whiteout,
 }
 
 pub trait EntryTrait {
@@ -20,6 +52,36 @@ pub trait EntryTrait {
 
     /// Under which name the entry will be stored
     fn name(&self) -> Cow<'_, str>;
+
+    /// The title of the entry, stored in the title index.
+    fn title(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// The modification time of the content, in seconds since epoch.
+    fn mtime(&self) -> Option<u64> {
+        None
+    }
+
+    /// The blake3 hash of the content.
+    fn hash(&self) -> Option<[u8; 32]> {
+        None
+    }
+
+    /// The precompressed variants of the content.
+    fn precompressed(&self) -> Vec<(Encoding, jbk::ContentAddress)> {
+        vec![]
+    }
+
+    /// The text of the entry, indexed in the full text index.
+    fn text(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// The references (`href`, `src`, css `url()`, ...) of the content, as written in the content.
+    fn links(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
 pub type Void = Result<(), CreatorError>;
@@ -0,0 +1,130 @@
+use crate::error::CreatorError;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use log::warn;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the files listing the paths to not add, with gitignore semantics.
+pub const IGNORE_FILENAME: &str = ".wajignore";
+
+fn build_patterns(patterns: &[String]) -> Result<Gitignore, CreatorError> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder.add_line(None, pattern)?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Select the files to add while walking directories.
+///
+/// Exclude and include patterns have gitignore semantics and are matched against the entry names
+/// (the paths in the archive). If include patterns are given, only the files matching one of them
+/// are added. The `.wajignore` files found while walking are honoured as `.gitignore` files.
+pub struct PathFilter {
+    excludes: Gitignore,
+    includes: Option<Gitignore>,
+    hidden: bool,
+    // The ignore file of the directories, loaded when walking them.
+    ignore_files: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl PathFilter {
+    pub fn new(excludes: &[String], includes: &[String]) -> Result<Self, CreatorError> {
+        Ok(Self {
+            excludes: build_patterns(excludes)?,
+            includes: if includes.is_empty() {
+                None
+            } else {
+                Some(build_patterns(includes)?)
+            },
+            hidden: false,
+            ignore_files: HashMap::new(),
+        })
+    }
+
+    /// Add hidden files (files whose name starts with a `.`). Hidden files are not added by default.
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    fn ignore_file(&mut self, dir: &Path) -> Option<&Gitignore> {
+        self.ignore_files
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let path = dir.join(IGNORE_FILENAME);
+                if !path.is_file() {
+                    return None;
+                }
+                let (gitignore, error) = Gitignore::new(&path);
+                if let Some(error) = error {
+                    warn!("Invalid pattern in {}: {error}", path.display());
+                }
+                Some(gitignore)
+            })
+            .as_ref()
+    }
+
+    /// Is the entry `name`, not coming from a walked directory (archive member, crawled url),
+    /// to be added?
+    ///
+    /// `.wajignore` files don't apply here. A entry in a excluded or hidden directory is not added.
+    pub fn accept_name(&self, name: &str) -> bool {
+        if name.is_empty() {
+            return true;
+        }
+        let hidden = |component: &str| {
+            component == IGNORE_FILENAME || (!self.hidden && component.starts_with('.'))
+        };
+        if name.split('/').any(hidden) {
+            return false;
+        }
+        if self
+            .excludes
+            .matched_path_or_any_parents(name, false)
+            .is_ignore()
+        {
+            return false;
+        }
+        match &self.includes {
+            Some(includes) => includes
+                .matched_path_or_any_parents(name, false)
+                .is_ignore(),
+            None => true,
+        }
+    }
+
+    /// Is the walked `entry` (named `name` in the archive) to be added?
+    ///
+    /// Directories which are not accepted are not walked.
+    pub fn accept(&mut self, entry: &walkdir::DirEntry, name: &str) -> bool {
+        // The walked path is always added.
+        if entry.depth() == 0 {
+            return true;
+        }
+        let file_name = entry.file_name().to_string_lossy();
+        if file_name == IGNORE_FILENAME || (!self.hidden && file_name.starts_with('.')) {
+            return false;
+        }
+        let is_dir = entry.file_type().is_dir();
+        if self.excludes.matched(name, is_dir).is_ignore() {
+            return false;
+        }
+        // The deepest ignore file matching the path decides.
+        for dir in entry.path().ancestors().skip(1).take(entry.depth()) {
+            if let Some(ignore_file) = self.ignore_file(dir) {
+                match ignore_file.matched(entry.path(), is_dir) {
+                    Match::None => continue,
+                    Match::Ignore(_) => return false,
+                    Match::Whitelist(_) => break,
+                }
+            }
+        }
+        match &self.includes {
+            Some(includes) if !is_dir => includes
+                .matched_path_or_any_parents(name, false)
+                .is_ignore(),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Guess the mimetype of a content from its first bytes.
+//!
+//! This follows the "rules for identifying an unknown MIME type" of the WHATWG mime sniffing
+//! standard (https://mimesniff.spec.whatwg.org/), extended with a few formats found on the web
+//! (svg, json, wasm).
+
+/// Number of bytes of the content needed to sniff its mimetype.
+pub(crate) const SNIFF_LEN: usize = 1445;
+
+/// A byte pattern. `mask` bytes are and-ed with the content before comparison.
+struct Pattern {
+    bytes: &'static [u8],
+    mask: &'static [u8],
+    mime_type: &'static str,
+}
+
+const fn pattern(bytes: &'static [u8], mask: &'static [u8], mime_type: &'static str) -> Pattern {
+    Pattern {
+        bytes,
+        mask,
+        mime_type,
+    }
+}
+
+const FF2: &[u8] = &[0xFF; 2];
+const FF3: &[u8] = &[0xFF; 3];
+const FF4: &[u8] = &[0xFF; 4];
+const FF5: &[u8] = &[0xFF; 5];
+const FF6: &[u8] = &[0xFF; 6];
+const FF7: &[u8] = &[0xFF; 7];
+const FF8: &[u8] = &[0xFF; 8];
+const FF11: &[u8] = &[0xFF; 11];
+// Skip 4 bytes (the size of a RIFF/FORM chunk).
+const CHUNK_MASK: &[u8] = &[
+    0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+
+const PATTERNS: &[Pattern] = &[
+    pattern(b"%PDF-", FF5, "application/pdf"),
+    pattern(b"%!PS-Adobe-", FF11, "application/postscript"),
+    // Byte order marks
+    pattern(b"\xFE\xFF", FF2, "text/plain"),
+    pattern(b"\xFF\xFE", FF2, "text/plain"),
+    pattern(b"\xEF\xBB\xBF", FF3, "text/plain"),
+    // Images
+    pattern(b"\x00\x00\x01\x00", FF4, "image/x-icon"),
+    pattern(b"\x00\x00\x02\x00", FF4, "image/x-icon"),
+    pattern(b"BM", FF2, "image/bmp"),
+    pattern(b"GIF87a", FF6, "image/gif"),
+    pattern(b"GIF89a", FF6, "image/gif"),
+    pattern(
+        b"RIFF\x00\x00\x00\x00WEBPVP",
+        &[
+            0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ],
+        "image/webp",
+    ),
+    pattern(b"\x89PNG\r\n\x1A\n", FF8, "image/png"),
+    pattern(b"\xFF\xD8\xFF", FF3, "image/jpeg"),
+    // Audio and video
+    pattern(b"FORM\x00\x00\x00\x00AIFF", CHUNK_MASK, "audio/aiff"),
+    pattern(b"ID3", FF3, "audio/mpeg"),
+    pattern(b"OggS\x00", FF5, "application/ogg"),
+    pattern(b"MThd\x00\x00\x00\x06", FF8, "audio/midi"),
+    pattern(b"RIFF\x00\x00\x00\x00AVI ", CHUNK_MASK, "video/avi"),
+    pattern(b"RIFF\x00\x00\x00\x00WAVE", CHUNK_MASK, "audio/wave"),
+    // Fonts
+    pattern(b"\x00\x01\x00\x00", FF4, "font/ttf"),
+    pattern(b"OTTO", FF4, "font/otf"),
+    pattern(b"ttcf", FF4, "font/collection"),
+    pattern(b"wOFF", FF4, "font/woff"),
+    pattern(b"wOF2", FF4, "font/woff2"),
+    // Archives
+    pattern(b"\x1F\x8B\x08", FF3, "application/x-gzip"),
+    pattern(b"PK\x03\x04", FF4, "application/zip"),
+    pattern(b"Rar \x1A\x07\x00", FF7, "application/x-rar-compressed"),
+    pattern(b"\x00asm", FF4, "application/wasm"),
+];
+
+/// Tags starting a html document. They must be followed by a space or a `>`.
+const HTML_TAGS: &[&[u8]] = &[
+    b"<!DOCTYPE HTML",
+    b"<HTML",
+    b"<HEAD",
+    b"<SCRIPT",
+    b"<IFRAME",
+    b"<H1",
+    b"<DIV",
+    b"<FONT",
+    b"<TABLE",
+    b"<A",
+    b"<STYLE",
+    b"<TITLE",
+    b"<B",
+    b"<BODY",
+    b"<BR",
+    b"<P",
+    b"<!--",
+];
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | 0x0C | b'\r' | b' ')
+}
+
+fn skip_whitespaces(head: &[u8]) -> &[u8] {
+    let start = head
+        .iter()
+        .position(|b| !is_whitespace(*b))
+        .unwrap_or(head.len());
+    &head[start..]
+}
+
+fn matches_pattern(head: &[u8], pattern: &Pattern) -> bool {
+    head.len() >= pattern.bytes.len()
+        && head
+            .iter()
+            .zip(pattern.bytes.iter().zip(pattern.mask))
+            .all(|(byte, (expected, mask))| byte & mask == *expected)
+}
+
+fn is_html(head: &[u8]) -> bool {
+    let head = skip_whitespaces(head);
+    HTML_TAGS.iter().any(|tag| {
+        head.len() > tag.len()
+            && head[..tag.len()].eq_ignore_ascii_case(tag)
+            && matches!(head[tag.len()], b' ' | b'>')
+    })
+}
+
+/// A xml document, a svg image if its root element is `<svg>`.
+fn xml_mime_type(head: &[u8]) -> Option<&'static str> {
+    let head = skip_whitespaces(head);
+    if head.starts_with(b"<svg") {
+        return Some("image/svg+xml");
+    }
+    if !head.starts_with(b"<?xml") {
+        return None;
+    }
+    // Skip the prolog (declarations, comments, processing instructions) to find the root element.
+    let mut rest = head;
+    while let Some(start) = rest.iter().position(|b| *b == b'<') {
+        rest = &rest[start + 1..];
+        match rest.first() {
+            Some(b'?' | b'!') => continue,
+            _ => {
+                return Some(if rest.starts_with(b"svg") {
+                    "image/svg+xml"
+                } else {
+                    "text/xml"
+                })
+            }
+        }
+    }
+    Some("text/xml")
+}
+
+/// A mp4 file starts with a `ftyp` box with a `mp4` brand.
+fn is_mp4(head: &[u8]) -> bool {
+    if head.len() < 12 || &head[4..8] != b"ftyp" {
+        return false;
+    }
+    let box_size = u32::from_be_bytes(head[..4].try_into().unwrap()) as usize;
+    if box_size % 4 != 0 || head.len() < box_size {
+        return false;
+    }
+    if &head[8..11] == b"mp4" {
+        return true;
+    }
+    // Compatible brands
+    head[16.min(box_size)..box_size]
+        .chunks_exact(4)
+        .any(|brand| &brand[..3] == b"mp4")
+}
+
+/// A webm file is a EBML document with a `webm` doctype (`0x4282`, a one byte size, `webm`).
+fn is_webm(head: &[u8]) -> bool {
+    head.starts_with(b"\x1A\x45\xDF\xA3")
+        && head[4..]
+            .windows(7)
+            .take(34)
+            .any(|w| w[..2] == [0x42, 0x82] && &w[3..] == b"webm")
+}
+
+/// A json document, a object or a array.
+fn is_json(head: &[u8]) -> bool {
+    let head = skip_whitespaces(head);
+    let next = |rest: &[u8]| skip_whitespaces(rest).first().copied();
+    match head.first() {
+        Some(b'{') => matches!(next(&head[1..]), Some(b'"' | b'}')),
+        Some(b'[') => matches!(
+            next(&head[1..]),
+            Some(b'{' | b'[' | b'"' | b']' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n')
+        ),
+        _ => false,
+    }
+}
+
+fn is_binary(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F)
+}
+
+/// Guess the mimetype of a content from its first bytes (up to `SNIFF_LEN`).
+pub(crate) fn sniff_mime_type(head: &[u8]) -> String {
+    let head = &head[..head.len().min(SNIFF_LEN)];
+    let mime_type = if is_html(head) {
+        "text/html"
+    } else if let Some(mime_type) = xml_mime_type(head) {
+        mime_type
+    } else if let Some(pattern) = PATTERNS.iter().find(|p| matches_pattern(head, p)) {
+        pattern.mime_type
+    } else if is_mp4(head) {
+        "video/mp4"
+    } else if is_webm(head) {
+        "video/webm"
+    } else if head.iter().any(|b| is_binary(*b)) {
+        "application/octet-stream"
+    } else if is_json(head) {
+        "application/json"
+    } else {
+        "text/plain"
+    };
+    mime_type.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_sniff_mime_type() -> Result {
+        let cases: &[(&[u8], &str)] = &[
+            (b"  <!doctype html><html></html>", "text/html"),
+            (b"<HTML>", "text/html"),
+            (b"<p>Some text</p>", "text/html"),
+            (b"<!-- comment -->", "text/html"),
+            (b"<?xml version=\"1.0\"?><rss></rss>", "text/xml"),
+            (
+                b"<?xml version=\"1.0\"?>\n<!DOCTYPE svg><svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+                "image/svg+xml",
+            ),
+            (b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", "image/svg+xml"),
+            (b"%PDF-1.7", "application/pdf"),
+            (b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR", "image/png"),
+            (b"\xFF\xD8\xFF\xE0\x00\x10JFIF", "image/jpeg"),
+            (b"GIF89a\x01\x00", "image/gif"),
+            (b"RIFF\x24\x00\x00\x00WEBPVP8 ", "image/webp"),
+            (b"RIFF\x24\x00\x00\x00WAVEfmt ", "audio/wave"),
+            (b"ID3\x03\x00", "audio/mpeg"),
+            (b"OggS\x00\x02", "application/ogg"),
+            (
+                b"\x00\x00\x00\x18ftypisom\x00\x00\x02\x00mp41isom",
+                "video/mp4",
+            ),
+            (
+                b"\x1A\x45\xDF\xA3\x9F\x42\x86\x81\x01\x42\x82\x84webm",
+                "video/webm",
+            ),
+            (b"wOF2\x00\x01\x00\x00", "font/woff2"),
+            (b"\x00\x01\x00\x00\x00\x0F", "font/ttf"),
+            (b"\x00asm\x01\x00\x00\x00", "application/wasm"),
+            (b"\x1F\x8B\x08\x00", "application/x-gzip"),
+            (b"PK\x03\x04\x14\x00", "application/zip"),
+            (b" {\"key\": [1, 2]}", "application/json"),
+            (b"[{\"a\": 1}]", "application/json"),
+            (b"[Section]\nkey=value", "text/plain"),
+            (b"Just some text, mentioning html.", "text/plain"),
+            (b"", "text/plain"),
+            (b"\x00\x01\x02\x03binary", "application/octet-stream"),
+        ];
+        for (head, expected) in cases {
+            assert_eq!(
+                sniff_mime_type(head),
+                *expected,
+                "{}",
+                String::from_utf8_lossy(head)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use crate::common::mime_essence;
+use crate::create::fs_adder::TITLE_SEARCH_SIZE;
+use crate::create::{EntryKind, EntryStoreCreator, EntryTrait, Void};
+use crate::error::{CreatorError, WajError};
+use crate::html;
+use crate::{
+    is_precompressible, CommonEntry, Content, Encoding, Entry, FullBuilder, RedirectStatus, Waj,
+};
+use jbk::creator::{CompHint, ContentAdder};
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+
+/// What to do when several merged archives contain the same path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the entry of the first archive.
+    #[default]
+    FirstWins,
+
+    /// Keep the entry of the last archive.
+    LastWins,
+
+    /// Fail the merge.
+    Error,
+}
+
+enum MergedKind {
+    Content(jbk::ContentAddress, String),
+    Redirect {
+        target: String,
+        status: RedirectStatus,
+        external: bool,
+    },
+    Whiteout,
+}
+
+struct MergedEntry {
+    kind: MergedKind,
+    path: String,
+    title: Option<String>,
+    text: Option<String>,
+    mtime: Option<u64>,
+    hash: Option<[u8; 32]>,
+    precompressed: Vec<(Encoding, jbk::ContentAddress)>,
+}
+
+impl EntryTrait for MergedEntry {
+    fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
+        Ok(Some(match &self.kind {
+            MergedKind::Content(content_address, mime) => {
+                EntryKind::Content(*content_address, mime.clone())
+            }
+            MergedKind::Redirect {
+                target,
+                status,
+                external,
+            } => EntryKind::Redirect {
+                target: target.clone(),
+                status: *status,
+                external: *external,
+            },
+            MergedKind::Whiteout => EntryKind::Whiteout,
+        }))
+    }
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.title.as_deref().map(Cow::Borrowed)
+    }
+    fn text(&self) -> Option<Cow<'_, str>> {
+        self.text.as_deref().map(Cow::Borrowed)
+    }
+    fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+    fn hash(&self) -> Option<[u8; 32]> {
+        self.hash
+    }
+    fn precompressed(&self) -> Vec<(Encoding, jbk::ContentAddress)> {
+        self.precompressed.clone()
+    }
+}
+
+fn merged_content(
+    waj: &Waj,
+    content: &Content,
+    path: String,
+    with_text: bool,
+    precompress: &[Encoding],
+    adder: &mut impl ContentAdder,
+) -> Result<MergedEntry, CreatorError> {
+    let source_path = String::from_utf8_lossy(content.path());
+    let mut reader = waj.read_entry(&source_path)?;
+    let mime_type = String::from_utf8_lossy(content.mimetype()).into_owned();
+    let mut title = None;
+    let mut text = None;
+    if mime_essence(&mime_type) == "text/html" {
+        let mut head = Vec::new();
+        if with_text {
+            reader.read_to_end(&mut head)?;
+            text = Some(html::extract_text(&head));
+        } else {
+            (&mut reader)
+                .take(TITLE_SEARCH_SIZE)
+                .read_to_end(&mut head)?;
+        }
+        title = html::extract_title(&head);
+    }
+    let hash = match content.hash() {
+        Some(hash) => hash,
+        None => {
+            reader.seek(SeekFrom::Start(0))?;
+            let mut hasher = blake3::Hasher::new();
+            std::io::copy(&mut reader, &mut hasher)?;
+            *hasher.finalize().as_bytes()
+        }
+    };
+    let mut precompressed = vec![];
+    if !precompress.is_empty() && is_precompressible(&mime_type) {
+        let existing = waj.precompressed_variants(&source_path)?;
+        let mut data = None;
+        for encoding in precompress {
+            // Reuse the variant of the source archive if any.
+            let existing_bytes = match existing.iter().find(|(e, _)| e == encoding) {
+                None => None,
+                Some((_, content_address)) => waj
+                    .get_bytes(*content_address)
+                    .map_err(WajError::from)?
+                    .and_then(|m| m.transpose()),
+            };
+            let compressed = match existing_bytes {
+                Some(jbk::reader::MayMissPack::FOUND(bytes)) => {
+                    let mut compressed = Vec::new();
+                    bytes.stream().read_to_end(&mut compressed)?;
+                    compressed
+                }
+                _ => {
+                    // Only read the whole content if a variant must be compressed.
+                    let data = match &mut data {
+                        Some(data) => data,
+                        None => {
+                            reader.seek(SeekFrom::Start(0))?;
+                            let mut buf = Vec::new();
+                            reader.read_to_end(&mut buf)?;
+                            data.insert(buf)
+                        }
+                    };
+                    encoding.compress(data)?
+                }
+            };
+            if (compressed.len() as u64) < reader.size() {
+                let content_address =
+                    adder.add_content(Box::new(std::io::Cursor::new(compressed)), CompHint::No)?;
+                precompressed.push((*encoding, content_address));
+            }
+        }
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    let content_address = adder.add_content(Box::new(reader), CompHint::Detect)?;
+    Ok(MergedEntry {
+        kind: MergedKind::Content(content_address, mime_type),
+        path,
+        title,
+        text,
+        mtime: content.mtime(),
+        hash: Some(hash),
+        precompressed,
+    })
+}
+
+/// Add the entries of existing waj archives.
+///
+/// Contents are streamed from the archives and added again (and so recompressed),
+/// even if the archives use the same compression as the created one.
+/// Duplicated contents are still stored only once.
+// TODO: Copy the compressed clusters as is when the compressions match.
+// The `ContentAdder` of jubako only accepts uncompressed contents and the reader
+// doesn't expose the raw clusters, this needs a raw cluster api in jubako.
+pub struct WajAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+}
+
+impl<'a> WajAdder<'a> {
+    pub fn new(creator: &'a mut EntryStoreCreator) -> Self {
+        Self { creator }
+    }
+
+    /// Add the entries and the metadata of `waj`, with the entry paths prefixed by `prefix`.
+    ///
+    /// Entries whose (prefixed) path has already been added are skipped,
+    /// or make the function fail if `fail_on_conflict` is true.
+    /// Metadata already set are kept.
+    pub fn add_from_waj(
+        &mut self,
+        waj: &Waj,
+        prefix: &str,
+        fail_on_conflict: bool,
+        adder: &mut impl ContentAdder,
+    ) -> Void {
+        let with_text = self.creator.full_text_index_enabled();
+        let precompress = self.creator.precompress().to_vec();
+        for (key, value) in waj.metadata()?.iter() {
+            if !self.creator.has_metadata(key) {
+                self.creator.add_metadata(key, value);
+            }
+        }
+        for entry in waj.entries_with_prefix::<FullBuilder>("")? {
+            let entry = entry?;
+            let source_path = String::from_utf8_lossy(match &entry {
+                Entry::Content(e) => e.path(),
+                Entry::Redirect(e) => e.path(),
+                Entry::Alias(e) => e.path(),
+                Entry::Whiteout(e) => e.path(),
+            })
+            .into_owned();
+            let path = format!("{prefix}{source_path}");
+            if self.creator.has_entry(&path) {
+                if fail_on_conflict {
+                    return Err(CreatorError::Conflict(path));
+                }
+                continue;
+            }
+            let merged = match entry {
+                Entry::Redirect(redirect) => {
+                    let target = String::from_utf8_lossy(redirect.target());
+                    // Relative targets stay valid, absolute ones must be moved under prefix.
+                    let target = match target.strip_prefix('/') {
+                        Some(root_target) if !redirect.is_external() => {
+                            format!("/{prefix}{root_target}")
+                        }
+                        _ => target.into_owned(),
+                    };
+                    MergedEntry {
+                        kind: MergedKind::Redirect {
+                            target,
+                            status: redirect.status(),
+                            external: redirect.is_external(),
+                        },
+                        path,
+                        title: None,
+                        text: None,
+                        mtime: None,
+                        hash: None,
+                        precompressed: vec![],
+                    }
+                }
+                // Whiteouts are kept, the merged archive may be used as a overlay layer.
+                Entry::Whiteout(_) => MergedEntry {
+                    kind: MergedKind::Whiteout,
+                    path,
+                    title: None,
+                    text: None,
+                    mtime: None,
+                    hash: None,
+                    precompressed: vec![],
+                },
+                Entry::Content(content) => {
+                    merged_content(waj, &content, path, with_text, &precompress, adder)?
+                }
+                Entry::Alias(alias) => {
+                    // Aliases are added as contents, duplicated contents are detected again.
+                    match waj.alias_content(&alias)? {
+                        Some((_, content)) => {
+                            let mut merged = merged_content(
+                                waj,
+                                &content,
+                                path,
+                                with_text,
+                                &precompress,
+                                adder,
+                            )?;
+                            merged.mtime = alias.mtime().or(merged.mtime);
+                            merged
+                        }
+                        None => continue,
+                    }
+                }
+            };
+            self.creator.add_entry(&merged)?;
+        }
+        Ok(())
+    }
+}
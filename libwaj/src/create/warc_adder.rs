@@ -0,0 +1,436 @@
+use super::content_entry::{ContentEntry, RedirectEntry};
+use super::sniff::sniff_mime_type;
+use crate::common::is_valid_mimetype;
+use crate::create::{EntryStoreCreator, PathFilter, Void};
+use crate::error::CreatorError;
+use crate::{Encoding, RedirectStatus};
+use jbk::creator::ContentAdder;
+use log::{debug, warn};
+use percent_encoding::percent_decode_str;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// The headers of a warc record or of a http response.
+struct Headers(Vec<(String, String)>);
+
+impl Headers {
+    fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Self {
+        let mut headers: Vec<(String, String)> = vec![];
+        for line in lines {
+            // Continuation of the previous header.
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_owned(), value.trim().to_owned()));
+            }
+        }
+        Self(headers)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct WarcRecord {
+    headers: Headers,
+    // Only the blocks of http responses are read, empty for the other records.
+    block: Vec<u8>,
+}
+
+/// Is the record a http response (the only records whose block is used)?
+fn is_http_response(headers: &Headers) -> bool {
+    headers.get("WARC-Type") == Some("response")
+        && headers
+            .get("Content-Type")
+            .is_some_and(|t| t.starts_with("application/http"))
+}
+
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    let line = String::from_utf8_lossy(&line);
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_owned()))
+}
+
+/// Read the next record of the warc file, or None at the end of the file.
+fn read_record(reader: &mut impl BufRead) -> Result<Option<WarcRecord>, CreatorError> {
+    // Records are separated by empty lines.
+    let version = loop {
+        match read_line(reader)? {
+            None => return Ok(None),
+            Some(line) if line.is_empty() => continue,
+            Some(line) => break line,
+        }
+    };
+    if !version.starts_with("WARC/") {
+        return Err(CreatorError::InvalidWarc(format!(
+            "Expected a record, got `{version}`"
+        )));
+    }
+    let mut lines = vec![];
+    loop {
+        match read_line(reader)? {
+            None => return Err(CreatorError::InvalidWarc("Truncated record".into())),
+            Some(line) if line.is_empty() => break,
+            Some(line) => lines.push(line),
+        }
+    }
+    let headers = Headers::parse(lines.iter().map(String::as_str));
+    let length: u64 = headers
+        .get("Content-Length")
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(|| CreatorError::InvalidWarc("Record without Content-Length".into()))?;
+    let mut block = Vec::new();
+    let read = if is_http_response(&headers) {
+        reader.take(length).read_to_end(&mut block)? as u64
+    } else {
+        std::io::copy(&mut reader.take(length), &mut std::io::sink())?
+    };
+    if read != length {
+        return Err(CreatorError::InvalidWarc("Truncated record".into()));
+    }
+    Ok(Some(WarcRecord { headers, block }))
+}
+
+/// Split a http response in its status code, its headers and its body.
+fn parse_http_response(block: &[u8]) -> Option<(u16, Headers, &[u8])> {
+    let (head_end, body_start) = match block.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(idx) => (idx, idx + 4),
+        None => {
+            let idx = block.windows(2).position(|w| w == b"\n\n")?;
+            (idx, idx + 2)
+        }
+    };
+    let head = String::from_utf8_lossy(&block[..head_end]);
+    let mut lines = head.lines();
+    let status_line = lines.next()?;
+    if !status_line.starts_with("HTTP/") {
+        return None;
+    }
+    let status = status_line.split_whitespace().nth(1)?.parse().ok()?;
+    Some((status, Headers::parse(lines), &block[body_start..]))
+}
+
+/// Decode a body sent with `Transfer-Encoding: chunked`.
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(body.len());
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        // Chunk extensions are ignored.
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(data);
+        }
+        data.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Split a absolute url in its (lowercase) host and its path (without the leading `/`).
+///
+/// The path is percent decoded, the query string is kept and the fragment removed.
+/// Directory urls (ending with a `/`) are stored as `index.html` in the directory,
+/// as `add_from_path` would store a directory with a `index.html` file.
+fn split_url(url: &str) -> Option<(String, String)> {
+    let url = url.trim().trim_start_matches('<').trim_end_matches('>');
+    let (_scheme, rest) = url.split_once("://")?;
+    let rest = rest.split_once('#').map_or(rest, |(r, _)| r);
+    let host_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let host = rest[..host_end].to_ascii_lowercase();
+    let rest = &rest[host_end..];
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+    let mut path = percent_decode_str(path.strip_prefix('/').unwrap_or(path))
+        .decode_utf8_lossy()
+        .into_owned();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index.html");
+    }
+    Some(match query {
+        Some(query) => (host, format!("{path}?{query}")),
+        None => (host, path),
+    })
+}
+
+/// Add the http responses stored in a warc file.
+///
+/// Each response is stored under the path of its url, with the `Content-Type` of the response.
+/// Only the responses of one host (the host of the first response) are added, the records of
+/// the other hosts are skipped with a warning.
+/// Responses with a 3xx status and revisit records are added as redirects.
+pub struct WarcAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+    path_filter: Option<&'a PathFilter>,
+    added_paths: HashSet<String>,
+    host: Option<String>,
+    skipped_hosts: HashSet<String>,
+    // Target uri of the records, by record id (to resolve revisit records).
+    record_uris: HashMap<String, String>,
+}
+
+impl<'a> WarcAdder<'a> {
+    pub fn new(creator: &'a mut EntryStoreCreator) -> Self {
+        Self {
+            creator,
+            path_filter: None,
+            added_paths: HashSet::new(),
+            host: None,
+            skipped_hosts: HashSet::new(),
+            record_uris: HashMap::new(),
+        }
+    }
+
+    /// Only add the records whose path is accepted by `path_filter`.
+    pub fn set_path_filter(&mut self, path_filter: &'a PathFilter) {
+        self.path_filter = Some(path_filter);
+    }
+
+    /// Add the records of the warc file at `path`. The file may be gzip compressed.
+    pub fn add_from_path(&mut self, path: &Path, adder: &mut impl ContentAdder) -> Void {
+        let mut reader = BufReader::new(std::fs::File::open(path)?);
+        let is_gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        if is_gzip {
+            self.add_from_reader(flate2::bufread::MultiGzDecoder::new(reader), adder)
+        } else {
+            self.add_from_reader(reader, adder)
+        }
+    }
+
+    /// Add the records of a (uncompressed) warc stream.
+    pub fn add_from_reader(&mut self, reader: impl Read, adder: &mut impl ContentAdder) -> Void {
+        let mut reader = BufReader::new(reader);
+        while let Some(record) = read_record(&mut reader)? {
+            self.add_record(record, adder)?;
+        }
+        Ok(())
+    }
+
+    /// The redirect target of a `Location`, and if it is external.
+    fn redirect_target(&self, location: &str) -> (String, bool) {
+        if location.contains("://") || location.starts_with("//") {
+            let url = match location.strip_prefix("//") {
+                Some(rest) => format!("http://{rest}"),
+                None => location.to_owned(),
+            };
+            match split_url(&url) {
+                Some((host, path)) if Some(&host) == self.host.as_ref() => {
+                    (format!("/{path}"), false)
+                }
+                _ => (location.to_owned(), true),
+            }
+        } else {
+            (
+                percent_decode_str(location).decode_utf8_lossy().into(),
+                false,
+            )
+        }
+    }
+
+    /// Get the path where to add the record of `uri`, or None if it must not be added.
+    fn record_path(&mut self, uri: &str) -> Option<String> {
+        let (host, path) = split_url(uri)?;
+        let main_host = self.host.get_or_insert_with(|| host.clone());
+        if host != *main_host {
+            if !self.skipped_hosts.contains(&host) {
+                warn!("Skip the records of {host} (only the records of {main_host} are added)");
+            }
+            debug!("Skip {uri} (not on host {main_host})");
+            self.skipped_hosts.insert(host);
+            return None;
+        }
+        if self.added_paths.contains(&path) {
+            debug!("Skip {uri} (already added)");
+            return None;
+        }
+        if self
+            .path_filter
+            .is_some_and(|path_filter| !path_filter.accept_name(&path))
+        {
+            debug!("Skip {uri} (filtered out)");
+            return None;
+        }
+        Some(path)
+    }
+
+    fn add_redirect(&mut self, path: String, target: String, status: RedirectStatus) -> Void {
+        let (target, external) = self.redirect_target(&target);
+        let redirect = RedirectEntry {
+            path: path.clone(),
+            target,
+            status,
+            external,
+        };
+        self.creator.add_entry(&redirect)?;
+        self.added_paths.insert(path);
+        Ok(())
+    }
+
+    fn add_response(&mut self, path: String, block: &[u8], adder: &mut impl ContentAdder) -> Void {
+        let (status, headers, body) = match parse_http_response(block) {
+            None => {
+                warn!("Invalid http response for {path}");
+                return Ok(());
+            }
+            Some(response) => response,
+        };
+        if (300..400).contains(&status) {
+            return match (RedirectStatus::from_code(status), headers.get("Location")) {
+                (Some(status), Some(location)) => {
+                    self.add_redirect(path, location.to_owned(), status)
+                }
+                _ => {
+                    debug!("Skip {path} (status {status})");
+                    Ok(())
+                }
+            };
+        }
+        if status != 200 {
+            debug!("Skip {path} (status {status})");
+            return Ok(());
+        }
+        let body = match headers.get("Transfer-Encoding") {
+            Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => match dechunk(body) {
+                None => {
+                    warn!("Invalid chunked body for {path}");
+                    return Ok(());
+                }
+                Some(body) => body,
+            },
+            _ => body.to_vec(),
+        };
+        let body = match headers.get("Content-Encoding") {
+            None => body,
+            Some(encoding) if encoding.eq_ignore_ascii_case("identity") => body,
+            Some(encoding) => match Encoding::from_name(&encoding.to_ascii_lowercase()) {
+                Some(encoding) => encoding.decompress(&body)?,
+                None => {
+                    warn!("Skip {path} (unsupported encoding {encoding})");
+                    return Ok(());
+                }
+            },
+        };
+        let mime_type = headers
+            .get("Content-Type")
+            .filter(|mime_type| {
+                let valid = is_valid_mimetype(mime_type);
+                if !valid {
+                    warn!("Ignore invalid Content-Type {mime_type:?} of {path}");
+                }
+                valid
+            })
+            .map(str::to_owned)
+            .or_else(|| self.creator.mime_type(&path))
+            .unwrap_or_else(|| sniff_mime_type(&body));
+        let mtime = headers
+            .get("Last-Modified")
+            .and_then(|d| httpdate::parse_http_date(d).ok())
+            .and_then(|d| d.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let entry = ContentEntry::new(self.creator, path.clone(), body, mime_type, mtime, adder)?;
+        self.creator.add_entry(&entry)?;
+        self.added_paths.insert(path);
+        Ok(())
+    }
+
+    fn add_record(&mut self, record: WarcRecord, adder: &mut impl ContentAdder) -> Void {
+        let headers = &record.headers;
+        let uri = match headers.get("WARC-Target-URI") {
+            None => return Ok(()),
+            Some(uri) => uri.trim_start_matches('<').trim_end_matches('>').to_owned(),
+        };
+        if let Some(record_id) = headers.get("WARC-Record-ID") {
+            self.record_uris.insert(record_id.to_owned(), uri.clone());
+        }
+        match headers.get("WARC-Type") {
+            Some("response") => {
+                if !is_http_response(headers) {
+                    return Ok(());
+                }
+                if let Some(path) = self.record_path(&uri) {
+                    self.add_response(path, &record.block, adder)?;
+                }
+            }
+            // Revisit records store a content identical to a previous record.
+            Some("revisit") => {
+                let refers_to = headers
+                    .get("WARC-Refers-To-Target-URI")
+                    .map(|u| u.trim_start_matches('<').trim_end_matches('>').to_owned())
+                    .or_else(|| {
+                        headers
+                            .get("WARC-Refers-To")
+                            .and_then(|id| self.record_uris.get(id).cloned())
+                    });
+                let refers_to = match refers_to {
+                    None => {
+                        warn!("Skip revisit of {uri} (unknown original record)");
+                        return Ok(());
+                    }
+                    Some(refers_to) => refers_to,
+                };
+                if split_url(&refers_to) == split_url(&uri) {
+                    return Ok(());
+                }
+                if let Some(path) = self.record_path(&uri) {
+                    self.add_redirect(path, refers_to, RedirectStatus::default())?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_split_url() -> Result {
+        assert_eq!(
+            split_url("https://Example.com/a/b%20c.html?x=1#top"),
+            Some(("example.com".into(), "a/b c.html?x=1".into()))
+        );
+        assert_eq!(
+            split_url("<http://example.com>"),
+            Some(("example.com".into(), "index.html".into()))
+        );
+        assert_eq!(
+            split_url("http://example.com:8080/docs/"),
+            Some(("example.com:8080".into(), "docs/index.html".into()))
+        );
+        assert_eq!(
+            split_url("http://example.com/docs/?page=2"),
+            Some(("example.com".into(), "docs/index.html?page=2".into()))
+        );
+        assert_eq!(split_url("/relative"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dechunk() -> Result {
+        assert_eq!(
+            dechunk(b"5\r\nHello\r\n7;ext=1\r\n world!\r\n0\r\n\r\n"),
+            Some(b"Hello world!".to_vec())
+        );
+        assert_eq!(dechunk(b"5\r\nHel"), None);
+        Ok(())
+    }
+}
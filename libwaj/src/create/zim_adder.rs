@@ -0,0 +1,373 @@
+use super::content_entry::{ContentEntry, RedirectEntry};
+use crate::common::is_valid_mimetype;
+use crate::create::{EntryStoreCreator, PathFilter, Void};
+use crate::error::CreatorError;
+use jbk::creator::ContentAdder;
+use log::{debug, warn};
+use mime_guess::mime;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const ZIM_MAGIC: u32 = 72173914;
+const REDIRECT_MIME: u16 = 0xffff;
+const NO_MAIN_PAGE: u32 = 0xffffffff;
+
+fn read_u8(reader: &mut impl Read) -> std::io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> std::io::Result<u16> {
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Read a zero terminated string.
+fn read_zstring(reader: &mut impl BufRead) -> std::io::Result<String> {
+    let mut buf = Vec::new();
+    reader.read_until(0, &mut buf)?;
+    if buf.last() == Some(&0) {
+        buf.pop();
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+struct Header {
+    minor_version: u16,
+    entry_count: u32,
+    cluster_count: u32,
+    path_ptr_pos: u64,
+    cluster_ptr_pos: u64,
+    mime_list_pos: u64,
+    main_page: Option<u32>,
+    checksum_pos: u64,
+}
+
+impl Header {
+    fn read(reader: &mut impl Read) -> Result<Self, CreatorError> {
+        if read_u32(reader)? != ZIM_MAGIC {
+            return Err(CreatorError::InvalidZim("Not a zim file".into()));
+        }
+        let major_version = read_u16(reader)?;
+        if major_version != 5 && major_version != 6 {
+            return Err(CreatorError::InvalidZim(format!(
+                "Unsupported version {major_version}"
+            )));
+        }
+        let minor_version = read_u16(reader)?;
+        let mut uuid = [0; 16];
+        reader.read_exact(&mut uuid)?;
+        let entry_count = read_u32(reader)?;
+        let cluster_count = read_u32(reader)?;
+        let path_ptr_pos = read_u64(reader)?;
+        let _title_ptr_pos = read_u64(reader)?;
+        let cluster_ptr_pos = read_u64(reader)?;
+        let mime_list_pos = read_u64(reader)?;
+        let main_page = read_u32(reader)?;
+        let _layout_page = read_u32(reader)?;
+        let checksum_pos = read_u64(reader)?;
+        Ok(Self {
+            // Namespaces have been merged in version 6.1
+            minor_version: if major_version == 6 { minor_version } else { 0 },
+            entry_count,
+            cluster_count,
+            path_ptr_pos,
+            cluster_ptr_pos,
+            mime_list_pos,
+            main_page: (main_page != NO_MAIN_PAGE).then_some(main_page),
+            checksum_pos,
+        })
+    }
+}
+
+enum DirentKind {
+    Content { mime: u16, cluster: u32, blob: u32 },
+    Redirect(u32),
+    // Deleted entries and link targets.
+    Other,
+}
+
+struct Dirent {
+    namespace: u8,
+    path: String,
+    kind: DirentKind,
+}
+
+impl Dirent {
+    fn read(reader: &mut impl BufRead) -> Result<Self, CreatorError> {
+        let mime = read_u16(reader)?;
+        let _parameter_len = read_u8(reader)?;
+        let namespace = read_u8(reader)?;
+        let _revision = read_u32(reader)?;
+        let kind = if mime == REDIRECT_MIME {
+            DirentKind::Redirect(read_u32(reader)?)
+        } else if mime < 0xfff0 {
+            let cluster = read_u32(reader)?;
+            let blob = read_u32(reader)?;
+            DirentKind::Content {
+                mime,
+                cluster,
+                blob,
+            }
+        } else {
+            DirentKind::Other
+        };
+        let path = match kind {
+            DirentKind::Other => String::new(),
+            _ => read_zstring(reader)?,
+        };
+        Ok(Self {
+            namespace,
+            path,
+            kind,
+        })
+    }
+}
+
+/// A decompressed cluster.
+struct Cluster {
+    data: Vec<u8>,
+    offsets: Vec<u64>,
+}
+
+impl Cluster {
+    fn new(raw: &[u8]) -> Result<Self, CreatorError> {
+        let (info, compressed) = raw
+            .split_first()
+            .ok_or_else(|| CreatorError::InvalidZim("Empty cluster".into()))?;
+        let data = match info & 0x0f {
+            0 | 1 => compressed.to_vec(),
+            4 => {
+                let mut data = Vec::new();
+                xz2::read::XzDecoder::new(compressed).read_to_end(&mut data)?;
+                data
+            }
+            5 => zstd::decode_all(compressed)?,
+            compression => {
+                return Err(CreatorError::InvalidZim(format!(
+                    "Unsupported cluster compression {compression}"
+                )))
+            }
+        };
+        let offset_size = if info & 0x10 != 0 { 8 } else { 4 };
+        let read_offset = |idx: usize| -> Option<u64> {
+            let bytes = data.get(idx * offset_size..(idx + 1) * offset_size)?;
+            Some(if offset_size == 8 {
+                u64::from_le_bytes(bytes.try_into().unwrap())
+            } else {
+                u32::from_le_bytes(bytes.try_into().unwrap()) as u64
+            })
+        };
+        let invalid = || CreatorError::InvalidZim("Invalid cluster".into());
+        let first_offset = read_offset(0).ok_or_else(invalid)?;
+        let offsets = (0..first_offset as usize / offset_size)
+            .map(|idx| read_offset(idx).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { data, offsets })
+    }
+
+    fn blob(&self, idx: u32) -> Option<&[u8]> {
+        let idx = idx as usize;
+        let start = *self.offsets.get(idx)? as usize;
+        let end = *self.offsets.get(idx + 1)? as usize;
+        self.data.get(start..end)
+    }
+}
+
+/// Add the entries of a zim file.
+///
+/// Contents are added as contents and zim redirects as redirects.
+/// The metadata of the zim file are added as archive metadata (with lowercase keys).
+/// The main page is not added, it is returned by `main_page` to be added as a redirect from the root.
+pub struct ZimAdder<'a> {
+    creator: &'a mut EntryStoreCreator,
+    path_filter: Option<&'a PathFilter>,
+    main_page: Option<String>,
+}
+
+impl<'a> ZimAdder<'a> {
+    pub fn new(creator: &'a mut EntryStoreCreator) -> Self {
+        Self {
+            creator,
+            path_filter: None,
+            main_page: None,
+        }
+    }
+
+    /// The target of the main page of the last added zim file, if any.
+    pub fn main_page(&self) -> Option<&str> {
+        self.main_page.as_deref()
+    }
+
+    /// Only add the entries whose path is accepted by `path_filter`.
+    pub fn set_path_filter(&mut self, path_filter: &'a PathFilter) {
+        self.path_filter = Some(path_filter);
+    }
+
+    /// Add the entries of the zim file at `path`.
+    pub fn add_from_path(&mut self, path: &Path, adder: &mut impl ContentAdder) -> Void {
+        self.add_from_reader(std::fs::File::open(path)?, adder)
+    }
+
+    pub fn add_from_reader(
+        &mut self,
+        reader: impl Read + Seek,
+        adder: &mut impl ContentAdder,
+    ) -> Void {
+        let mut reader = BufReader::new(reader);
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let header = Header::read(&mut reader)?;
+
+        reader.seek(SeekFrom::Start(header.mime_list_pos))?;
+        let mut mime_types = vec![];
+        loop {
+            let mime_type = read_zstring(&mut reader)?;
+            if mime_type.is_empty() {
+                break;
+            }
+            mime_types.push(mime_type);
+        }
+
+        reader.seek(SeekFrom::Start(header.path_ptr_pos))?;
+        let dirent_ptrs = (0..header.entry_count)
+            .map(|_| read_u64(&mut reader))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let mut dirents = Vec::with_capacity(dirent_ptrs.len());
+        for ptr in dirent_ptrs {
+            reader.seek(SeekFrom::Start(ptr))?;
+            dirents.push(Dirent::read(&mut reader)?);
+        }
+
+        reader.seek(SeekFrom::Start(header.cluster_ptr_pos))?;
+        let mut cluster_ptrs = (0..header.cluster_count)
+            .map(|_| read_u64(&mut reader))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        // The last cluster ends at the checksum.
+        cluster_ptrs.push(header.checksum_pos);
+
+        let entry_path = |dirent: &Dirent| -> Option<String> {
+            if header.minor_version >= 1 {
+                (dirent.namespace == b'C').then(|| dirent.path.clone())
+            } else {
+                match dirent.namespace {
+                    b'M' | b'X' | b'W' => None,
+                    namespace => Some(format!("{}/{}", namespace as char, dirent.path)),
+                }
+            }
+        };
+        let path_filter = self.path_filter;
+        let accepted = |path: &str| path_filter.is_none_or(|f| f.accept_name(path));
+
+        // Read the contents cluster by cluster.
+        let mut contents: Vec<(u32, u32, &Dirent, u16)> = dirents
+            .iter()
+            .filter_map(|dirent| match dirent.kind {
+                DirentKind::Content {
+                    mime,
+                    cluster,
+                    blob,
+                } => Some((cluster, blob, dirent, mime)),
+                _ => None,
+            })
+            .collect();
+        contents.sort_by_key(|(cluster, blob, _, _)| (*cluster, *blob));
+        let mut current_cluster: Option<(u32, Cluster)> = None;
+        for (cluster_idx, blob_idx, dirent, mime_idx) in contents {
+            let path = entry_path(dirent).filter(|path| accepted(path));
+            let is_metadata = dirent.namespace == b'M';
+            if path.is_none() && !is_metadata {
+                continue;
+            }
+            if current_cluster.as_ref().map(|(idx, _)| *idx) != Some(cluster_idx) {
+                let (start, end) = match (
+                    cluster_ptrs.get(cluster_idx as usize),
+                    cluster_ptrs.get(cluster_idx as usize + 1),
+                ) {
+                    // Don't trust the offsets to allocate.
+                    (Some(start), Some(end)) if start <= end && *end <= file_size => (*start, *end),
+                    _ => {
+                        return Err(CreatorError::InvalidZim(format!(
+                            "Invalid cluster {cluster_idx}"
+                        )))
+                    }
+                };
+                reader.seek(SeekFrom::Start(start))?;
+                let mut raw = Vec::with_capacity((end - start) as usize);
+                (&mut reader).take(end - start).read_to_end(&mut raw)?;
+                current_cluster = Some((cluster_idx, Cluster::new(&raw)?));
+            }
+            let cluster = &current_cluster.as_ref().unwrap().1;
+            let data = match cluster.blob(blob_idx) {
+                Some(data) => data.to_vec(),
+                None => {
+                    warn!("Skip {} (invalid blob {blob_idx})", dirent.path);
+                    continue;
+                }
+            };
+            if is_metadata {
+                match String::from_utf8(data) {
+                    Ok(value) => self
+                        .creator
+                        .add_metadata(&dirent.path.to_lowercase(), &value),
+                    Err(_) => debug!("Skip binary metadata {}", dirent.path),
+                }
+                continue;
+            }
+            let mime_type = mime_types
+                .get(mime_idx as usize)
+                .filter(|mime_type| is_valid_mimetype(mime_type))
+                .cloned()
+                .unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.to_string());
+            let entry =
+                ContentEntry::new(self.creator, path.unwrap(), data, mime_type, None, adder)?;
+            self.creator.add_entry(&entry)?;
+        }
+
+        let redirect_target = |idx: u32| -> Option<String> {
+            entry_path(dirents.get(idx as usize)?).map(|target| format!("/{target}"))
+        };
+        for dirent in &dirents {
+            if let DirentKind::Redirect(target_idx) = dirent.kind {
+                let (path, target) = match (entry_path(dirent), redirect_target(target_idx)) {
+                    (Some(path), Some(target)) if accepted(&path) => (path, target),
+                    _ => continue,
+                };
+                let redirect = RedirectEntry {
+                    path,
+                    target,
+                    status: Default::default(),
+                    external: false,
+                };
+                self.creator.add_entry(&redirect)?;
+            }
+        }
+        // The main page may be a redirect (`W/mainPage`) to the real main page.
+        let mut main_page = header.main_page;
+        for _ in 0..8 {
+            match main_page.and_then(|idx| dirents.get(idx as usize)) {
+                Some(Dirent {
+                    kind: DirentKind::Redirect(target_idx),
+                    ..
+                }) => main_page = Some(*target_idx),
+                _ => break,
+            }
+        }
+        self.main_page = main_page.and_then(redirect_target);
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::common::Entry;
+use crate::error::{WajError, WajFormatError};
+use crate::{CommonEntry, FullBuilder, FullEntry, Waj};
+use jbk::reader::ByteRegion;
+use std::cmp::Ordering;
+use std::io::Read;
+
+/// What changed in a entry present in both archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The entry changed from a content to a redirect, a whiteout (or the reverse).
+    Kind,
+
+    /// The mimetype of the content changed.
+    Mimetype,
+
+    /// The bytes of the content changed.
+    Content,
+
+    /// The target (or status) of the redirect changed.
+    Redirect,
+}
+
+impl ChangeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kind => "kind",
+            Self::Mimetype => "mimetype",
+            Self::Content => "content",
+            Self::Redirect => "redirect",
+        }
+    }
+}
+
+/// A difference between two archives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// The path exists only in the new archive.
+    Added(String),
+
+    /// The path exists only in the old archive.
+    Removed(String),
+
+    /// The path exists in both archives with different entries.
+    Changed(String, Vec<ChangeKind>),
+}
+
+impl Difference {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added(path) | Self::Removed(path) | Self::Changed(path, _) => path,
+        }
+    }
+}
+
+/// A entry, with alias resolved to their target content.
+enum Resolved {
+    Content {
+        mimetype: Vec<u8>,
+        hash: Option<[u8; 32]>,
+        bytes: Option<ByteRegion>,
+    },
+    Redirect {
+        target: Vec<u8>,
+        status: u16,
+    },
+    Whiteout,
+    Missing,
+}
+
+fn resolve(waj: &Waj, entry: FullEntry) -> Result<Resolved, WajError> {
+    let content = match entry {
+        Entry::Content(content) => content,
+        Entry::Alias(alias) => match waj.alias_content(&alias)? {
+            Some((_, content)) => content,
+            None => return Ok(Resolved::Missing),
+        },
+        Entry::Redirect(redirect) => {
+            return Ok(Resolved::Redirect {
+                target: redirect.target().to_vec(),
+                status: redirect.status().code(),
+            })
+        }
+        Entry::Whiteout(_) => return Ok(Resolved::Whiteout),
+    };
+    let bytes = match waj
+        .get_bytes(content.content())?
+        .and_then(|m| m.transpose())
+        .ok_or(WajFormatError("Content address not valid"))?
+    {
+        jbk::reader::MayMissPack::FOUND(bytes) => Some(bytes),
+        jbk::reader::MayMissPack::MISSING(_) => None,
+    };
+    Ok(Resolved::Content {
+        mimetype: content.mimetype().to_vec(),
+        hash: content.hash(),
+        bytes,
+    })
+}
+
+fn same_bytes(a: &ByteRegion, b: &ByteRegion) -> std::io::Result<bool> {
+    if a.size() != b.size() {
+        return Ok(false);
+    }
+    let mut a = a.stream();
+    let mut b = b.stream();
+    let mut buf_a = vec![0; 64 * 1024];
+    let mut buf_b = vec![0; 64 * 1024];
+    loop {
+        let read = a.read(&mut buf_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buf_b[..read])?;
+        if buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+fn compare(old: Resolved, new: Resolved) -> std::io::Result<Vec<ChangeKind>> {
+    Ok(match (old, new) {
+        (
+            Resolved::Content {
+                mimetype: old_mimetype,
+                hash: old_hash,
+                bytes: old_bytes,
+            },
+            Resolved::Content {
+                mimetype: new_mimetype,
+                hash: new_hash,
+                bytes: new_bytes,
+            },
+        ) => {
+            let mut changes = vec![];
+            if old_mimetype != new_mimetype {
+                changes.push(ChangeKind::Mimetype);
+            }
+            let same_content = match (old_hash, new_hash, old_bytes, new_bytes) {
+                (Some(old_hash), Some(new_hash), _, _) => old_hash == new_hash,
+                (_, _, Some(old_bytes), Some(new_bytes)) => same_bytes(&old_bytes, &new_bytes)?,
+                // We cannot read one of the content, consider it changed.
+                _ => false,
+            };
+            if !same_content {
+                changes.push(ChangeKind::Content);
+            }
+            changes
+        }
+        (
+            Resolved::Redirect {
+                target: old_target,
+                status: old_status,
+            },
+            Resolved::Redirect {
+                target: new_target,
+                status: new_status,
+            },
+        ) => {
+            if old_target != new_target || old_status != new_status {
+                vec![ChangeKind::Redirect]
+            } else {
+                vec![]
+            }
+        }
+        (Resolved::Whiteout, Resolved::Whiteout) | (Resolved::Missing, Resolved::Missing) => {
+            vec![]
+        }
+        _ => vec![ChangeKind::Kind],
+    })
+}
+
+impl Waj {
+    /// Compare the entries of this (old) archive with the ones of the `new` archive.
+    ///
+    /// Both indexes are walked in parallel (they are sorted by path).
+    /// Aliases are compared as the content they share.
+    /// Differences are returned sorted by path.
+    pub fn diff(&self, new: &Waj) -> Result<Vec<Difference>, WajError> {
+        let old = self;
+        let mut old_entries = old.entries_with_prefix::<FullBuilder>("")?.peekable();
+        let mut new_entries = new.entries_with_prefix::<FullBuilder>("")?.peekable();
+        let mut differences = vec![];
+        // Paths are compared as bytes, the order of the indexes.
+        fn path(entry: &FullEntry) -> &[u8] {
+            match entry {
+                Entry::Content(e) => e.path(),
+                Entry::Redirect(e) => e.path(),
+                Entry::Alias(e) => e.path(),
+                Entry::Whiteout(e) => e.path(),
+            }
+        }
+        let path_string = |entry: &FullEntry| String::from_utf8_lossy(path(entry)).into_owned();
+        loop {
+            let order = match (old_entries.peek(), new_entries.peek()) {
+                (None, None) => break,
+                (Some(Err(_)), _) => return Err(old_entries.next().unwrap().err().unwrap()),
+                (_, Some(Err(_))) => return Err(new_entries.next().unwrap().err().unwrap()),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(Ok(old_entry)), Some(Ok(new_entry))) => path(old_entry).cmp(path(new_entry)),
+            };
+            match order {
+                Ordering::Less => {
+                    let old_entry = old_entries.next().unwrap()?;
+                    differences.push(Difference::Removed(path_string(&old_entry)));
+                }
+                Ordering::Greater => {
+                    let new_entry = new_entries.next().unwrap()?;
+                    differences.push(Difference::Added(path_string(&new_entry)));
+                }
+                Ordering::Equal => {
+                    let old_entry = old_entries.next().unwrap()?;
+                    let new_entry = new_entries.next().unwrap()?;
+                    let entry_path = path_string(&old_entry);
+                    let changes = compare(resolve(old, old_entry)?, resolve(new, new_entry)?)?;
+                    if !changes.is_empty() {
+                        differences.push(Difference::Changed(entry_path, changes));
+                    }
+                }
+            }
+        }
+        Ok(differences)
+    }
+}
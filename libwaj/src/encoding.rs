@@ -0,0 +1,254 @@
+use crate::common::{mime_essence, partition_point, EncodingProperties, ReadEntry};
+use crate::error::BaseError;
+use jbk::reader::builder::PropertyBuilderTrait;
+use jbk::reader::Range;
+use std::io::{Read, Write};
+
+/// A content encoding in which contents can be precompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// All encodings, in order of preference.
+    pub const ALL: [Encoding; 2] = [Encoding::Brotli, Encoding::Gzip];
+
+    /// The name of the encoding, as used in `Accept-Encoding` and `Content-Encoding` headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "br" | "brotli" => Some(Self::Brotli),
+            "gzip" => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    /// Compress `data` with the best compression level.
+    pub fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+                encoder.write_all(data)?;
+                encoder.flush()?;
+                Ok(encoder.into_inner())
+            }
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    /// Decompress `data` encoded with this encoding.
+    pub fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        match self {
+            Self::Brotli => brotli::Decompressor::new(data, 4096).read_to_end(&mut decompressed)?,
+            Self::Gzip => flate2::read::MultiGzDecoder::new(data).read_to_end(&mut decompressed)?,
+        };
+        Ok(decompressed)
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s.trim()).ok_or_else(|| format!("Unknown encoding `{s}`"))
+    }
+}
+
+/// Is it worth storing precompressed variants of a content of type `mimetype` ?
+pub fn is_precompressible(mimetype: &str) -> bool {
+    matches!(
+        mime_essence(mimetype).as_str(),
+        "text/html"
+            | "text/css"
+            | "text/javascript"
+            | "application/javascript"
+            | "application/x-javascript"
+            | "image/svg+xml"
+            | "application/json"
+    )
+}
+
+/// Select the best encoding in `available` accepted by the `accept_encoding` header.
+///
+/// Encodings are selected by quality value, ties are resolved with the order of `Encoding::ALL`.
+/// Encodings not listed (and not covered by `*`) are not acceptable.
+pub(crate) fn negotiate(accept_encoding: &str, available: &[Encoding]) -> Option<Encoding> {
+    let mut explicit = vec![];
+    let mut wildcard = None;
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let coding = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        if coding.is_empty() {
+            continue;
+        }
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if coding == "*" {
+            wildcard = Some(quality);
+        } else if let Some(encoding) = Encoding::from_name(&coding) {
+            explicit.push((encoding, quality));
+        }
+    }
+    let quality = |encoding: Encoding| {
+        explicit
+            .iter()
+            .find(|(e, _)| *e == encoding)
+            .map(|(_, q)| *q)
+            .or(wildcard)
+            .unwrap_or(0.0)
+    };
+    let mut best: Option<(Encoding, f32)> = None;
+    for encoding in Encoding::ALL {
+        if !available.contains(&encoding) {
+            continue;
+        }
+        let q = quality(encoding);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((encoding, q));
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+struct EncodedBuilder<'a> {
+    properties: &'a EncodingProperties,
+}
+
+impl jbk::reader::builder::BuilderTrait for EncodedBuilder<'_> {
+    type Entry = Option<(Encoding, jbk::ContentAddress)>;
+    type Error = BaseError;
+
+    fn create_entry(&self, idx: jbk::EntryIdx) -> Result<Option<Self::Entry>, Self::Error> {
+        self.properties
+            .store
+            .get_entry_reader(idx)
+            .map(|reader| {
+                let mut encoding = jbk::SmallBytes::new();
+                self.properties
+                    .encoding_property
+                    .create(&reader)?
+                    .resolve_to_vec(&mut encoding)?;
+                let content = self.properties.content_property.create(&reader)?;
+                // Ignore encodings we don't know about.
+                Ok(std::str::from_utf8(&encoding)
+                    .ok()
+                    .and_then(Encoding::from_name)
+                    .map(|encoding| (encoding, content)))
+            })
+            .transpose()
+    }
+}
+
+/// The precompressed variants of the contents.
+pub(crate) struct EncodingIndex {
+    index: jbk::reader::Index,
+    properties: EncodingProperties,
+}
+
+impl EncodingIndex {
+    pub fn new(container: &jbk::reader::Container) -> Result<Option<Self>, BaseError> {
+        let index = match container
+            .get_directory_pack()
+            .get_index_from_name("waj_encodings")?
+        {
+            None => return Ok(None),
+            Some(index) => index,
+        };
+        let properties = EncodingProperties::new(
+            index.get_store(container.get_entry_storage())?,
+            container.get_value_storage(),
+        )?;
+        Ok(Some(Self { index, properties }))
+    }
+
+    fn path(&self, idx: jbk::EntryIdx) -> jbk::Result<jbk::SmallBytes> {
+        let reader = self
+            .properties
+            .store
+            .get_entry_reader(idx)
+            .expect("idx should be valid");
+        let mut path = jbk::SmallBytes::new();
+        self.properties
+            .path_property
+            .create(&reader)?
+            .resolve_to_vec(&mut path)?;
+        Ok(path)
+    }
+
+    /// Get the precompressed variants of the content at `path`.
+    pub fn variants(&self, path: &str) -> Result<Vec<(Encoding, jbk::ContentAddress)>, BaseError> {
+        let path = path.as_bytes();
+        let start = self.index.offset();
+        let end = start + self.index.count();
+        let first = partition_point(start, end, |idx| Ok(self.path(idx)?.as_slice() < path))?;
+        let last = partition_point(first, end, |idx| Ok(self.path(idx)?.as_slice() == path))?;
+        let builder = EncodedBuilder {
+            properties: &self.properties,
+        };
+        let variants: Vec<_> =
+            ReadEntry::new_with_bounds(first, last, &builder).collect::<Result<_, _>>()?;
+        Ok(variants.into_iter().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_precompressible() -> Result {
+        assert!(is_precompressible("text/html"));
+        assert!(is_precompressible("text/css; charset=utf-8"));
+        assert!(is_precompressible("image/svg+xml"));
+        assert!(!is_precompressible("image/png"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_negotiate() -> Result {
+        let all = &Encoding::ALL;
+        assert_eq!(negotiate("gzip, deflate, br", all), Some(Encoding::Brotli));
+        assert_eq!(negotiate("gzip, deflate", all), Some(Encoding::Gzip));
+        assert_eq!(negotiate("gzip;q=1.0, br;q=0.5", all), Some(Encoding::Gzip));
+        assert_eq!(negotiate("br;q=0, *", all), Some(Encoding::Gzip));
+        assert_eq!(negotiate("*;q=0", all), None);
+        assert_eq!(negotiate("identity", all), None);
+        assert_eq!(negotiate("", all), None);
+        assert_eq!(negotiate("br", &[Encoding::Gzip]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compress() -> Result {
+        let data = "Hello world. ".repeat(100);
+        for encoding in Encoding::ALL {
+            let compressed = encoding.compress(data.as_bytes())?;
+            assert!(compressed.len() < data.len());
+            assert_eq!(encoding.decompress(&compressed)?, data.as_bytes());
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::common::{AllProperties, Builder};
+use crate::common::{read_hash, resolve_path, AllProperties, Builder, RedirectStatus};
 use jbk::reader::builder::PropertyBuilderTrait;
 use jbk::reader::ByteSlice;
 
@@ -21,6 +21,8 @@ pub struct Content {
     common: CommonPart,
     mimetype: jbk::SmallBytes,
     content: jbk::ContentAddress,
+    mtime: Option<u64>,
+    hash: Option<[u8; 32]>,
 }
 
 impl CommonEntry for Content {
@@ -37,11 +39,23 @@ impl Content {
     pub fn mimetype(&self) -> &[u8] {
         &self.mimetype
     }
+
+    /// The modification time of the content (in seconds since epoch), if known.
+    pub fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+
+    /// The blake3 hash of the content, if known.
+    pub fn hash(&self) -> Option<[u8; 32]> {
+        self.hash
+    }
 }
 
 pub struct Redirect {
     common: CommonPart,
     target: jbk::SmallBytes,
+    status: RedirectStatus,
+    external: bool,
 }
 
 impl CommonEntry for Redirect {
@@ -54,6 +68,67 @@ impl Redirect {
     pub fn target(&self) -> &[u8] {
         &self.target
     }
+
+    /// The http status code of the redirect.
+    pub fn status(&self) -> RedirectStatus {
+        self.status
+    }
+
+    /// Is the target a absolute url outside of the archive (instead of a path in the archive) ?
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    /// The path in the archive of the target (or None if the redirect is external).
+    ///
+    /// Target is relative to the directory of the redirect (as a symlink or a html link),
+    /// or to the root of the archive if it starts with a `/`. The fragment is removed.
+    pub fn target_path(&self) -> Option<String> {
+        if self.external {
+            return None;
+        }
+        let target = String::from_utf8_lossy(&self.target);
+        let target = target.split_once('#').map_or(&*target, |(t, _)| t);
+        Some(resolve_path(&String::from_utf8_lossy(self.path()), target))
+    }
+}
+
+/// A entry sharing the content of another (content) entry.
+pub struct Alias {
+    common: CommonPart,
+    target: jbk::SmallBytes,
+    mtime: Option<u64>,
+}
+
+impl CommonEntry for Alias {
+    fn common(&self) -> &CommonPart {
+        &self.common
+    }
+}
+
+impl Alias {
+    /// The path of the entry whose content is shared.
+    pub fn target(&self) -> &[u8] {
+        &self.target
+    }
+
+    /// The modification time of the alias itself (in seconds since epoch), if known.
+    pub fn mtime(&self) -> Option<u64> {
+        self.mtime
+    }
+}
+
+/// A entry hiding the entry of the same path in the lower layers of a overlay.
+///
+/// A whiteout is never served by itself, the path is reported as not found.
+pub struct Whiteout {
+    common: CommonPart,
+}
+
+impl CommonEntry for Whiteout {
+    fn common(&self) -> &CommonPart {
+        &self.common
+    }
 }
 
 mod private {
@@ -81,6 +156,8 @@ mod private {
         common: CommonBuilder,
         mimetype_property: jbk::reader::builder::ArrayProperty,
         content_address_property: jbk::reader::builder::ContentProperty,
+        mtime_property: Option<jbk::reader::builder::IntProperty>,
+        hash_property: Option<jbk::reader::builder::ArrayProperty>,
     }
 
     impl Builder for ContentBuilder {
@@ -91,6 +168,8 @@ mod private {
                 common: CommonBuilder::new(properties),
                 mimetype_property: properties.content_mimetype_property.clone(),
                 content_address_property: properties.content_address_property,
+                mtime_property: properties.content_mtime_property.clone(),
+                hash_property: properties.content_hash_property.clone(),
             }
         }
 
@@ -98,10 +177,20 @@ mod private {
             let mimetype_prop = self.mimetype_property.create(reader)?;
             let mut mimetype = jbk::SmallBytes::new();
             mimetype_prop.resolve_to_vec(&mut mimetype)?;
+            let mtime = match &self.mtime_property {
+                None => None,
+                Some(mtime_property) => Some(mtime_property.create(reader)?).filter(|m| *m != 0),
+            };
+            let hash = match &self.hash_property {
+                None => None,
+                Some(hash_property) => read_hash(hash_property, reader)?,
+            };
             Ok(Content {
                 common: self.common.create_entry(idx, reader)?,
                 mimetype,
                 content: self.content_address_property.create(reader)?,
+                mtime,
+                hash,
             })
         }
     }
@@ -109,6 +198,8 @@ mod private {
     pub struct RedirectBuilder {
         common: CommonBuilder,
         link_property: jbk::reader::builder::ArrayProperty,
+        status_property: Option<jbk::reader::builder::IntProperty>,
+        external_property: Option<jbk::reader::builder::IntProperty>,
     }
 
     impl Builder for RedirectBuilder {
@@ -118,6 +209,8 @@ mod private {
             Self {
                 common: CommonBuilder::new(properties),
                 link_property: properties.redirect_target_property.clone(),
+                status_property: properties.redirect_status_property.clone(),
+                external_property: properties.redirect_external_property.clone(),
             }
         }
 
@@ -126,11 +219,90 @@ mod private {
             let target_prop = self.link_property.create(reader)?;
             let mut target = jbk::SmallBytes::new();
             target_prop.resolve_to_vec(&mut target)?;
-            Ok(Redirect { common, target })
+            let status = match &self.status_property {
+                None => RedirectStatus::default(),
+                Some(status_property) => {
+                    RedirectStatus::from_code(status_property.create(reader)? as u16)
+                        .unwrap_or_default()
+                }
+            };
+            let external = match &self.external_property {
+                None => false,
+                Some(external_property) => external_property.create(reader)? != 0,
+            };
+            Ok(Redirect {
+                common,
+                target,
+                status,
+                external,
+            })
+        }
+    }
+
+    pub struct AliasBuilder {
+        common: CommonBuilder,
+        target_property: Option<jbk::reader::builder::ArrayProperty>,
+        mtime_property: Option<jbk::reader::builder::IntProperty>,
+    }
+
+    impl Builder for AliasBuilder {
+        type Entry = Alias;
+
+        fn new(properties: &AllProperties) -> Self {
+            Self {
+                common: CommonBuilder::new(properties),
+                target_property: properties.alias_target_property.clone(),
+                mtime_property: properties.alias_mtime_property.clone(),
+            }
+        }
+
+        fn create_entry(&self, idx: jbk::EntryIdx, reader: &ByteSlice) -> jbk::Result<Self::Entry> {
+            let common = self.common.create_entry(idx, reader)?;
+            let target_prop = self
+                .target_property
+                .as_ref()
+                .expect("Alias entries only exist in layouts with an alias variant")
+                .create(reader)?;
+            let mut target = jbk::SmallBytes::new();
+            target_prop.resolve_to_vec(&mut target)?;
+            let mtime = match &self.mtime_property {
+                None => None,
+                Some(mtime_property) => Some(mtime_property.create(reader)?).filter(|m| *m != 0),
+            };
+            Ok(Alias {
+                common,
+                target,
+                mtime,
+            })
+        }
+    }
+
+    pub struct WhiteoutBuilder {
+        common: CommonBuilder,
+    }
+
+    impl Builder for WhiteoutBuilder {
+        type Entry = Whiteout;
+
+        fn new(properties: &AllProperties) -> Self {
+            Self {
+                common: CommonBuilder::new(properties),
+            }
+        }
+
+        fn create_entry(&self, idx: jbk::EntryIdx, reader: &ByteSlice) -> jbk::Result<Self::Entry> {
+            Ok(Whiteout {
+                common: self.common.create_entry(idx, reader)?,
+            })
         }
     }
 } // private mode
 
-pub type FullBuilder = (private::ContentBuilder, private::RedirectBuilder);
+pub type FullBuilder = (
+    private::ContentBuilder,
+    private::RedirectBuilder,
+    private::AliasBuilder,
+    private::WhiteoutBuilder,
+);
 
-pub type FullEntry = super::Entry<(Content, Redirect)>;
+pub type FullEntry = super::Entry<(Content, Redirect, Alias, Whiteout)>;
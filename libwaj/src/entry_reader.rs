@@ -0,0 +1,68 @@
+use jbk::reader::ByteRegion;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A reader on the content of a entry.
+///
+/// Created by [`crate::Waj::read_entry`] or [`crate::Waj::read_content`].
+pub struct EntryReader {
+    region: ByteRegion,
+    position: u64,
+}
+
+impl EntryReader {
+    pub(crate) fn new(region: ByteRegion) -> Self {
+        Self {
+            region,
+            position: 0,
+        }
+    }
+
+    /// The size of the content.
+    pub fn size(&self) -> u64 {
+        self.region.size().into_u64()
+    }
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.size().saturating_sub(self.position);
+        let to_read = std::cmp::min(remaining, buf.len() as u64);
+        if to_read == 0 {
+            return Ok(0);
+        }
+        let read = self
+            .region
+            .cut(jbk::Offset::from(self.position), jbk::Size::from(to_read))
+            .stream()
+            .read(&mut buf[..to_read as usize])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+// Allow to add a content of a archive to a new one without reading it in memory.
+impl jbk::creator::InputReader for EntryReader {
+    fn size(&self) -> jbk::Size {
+        self.region.size()
+    }
+}
+
+impl Seek for EntryReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+        }
+    }
+}
@@ -27,6 +27,12 @@ pub enum WajError {
 
     #[error("Path {0} not found in archive")]
     PathNotFound(String),
+
+    #[error("Entry {0} is not a content")]
+    NotAContent(String),
+
+    #[error("Content of {0} is in a missing pack")]
+    MissingPack(String),
 }
 
 impl From<jbk::Error> for WajError {
@@ -48,4 +54,28 @@ pub enum CreatorError {
 
     #[error("{0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Waj(#[from] WajError),
+
+    #[error("Several entries have the path {0}")]
+    Conflict(String),
+
+    #[error("{0} broken link(s) found")]
+    BrokenLinks(usize),
+
+    #[error("Invalid warc file: {0}")]
+    InvalidWarc(String),
+
+    #[error("Invalid zip file: {0}")]
+    InvalidZip(#[from] zip::result::ZipError),
+
+    #[error("Invalid zim file: {0}")]
+    InvalidZim(String),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] ignore::Error),
+
+    #[error("Invalid mimetype: {0:?}")]
+    InvalidMimetype(String),
 }
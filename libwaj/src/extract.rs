@@ -0,0 +1,330 @@
+use crate::error::WajError;
+use crate::walk::{Operator, Walker};
+use crate::{Alias, CommonEntry, Content, FullBuilder, Redirect, Waj};
+use log::warn;
+use std::cell::RefCell;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// How redirects are written on the file system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedirectMode {
+    /// As symbolic links (external redirects are always written as html stubs).
+    #[default]
+    Symlink,
+
+    /// As small html pages redirecting to the target with a `<meta http-equiv="refresh">`.
+    Html,
+}
+
+/// The result of a extraction.
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// Number of entries written.
+    pub extracted: usize,
+
+    /// Path of the entries whose content is in a missing pack.
+    pub missing: Vec<String>,
+
+    /// Path of the entries which cannot be written on a file system (empty path, `..`, ...).
+    pub skipped: Vec<String>,
+}
+
+struct Extractor<'a> {
+    waj: &'a Waj,
+    outdir: &'a Path,
+    redirect_mode: RedirectMode,
+    // Symlinks are created once all the contents are written,
+    // so contents are never written through a extracted symlink.
+    symlinks: RefCell<Vec<(String, PathBuf, String)>>,
+}
+
+/// Convert a entry path to a file system path relative to the output directory.
+///
+/// Returns None if the path would not stay in the output directory.
+fn relative_fs_path(path: &str) -> Option<PathBuf> {
+    let fs_path = PathBuf::from(path);
+    if path.is_empty()
+        || !fs_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        None
+    } else {
+        Some(fs_path)
+    }
+}
+
+/// Does the symlink target `target` of the entry at `path` stay in the output directory?
+fn symlink_target_is_inside(path: &str, target: &str) -> bool {
+    let mut depth = path.matches('/').count();
+    for component in Path::new(target).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Keep only the prefixes not already covered by a shorter one.
+fn normalize_prefixes(paths: &[String]) -> Vec<&str> {
+    let mut paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    paths.sort_unstable();
+    paths.dedup();
+    let mut prefixes: Vec<&str> = Vec::with_capacity(paths.len());
+    for path in paths {
+        // Sorted, so a covering prefix is always the last kept one.
+        if !prefixes
+            .last()
+            .is_some_and(|prefix| path.starts_with(prefix))
+        {
+            prefixes.push(path);
+        }
+    }
+    prefixes
+}
+
+fn redirect_stub(target: &str) -> String {
+    let target = crate::html::escape(target);
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"0; url={target}\">\
+         </head><body><a href=\"{target}\">{target}</a></body></html>"
+    )
+}
+
+impl Extractor<'_> {
+    /// Get the path where to write the entry, creating the parent directories.
+    ///
+    /// Returns None if the entry cannot be written (invalid path or a parent being a symlink).
+    fn prepare(&self, report: &mut ExtractReport, path: &[u8]) -> jbk::Result<Option<PathBuf>> {
+        let path = String::from_utf8_lossy(path);
+        let relative_path = match relative_fs_path(&path) {
+            None => {
+                warn!("Cannot extract entry `{path}`");
+                report.skipped.push(path.into_owned());
+                return Ok(None);
+            }
+            Some(relative_path) => relative_path,
+        };
+        // Never write through a symlink (from a previous extraction), it may point outside `outdir`.
+        let mut parent = self.outdir.to_path_buf();
+        for component in relative_path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+        {
+            parent.push(component);
+            match fs::symlink_metadata(&parent) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    warn!(
+                        "Cannot extract entry `{path}` ({} is a symlink)",
+                        parent.display()
+                    );
+                    report.skipped.push(path.into_owned());
+                    return Ok(None);
+                }
+                Ok(metadata) if metadata.is_dir() => {}
+                Ok(_) => fs::remove_file(&parent)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let fs_path = self.outdir.join(relative_path);
+        fs::create_dir_all(fs_path.parent().unwrap())?;
+        // Entry may have already been extracted (previous extraction).
+        match fs::symlink_metadata(&fs_path) {
+            Ok(metadata) if !metadata.is_dir() => fs::remove_file(&fs_path)?,
+            _ => {}
+        }
+        Ok(Some(fs_path))
+    }
+
+    /// Create the symlinks of the redirects, once all the contents are written.
+    fn write_symlinks(&self, report: &mut ExtractReport) -> jbk::Result<()> {
+        for (path, fs_path, target) in self.symlinks.take() {
+            // A content may have been written under the symlink path.
+            if fs_path.is_dir() && !fs_path.is_symlink() {
+                warn!("Cannot extract redirect `{path}` (a directory exists at its path)");
+                report.skipped.push(path);
+                continue;
+            }
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(target, &fs_path)?;
+            report.extracted += 1;
+        }
+        Ok(())
+    }
+
+    fn write_content(
+        &self,
+        report: &mut ExtractReport,
+        path: &[u8],
+        content: &Content,
+    ) -> jbk::Result<()> {
+        let bytes = match self
+            .waj
+            .get_bytes(content.content())?
+            .and_then(|m| m.transpose())
+        {
+            Some(jbk::reader::MayMissPack::FOUND(bytes)) => bytes,
+            _ => {
+                let path = String::from_utf8_lossy(path);
+                warn!("Content of `{path}` is missing");
+                report.missing.push(path.into_owned());
+                return Ok(());
+            }
+        };
+        let fs_path = match self.prepare(report, path)? {
+            None => return Ok(()),
+            Some(fs_path) => fs_path,
+        };
+        let mut file = fs::File::create(&fs_path)?;
+        std::io::copy(&mut bytes.stream(), &mut file)?;
+        if let Some(mtime) = content.mtime() {
+            file.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime))?;
+        }
+        report.extracted += 1;
+        Ok(())
+    }
+}
+
+impl Operator<ExtractReport, FullBuilder> for Extractor<'_> {
+    fn on_start(&self, _report: &mut ExtractReport) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_stop(&self, _report: &mut ExtractReport) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_content(&self, report: &mut ExtractReport, entry: &Content) -> jbk::Result<()> {
+        self.write_content(report, entry.path(), entry)
+    }
+
+    fn on_redirect(&self, report: &mut ExtractReport, entry: &Redirect) -> jbk::Result<()> {
+        let fs_path = match self.prepare(report, entry.path())? {
+            None => return Ok(()),
+            Some(fs_path) => fs_path,
+        };
+        let target = String::from_utf8_lossy(entry.target());
+        // Targets are relative to the redirect, as a symlink target is.
+        let as_symlink =
+            self.redirect_mode == RedirectMode::Symlink && cfg!(unix) && !entry.is_external();
+        if as_symlink {
+            let path = String::from_utf8_lossy(entry.path()).into_owned();
+            let target = match target.strip_prefix('/') {
+                None => target.into_owned(),
+                Some(root_target) => {
+                    let depth = entry.path().iter().filter(|c| **c == b'/').count();
+                    "../".repeat(depth) + root_target
+                }
+            };
+            if !symlink_target_is_inside(&path, &target) {
+                warn!("Cannot extract redirect `{path}` (target `{target}` is outside the output directory)");
+                report.skipped.push(path);
+                return Ok(());
+            }
+            self.symlinks.borrow_mut().push((path, fs_path, target));
+        } else {
+            fs::File::create(&fs_path)?.write_all(redirect_stub(&target).as_bytes())?;
+            report.extracted += 1;
+        }
+        Ok(())
+    }
+
+    fn on_alias(&self, report: &mut ExtractReport, entry: &Alias) -> jbk::Result<()> {
+        match self.waj.alias_content(entry) {
+            Ok(Some((_, content))) => self.write_content(report, entry.path(), &content),
+            _ => {
+                let target = String::from_utf8_lossy(entry.target());
+                let path = String::from_utf8_lossy(entry.path());
+                warn!("`{path}` is a alias to `{target}` which is not a content");
+                report.skipped.push(path.into_owned());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Waj {
+    /// Extract the entries of the archive in the directory `outdir`.
+    ///
+    /// If `paths` is not empty, only the entries whose path starts with one of `paths` are extracted.
+    /// Aliases are written as a copy of their target.
+    /// Redirects whose target is outside `outdir` are not written as symlinks but skipped.
+    pub fn extract(
+        &self,
+        outdir: &Path,
+        paths: &[String],
+        redirect_mode: RedirectMode,
+    ) -> Result<ExtractReport, WajError> {
+        let extractor = Extractor {
+            waj: self,
+            outdir,
+            redirect_mode,
+            symlinks: RefCell::new(vec![]),
+        };
+        fs::create_dir_all(outdir)?;
+        let mut walker = Walker::new(self, ExtractReport::default());
+        if paths.is_empty() {
+            walker.run(&extractor)?;
+        } else {
+            for prefix in normalize_prefixes(paths) {
+                walker.run_with_prefix(prefix, &extractor)?;
+            }
+        }
+        let mut report = walker.into_context();
+        extractor.write_symlinks(&mut report)?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_relative_fs_path() -> Result {
+        assert_eq!(
+            relative_fs_path("a/b.html"),
+            Some(PathBuf::from("a/b.html"))
+        );
+        assert_eq!(relative_fs_path(""), None);
+        assert_eq!(relative_fs_path("../a.html"), None);
+        assert_eq!(relative_fs_path("a/../../b"), None);
+        assert_eq!(relative_fs_path("/etc/passwd"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_symlink_target_is_inside() -> Result {
+        assert!(symlink_target_is_inside("a", "b/c"));
+        assert!(symlink_target_is_inside("a/b", "../c"));
+        assert!(symlink_target_is_inside("a/b", "./c/../d"));
+        assert!(!symlink_target_is_inside("a", "../b"));
+        assert!(!symlink_target_is_inside("a/b", "../../../tmp"));
+        assert!(!symlink_target_is_inside("a", "/tmp"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_prefixes() -> Result {
+        let paths = [
+            "docs/api".to_owned(),
+            "docs".to_owned(),
+            "img".to_owned(),
+            "docs".to_owned(),
+        ];
+        assert_eq!(normalize_prefixes(&paths), vec!["docs", "img"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use crate::common::{partition_point, FtsDocumentProperties, FtsWordProperties};
+use crate::error::{BaseError, WajError, WajFormatError};
+use jbk::reader::builder::PropertyBuilderTrait;
+use jbk::reader::Range;
+use std::collections::HashMap;
+use std::io::Read;
+
+const MIN_WORD_LEN: usize = 2;
+const MAX_WORD_LEN: usize = 64;
+
+/// Split a text into normalized (lowercased) words.
+///
+/// This is used both to index the content and to parse the search query.
+pub(crate) fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_LEN && w.len() <= MAX_WORD_LEN)
+        .map(str::to_lowercase)
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+        if shift >= 64 {
+            return None;
+        }
+    }
+}
+
+/// Decode a postings list.
+///
+/// Postings are a list of (delta encoded document id, term frequency) varints.
+fn decode_postings(data: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let mut postings = vec![];
+    let mut pos = 0;
+    let mut doc_id = 0u32;
+    while pos < data.len() {
+        // Corrupted postings must not overflow.
+        let delta = u32::try_from(read_varint(data, &mut pos)?).ok()?;
+        doc_id = doc_id.checked_add(delta)?;
+        let term_frequency = u32::try_from(read_varint(data, &mut pos)?).ok()?;
+        postings.push((doc_id, term_frequency));
+    }
+    Some(postings)
+}
+
+/// A result of a full text search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub(crate) path: String,
+    pub(crate) score: f32,
+    pub(crate) snippet: String,
+}
+
+impl SearchResult {
+    /// The path of the matching entry.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The score of the entry. Higher is better.
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    /// A short text extract of the entry around the first match.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+pub(crate) struct FtsIndex {
+    word_index: jbk::reader::Index,
+    words: FtsWordProperties,
+    document_index: jbk::reader::Index,
+    documents: FtsDocumentProperties,
+}
+
+impl FtsIndex {
+    pub fn new(container: &jbk::reader::Container) -> Result<Option<Self>, BaseError> {
+        let directory_pack = container.get_directory_pack();
+        let word_index = match directory_pack.get_index_from_name("waj_fts_words")? {
+            None => return Ok(None),
+            Some(index) => index,
+        };
+        let document_index = directory_pack
+            .get_index_from_name("waj_fts_documents")?
+            .ok_or(WajFormatError("No `waj_fts_documents` in the archive"))?;
+        let words = FtsWordProperties::new(
+            word_index.get_store(container.get_entry_storage())?,
+            container.get_value_storage(),
+        )?;
+        let documents = FtsDocumentProperties::new(
+            document_index.get_store(container.get_entry_storage())?,
+            container.get_value_storage(),
+        )?;
+        Ok(Some(Self {
+            word_index,
+            words,
+            document_index,
+            documents,
+        }))
+    }
+
+    fn word(&self, idx: jbk::EntryIdx) -> jbk::Result<jbk::SmallBytes> {
+        let reader = self
+            .words
+            .store
+            .get_entry_reader(idx)
+            .expect("idx should be valid");
+        let mut word = jbk::SmallBytes::new();
+        self.words
+            .word_property
+            .create(&reader)?
+            .resolve_to_vec(&mut word)?;
+        Ok(word)
+    }
+
+    fn find_word(&self, word: &str) -> jbk::Result<Option<jbk::EntryIdx>> {
+        let word = word.as_bytes();
+        let start = self.word_index.offset();
+        let end = start + self.word_index.count();
+        let idx = partition_point(start, end, |idx| Ok(self.word(idx)?.as_slice() < word))?;
+        if idx != end && self.word(idx)?.as_slice() == word {
+            Ok(Some(idx))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn postings(
+        &self,
+        container: &jbk::reader::Container,
+        idx: jbk::EntryIdx,
+    ) -> Result<(u64, Vec<(u32, u32)>), WajError> {
+        let reader = self
+            .words
+            .store
+            .get_entry_reader(idx)
+            .expect("idx should be valid");
+        let doc_count = self.words.doc_count_property.create(&reader)?;
+        let content_address = self.words.postings_property.create(&reader)?;
+        let bytes = container
+            .get_bytes(content_address)?
+            .and_then(|m| m.transpose())
+            .ok_or(WajFormatError("Content address not valid"))?;
+        let bytes = match bytes {
+            jbk::reader::MayMissPack::FOUND(bytes) => bytes,
+            // We cannot search if the pack containing the index is missing.
+            jbk::reader::MayMissPack::MISSING(_) => return Ok((doc_count, vec![])),
+        };
+        let mut data = vec![];
+        bytes.stream().read_to_end(&mut data)?;
+        let postings =
+            decode_postings(&data).ok_or(WajFormatError("Invalid full text postings"))?;
+        Ok((doc_count, postings))
+    }
+
+    fn document(&self, doc_id: u32) -> Result<(String, u64), BaseError> {
+        let idx = self.document_index.offset() + jbk::EntryCount::from(doc_id);
+        let reader = self
+            .documents
+            .store
+            .get_entry_reader(idx)
+            .ok_or(WajFormatError("Invalid full text document id"))?;
+        let mut path = jbk::SmallBytes::new();
+        self.documents
+            .path_property
+            .create(&reader)?
+            .resolve_to_vec(&mut path)?;
+        let length = self.documents.length_property.create(&reader)?;
+        Ok((String::from_utf8_lossy(&path).into_owned(), length))
+    }
+
+    /// Search documents containing any of the `words`.
+    ///
+    /// Returns the path and score of the best `max_results` documents.
+    /// Score is a classic tf-idf with normalization on the length of the document.
+    pub fn search(
+        &self,
+        container: &jbk::reader::Container,
+        words: &[String],
+        max_results: usize,
+    ) -> Result<Vec<(String, f32)>, WajError> {
+        let nb_documents = self.document_index.count().into_u32() as f32;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        for word in words {
+            let idx = match self.find_word(word)? {
+                None => continue,
+                Some(idx) => idx,
+            };
+            let (doc_count, postings) = self.postings(container, idx)?;
+            let idf = (1.0 + nb_documents / (doc_count as f32).max(1.0)).ln();
+            for (doc_id, term_frequency) in postings {
+                *scores.entry(doc_id).or_default() += idf * (1.0 + (term_frequency as f32).ln());
+            }
+        }
+        let mut scores = scores
+            .into_iter()
+            .map(|(doc_id, score)| {
+                let (path, length) = self.document(doc_id)?;
+                Ok((path, score / ((length as f32).max(1.0)).sqrt()))
+            })
+            .collect::<Result<Vec<_>, BaseError>>()?;
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scores.truncate(max_results);
+        Ok(scores)
+    }
+}
+
+/// Build a snippet of `text` around the first occurrence of one of the `words`.
+pub(crate) fn build_snippet(text: &str, words: &[String]) -> String {
+    const BEFORE: usize = 10;
+    const AFTER: usize = 20;
+    let text_words: Vec<&str> = text.split_whitespace().collect();
+    let first_match = text_words
+        .iter()
+        .position(|w| tokenize(w).any(|t| words.contains(&t)))
+        .unwrap_or(0);
+    let start = first_match.saturating_sub(BEFORE);
+    let end = std::cmp::min(first_match + AFTER, text_words.len());
+    let mut snippet = text_words[start..end].join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "… ");
+    }
+    if end < text_words.len() {
+        snippet.push_str(" …");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_tokenize() -> Result {
+        assert_eq!(
+            tokenize("Hello, World! A l'été 2024.").collect::<Vec<_>>(),
+            vec!["hello", "world", "été", "2024"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_postings() -> Result {
+        let postings = vec![(0, 1), (5, 300), (1000, 2)];
+        let mut data = vec![];
+        let mut previous = 0;
+        for (doc_id, tf) in &postings {
+            write_varint(&mut data, (doc_id - previous) as u64);
+            write_varint(&mut data, *tf as u64);
+            previous = *doc_id;
+        }
+        assert_eq!(decode_postings(&data), Some(postings));
+        assert_eq!(decode_postings(&[0x80]), None);
+        let mut overflowing = vec![];
+        for delta in [u32::MAX as u64, 1] {
+            write_varint(&mut overflowing, delta);
+            write_varint(&mut overflowing, 1);
+        }
+        assert_eq!(decode_postings(&overflowing), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet() -> Result {
+        let text = (0..50)
+            .map(|i| format!("w{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            build_snippet(&text, &["w15".into()]),
+            format!(
+                "… {} …",
+                (5..35)
+                    .map(|i| format!("w{i}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        );
+        assert_eq!(build_snippet("foo bar", &["baz".into()]), "foo bar");
+        Ok(())
+    }
+}
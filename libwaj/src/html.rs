@@ -0,0 +1,201 @@
+//! Minimal html helpers.
+//!
+//! This is not a html parser. We only search for some specific patterns in the
+//! (potentially partial) content of a html page.
+
+fn find_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+/// Decode the most common html character references.
+///
+/// Unknown references are kept as is.
+pub fn decode_entities(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(amp_idx) = rest.find('&') {
+        output.push_str(&rest[..amp_idx]);
+        rest = &rest[amp_idx..];
+        let decoded = rest.find(';').and_then(|semicolon_idx| {
+            let entity = &rest[1..semicolon_idx];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => {
+                    if let Some(hex) = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse::<u32>().ok().and_then(char::from_u32)
+                    } else {
+                        None
+                    }
+                }
+            };
+            c.map(|c| (c, semicolon_idx))
+        });
+        match decoded {
+            Some((c, semicolon_idx)) => {
+                output.push(c);
+                rest = &rest[semicolon_idx + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Extract the content of the `<title>` tag of a html page.
+///
+/// Whitespaces are collapsed and entities decoded.
+/// Returns None if there is no title or if title is empty.
+pub fn extract_title(html: &[u8]) -> Option<String> {
+    let mut search = html;
+    let content = loop {
+        let start = find_ignore_case(search, b"<title")?;
+        let after = &search[start + 6..];
+        match after.first() {
+            Some(b'>') | Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') => {
+                let content_start = after.iter().position(|c| *c == b'>')? + 1;
+                break &after[content_start..];
+            }
+            _ => search = after,
+        }
+    };
+    let end = find_ignore_case(content, b"</title")?;
+    let title = decode_entities(&String::from_utf8_lossy(&content[..end]));
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Extract the text of a html page.
+///
+/// Tags are removed, as the content of `<script>` and `<style>` elements.
+/// Whitespaces are collapsed and entities decoded.
+pub fn extract_text(html: &[u8]) -> String {
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut rest = html;
+    while !rest.is_empty() {
+        match rest.iter().position(|c| *c == b'<') {
+            None => {
+                text.push_str(&String::from_utf8_lossy(rest));
+                break;
+            }
+            Some(tag_start) => {
+                text.push_str(&String::from_utf8_lossy(&rest[..tag_start]));
+                text.push(' ');
+                rest = &rest[tag_start..];
+                // Where the "tag" ends. Closing tag of script and style, end of comment.
+                let end_pattern: Option<&[u8]> =
+                    if rest.len() > 7 && rest[..7].eq_ignore_ascii_case(b"<script") {
+                        Some(b"</script")
+                    } else if rest.len() > 6 && rest[..6].eq_ignore_ascii_case(b"<style") {
+                        Some(b"</style")
+                    } else if rest.starts_with(b"<!--") {
+                        Some(b"-->")
+                    } else {
+                        None
+                    };
+                if let Some(end_pattern) = end_pattern {
+                    match find_ignore_case(rest, end_pattern) {
+                        // Move to the last `>` of the end pattern (or just before the closing tag)
+                        Some(idx) if end_pattern == b"-->" => rest = &rest[idx + 2..],
+                        Some(idx) => rest = &rest[idx..],
+                        None => break,
+                    }
+                }
+                match rest.iter().position(|c| *c == b'>') {
+                    Some(tag_end) => rest = &rest[tag_end + 1..],
+                    None => break,
+                }
+            }
+        }
+    }
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Escape a text to be included in a html page.
+pub fn escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_extract_title() -> Result {
+        assert_eq!(
+            extract_title(b"<html><head><TITLE>A  simple\n title</TITLE></head></html>"),
+            Some("A simple title".into())
+        );
+        assert_eq!(
+            extract_title(b"<title lang=\"en\">Fish &amp; Chips &#x263A;</title>"),
+            Some("Fish & Chips \u{263A}".into())
+        );
+        assert_eq!(
+            extract_title(b"<titlebar>Foo</titlebar><title>Bar</title>"),
+            Some("Bar".into())
+        );
+        assert_eq!(extract_title(b"<title>  </title>"), None);
+        assert_eq!(extract_title(b"<title>Not closed"), None);
+        assert_eq!(extract_title(b"<html></html>"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_text() -> Result {
+        assert_eq!(
+            extract_text(
+                b"<html><head><title>T</title><style>p {}</style></head>\
+                <body><!-- <p>comment</p> --><p>Hello&nbsp;<b>world</b></p>\
+                <script>var a = '<p>';</script>!</body></html>"
+            ),
+            "T Hello world !"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_entities() -> Result {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#65; &unknown; &"),
+            "a <b> A &unknown; &"
+        );
+        Ok(())
+    }
+}
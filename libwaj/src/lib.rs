@@ -1,15 +1,37 @@
+mod check;
 mod common;
 pub mod create;
+mod diff;
+mod encoding;
 mod entry;
+mod entry_reader;
+mod extract;
 //pub mod fs_adder;
 pub mod error;
+mod fts;
+mod html;
+mod links;
+pub mod metadata;
+mod prefix;
 mod serve;
+mod stats;
+pub mod title;
 mod waj;
 pub mod walk;
 
-pub use common::{AllProperties, Builder, Entry, FullBuilderTrait, VENDOR_ID};
+pub use check::{CheckReport, Issue};
+pub use common::{AllProperties, Builder, Entry, FullBuilderTrait, RedirectStatus, VENDOR_ID};
+pub use diff::{ChangeKind, Difference};
+pub use encoding::{is_precompressible, Encoding};
 pub use entry::*;
-pub use serve::{HostRouter, Router, Server, SubPathRouter, WajServer};
+pub use entry_reader::EntryReader;
+pub use extract::{ExtractReport, RedirectMode};
+pub use fts::SearchResult;
+pub use metadata::Metadata;
+pub use prefix::{Child, DirectoryChildren, PrefixEntries};
+pub use serve::{HostRouter, OverlayRouter, Router, Server, SubPathRouter, WajServer};
+pub use stats::{MimetypeStats, PackStats, Stats};
+pub use title::TitleEntry;
 pub use waj::Waj;
 //pub use walk::*;
 
@@ -0,0 +1,264 @@
+//! Extraction of the references (links, images, stylesheets, ...) of html and css contents.
+//!
+//! As the html helpers, this is not a parser. We search for the attributes and css functions
+//! which reference other resources.
+
+use crate::common::resolve_path;
+use crate::html::decode_entities;
+use percent_encoding::percent_decode_str;
+
+const LINK_ATTRIBUTES: [&str; 3] = ["href", "src", "srcset"];
+
+fn skip_whitespaces(input: &[u8]) -> &[u8] {
+    let start = input
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(input.len());
+    &input[start..]
+}
+
+/// Parse the attributes of a tag.
+///
+/// `tag` starts after the tag name. Returns the (lowercase) attributes name and value
+/// (entities decoded) and the rest of the input after the end of the tag.
+fn parse_attributes(mut tag: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let mut attributes = vec![];
+    loop {
+        tag = skip_whitespaces(tag);
+        match tag.first() {
+            None => return (attributes, tag),
+            Some(b'>') => return (attributes, &tag[1..]),
+            Some(b'/') => {
+                tag = &tag[1..];
+                continue;
+            }
+            _ => {}
+        }
+        let name_end = tag
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || matches!(c, b'=' | b'>' | b'/'))
+            .unwrap_or(tag.len())
+            .max(1);
+        let name = String::from_utf8_lossy(&tag[..name_end]).to_ascii_lowercase();
+        tag = skip_whitespaces(&tag[name_end..]);
+        let value = match tag.strip_prefix(b"=") {
+            None => String::new(),
+            Some(rest) => {
+                let rest = skip_whitespaces(rest);
+                let (value, rest) = match rest.first() {
+                    Some(quote @ (b'"' | b'\'')) => {
+                        let rest = &rest[1..];
+                        match rest.iter().position(|c| c == quote) {
+                            Some(end) => (&rest[..end], &rest[end + 1..]),
+                            None => (rest, &rest[rest.len()..]),
+                        }
+                    }
+                    _ => {
+                        let end = rest
+                            .iter()
+                            .position(|c| c.is_ascii_whitespace() || *c == b'>')
+                            .unwrap_or(rest.len());
+                        (&rest[..end], &rest[end..])
+                    }
+                };
+                tag = rest;
+                decode_entities(&String::from_utf8_lossy(value))
+            }
+        };
+        attributes.push((name, value));
+    }
+}
+
+/// The urls of a `srcset` attribute (`url [descriptor], url [descriptor], ...`).
+fn srcset_urls(srcset: &str) -> impl Iterator<Item = &str> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| candidate.split_whitespace().next())
+}
+
+/// Extract the references of a html page.
+///
+/// References are taken from `href`, `src` and `srcset` attributes, and from the `url()`
+/// of `<style>` elements and `style` attributes. References are returned as written
+/// (entities decoded), without resolution.
+pub fn extract_html_links(html: &[u8]) -> Vec<String> {
+    let mut links = vec![];
+    let mut rest = html;
+    while let Some(tag_start) = rest.iter().position(|c| *c == b'<') {
+        rest = &rest[tag_start + 1..];
+        if let Some(comment) = rest.strip_prefix(b"!--") {
+            rest = match comment.windows(3).position(|w| w == b"-->") {
+                Some(end) => &comment[end + 3..],
+                None => break,
+            };
+            continue;
+        }
+        // Closing tags have no attributes.
+        if rest.first() == Some(&b'/') {
+            continue;
+        }
+        let name_end = rest
+            .iter()
+            .position(|c| c.is_ascii_whitespace() || matches!(c, b'>' | b'/'))
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        let (attributes, after_tag) = parse_attributes(&rest[name_end..]);
+        rest = after_tag;
+        for (attribute, value) in attributes {
+            match attribute.as_str() {
+                "srcset" => links.extend(srcset_urls(&value).map(str::to_owned)),
+                "style" => links.extend(extract_css_links(value.as_bytes())),
+                a if LINK_ATTRIBUTES.contains(&a) => links.push(value),
+                _ => {}
+            }
+        }
+        // Content of script and style elements is not html.
+        let end_pattern: &[u8] = match name.as_slice() {
+            b"script" => b"</script",
+            b"style" => b"</style",
+            _ => continue,
+        };
+        let end = rest
+            .windows(end_pattern.len())
+            .position(|w| w.eq_ignore_ascii_case(end_pattern))
+            .unwrap_or(rest.len());
+        if name == b"style" {
+            links.extend(extract_css_links(&rest[..end]));
+        }
+        rest = &rest[end..];
+    }
+    links
+}
+
+/// Extract the references of a css stylesheet.
+///
+/// References are taken from `url()` functions and `@import` rules.
+pub fn extract_css_links(css: &[u8]) -> Vec<String> {
+    let css = String::from_utf8_lossy(css);
+    let mut links = vec![];
+    let mut rest = &*css;
+    loop {
+        let url_idx = rest.find("url(");
+        let import_idx = rest.find("@import");
+        let (value, is_url) = match (url_idx, import_idx) {
+            (None, None) => break,
+            (Some(u), Some(i)) if i < u => (&rest[i + 7..], false),
+            (None, Some(i)) => (&rest[i + 7..], false),
+            (Some(u), _) => (&rest[u + 4..], true),
+        };
+        let value = value.trim_start();
+        let (link, after) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let value = &value[1..];
+                match value.find(quote) {
+                    Some(end) => (&value[..end], &value[end + 1..]),
+                    None => break,
+                }
+            }
+            // `@import url(...)` is handled by the next loop iteration.
+            _ if !is_url => {
+                rest = value;
+                continue;
+            }
+            _ => match value.find(')') {
+                Some(end) => (value[..end].trim_end(), &value[end + 1..]),
+                None => break,
+            },
+        };
+        links.push(link.to_owned());
+        rest = after;
+    }
+    links
+}
+
+/// Resolve a reference found in the entry at `path` to the path of a entry in the archive.
+///
+/// Returns None if the reference is not a path in the archive (absolute url, `mailto:`, `data:`,
+/// fragment only, ...). Query string and fragment are removed and the path is percent decoded.
+pub fn resolve_link(path: &str, link: &str) -> Option<String> {
+    let link = link.trim();
+    let link = link.split_once('#').map_or(link, |(l, _)| l);
+    let link = link.split_once('?').map_or(link, |(l, _)| l);
+    if link.is_empty() || link.starts_with("//") {
+        return None;
+    }
+    // A scheme is before any `/`.
+    let first_slash = link.find('/').unwrap_or(link.len());
+    if link[..first_slash].contains(':') {
+        return None;
+    }
+    let link = percent_decode_str(link).decode_utf8_lossy();
+    Some(resolve_path(path, &link))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustest::{test, Result};
+
+    #[test]
+    fn test_extract_html_links() -> Result {
+        assert_eq!(
+            extract_html_links(
+                b"<html><head><link rel=stylesheet href=\"style.css\">\
+                <style>body { background: url(bg.png) }</style>\
+                <script src='app.js'>var a = '<a href=\"no.html\">';</script></head>\
+                <body><!-- <a href=\"comment.html\"> --><A HREF=\"a.html?x=1&amp;y=2\">A</A>\
+                <img src=img.png srcset=\"img-2x.png 2x, img-3x.png 3x\" alt=\"<src>\">\
+                <p style=\"background-image: url('p.png')\">Text</p></body></html>"
+            ),
+            vec![
+                "style.css",
+                "bg.png",
+                "app.js",
+                "a.html?x=1&y=2",
+                "img.png",
+                "img-2x.png",
+                "img-3x.png",
+                "p.png"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_css_links() -> Result {
+        assert_eq!(
+            extract_css_links(
+                b"@import \"base.css\";\n@import url(print.css) print;\n\
+                .a { background: url( \"a.png\" ) }\n.b { background: url('b.png') }"
+            ),
+            vec!["base.css", "print.css", "a.png", "b.png"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_link() -> Result {
+        assert_eq!(
+            resolve_link("docs/page.html", "other.html"),
+            Some("docs/other.html".into())
+        );
+        assert_eq!(
+            resolve_link("docs/page.html", "../img/a%20b.png?v=2#top"),
+            Some("img/a b.png".into())
+        );
+        assert_eq!(
+            resolve_link("docs/page.html", "/index.html"),
+            Some("index.html".into())
+        );
+        assert_eq!(
+            resolve_link("docs/page.html", "sub/"),
+            Some("docs/sub/".into())
+        );
+        assert_eq!(resolve_link("docs/page.html", "#top"), None);
+        assert_eq!(resolve_link("docs/page.html", "https://example.com/"), None);
+        assert_eq!(resolve_link("docs/page.html", "//example.com/a.js"), None);
+        assert_eq!(resolve_link("docs/page.html", "mailto:a@example.com"), None);
+        assert_eq!(
+            resolve_link("docs/page.html", "data:image/png;base64,AAA"),
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use crate::common::MetadataProperties;
+use crate::error::BaseError;
+use jbk::reader::builder::PropertyBuilderTrait;
+use std::collections::BTreeMap;
+
+/// Well known metadata keys.
+///
+/// Any other key can be stored in a waj archive, those are only the ones
+/// with a typed accessor in [`Metadata`].
+pub mod keys {
+    pub const TITLE: &str = "title";
+    pub const DESCRIPTION: &str = "description";
+    pub const LANGUAGE: &str = "language";
+    pub const CREATOR: &str = "creator";
+    pub const PUBLISHER: &str = "publisher";
+    pub const DATE: &str = "date";
+    pub const LICENSE: &str = "license";
+    pub const SOURCE: &str = "source";
+    pub const ILLUSTRATION: &str = "illustration";
+    pub const FAVICON: &str = "favicon";
+
+    pub const ALL: [&str; 10] = [
+        TITLE,
+        DESCRIPTION,
+        LANGUAGE,
+        CREATOR,
+        PUBLISHER,
+        DATE,
+        LICENSE,
+        SOURCE,
+        ILLUSTRATION,
+        FAVICON,
+    ];
+}
+
+/// Keys of the creation info.
+///
+/// The creation info is recorded by the creator itself, apart from the user metadata.
+pub(crate) mod creation_info {
+    /// The compression algorithm of the content packs written by the creator.
+    pub const COMPRESSION: &str = "compression";
+}
+
+/// The archive level metadata of a waj archive.
+///
+/// Metadata are simple key/value strings.
+/// `illustration` and `favicon` values are paths of entries in the archive.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Metadata(BTreeMap<String, String>);
+
+impl Metadata {
+    pub(crate) fn new(values: BTreeMap<String, String>) -> Self {
+        Self(values)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Iterate on all (key, value) pairs, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get(keys::TITLE)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.get(keys::DESCRIPTION)
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.get(keys::LANGUAGE)
+    }
+
+    pub fn creator(&self) -> Option<&str> {
+        self.get(keys::CREATOR)
+    }
+
+    pub fn publisher(&self) -> Option<&str> {
+        self.get(keys::PUBLISHER)
+    }
+
+    pub fn date(&self) -> Option<&str> {
+        self.get(keys::DATE)
+    }
+
+    pub fn license(&self) -> Option<&str> {
+        self.get(keys::LICENSE)
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.get(keys::SOURCE)
+    }
+
+    pub fn illustration(&self) -> Option<&str> {
+        self.get(keys::ILLUSTRATION)
+    }
+
+    pub fn favicon(&self) -> Option<&str> {
+        self.get(keys::FAVICON)
+    }
+}
+
+pub(crate) struct MetadataBuilder {
+    properties: MetadataProperties,
+}
+
+impl MetadataBuilder {
+    pub fn new(properties: MetadataProperties) -> Self {
+        Self { properties }
+    }
+}
+
+impl jbk::reader::builder::BuilderTrait for MetadataBuilder {
+    type Entry = (String, String);
+    type Error = BaseError;
+
+    fn create_entry(&self, idx: jbk::EntryIdx) -> Result<Option<Self::Entry>, Self::Error> {
+        self.properties
+            .store
+            .get_entry_reader(idx)
+            .map(|reader| {
+                let mut key = jbk::SmallBytes::new();
+                self.properties
+                    .key_property
+                    .create(&reader)?
+                    .resolve_to_vec(&mut key)?;
+                let mut value = jbk::SmallBytes::new();
+                self.properties
+                    .value_property
+                    .create(&reader)?
+                    .resolve_to_vec(&mut value)?;
+                Ok((
+                    String::from_utf8_lossy(&key).into_owned(),
+                    String::from_utf8_lossy(&value).into_owned(),
+                ))
+            })
+            .transpose()
+    }
+}
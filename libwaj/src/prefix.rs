@@ -0,0 +1,155 @@
+use crate::common::{
+    partition_point, AllProperties, Entry, EntryDef, FullBuilderTrait, RealBuilder,
+};
+use crate::error::WajError;
+use jbk::reader::builder::{BuilderTrait, PropertyBuilderTrait};
+use jbk::reader::Range;
+
+fn path_at(properties: &AllProperties, idx: jbk::EntryIdx) -> jbk::Result<jbk::SmallBytes> {
+    let reader = properties
+        .store
+        .get_entry_reader(idx)
+        .expect("idx should be valid");
+    let mut path = jbk::SmallBytes::new();
+    properties
+        .path_property
+        .create(&reader)?
+        .resolve_to_vec(&mut path)?;
+    Ok(path)
+}
+
+/// Search the bounds `[start, end[` of the entries whose path starts with `prefix` in `range`.
+pub(crate) fn prefix_bounds<R: Range>(
+    properties: &AllProperties,
+    range: &R,
+    prefix: &[u8],
+) -> jbk::Result<(jbk::EntryIdx, jbk::EntryIdx)> {
+    let start = range.offset();
+    let end = start + range.count();
+    let first = partition_point(start, end, |idx| {
+        Ok(path_at(properties, idx)?.as_slice() < prefix)
+    })?;
+    let last = partition_point(first, end, |idx| {
+        Ok(path_at(properties, idx)?.starts_with(prefix))
+    })?;
+    Ok((first, last))
+}
+
+/// Iterator on the entries whose path starts with a prefix.
+///
+/// Created by [`crate::Waj::entries_with_prefix`].
+pub struct PrefixEntries<B: FullBuilderTrait> {
+    builder: RealBuilder<B>,
+    current: jbk::EntryIdx,
+    end: jbk::EntryIdx,
+}
+
+impl<B: FullBuilderTrait> PrefixEntries<B> {
+    pub(crate) fn new(
+        properties: &AllProperties,
+        start: jbk::EntryIdx,
+        end: jbk::EntryIdx,
+    ) -> Self {
+        Self {
+            builder: RealBuilder::new(properties),
+            current: start,
+            end,
+        }
+    }
+}
+
+impl<B: FullBuilderTrait> Iterator for PrefixEntries<B> {
+    type Item = Result<Entry<B::Entry>, WajError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == self.end {
+            return None;
+        }
+        let entry = self
+            .builder
+            .create_entry(self.current)
+            .transpose()
+            .expect("self.current is valid");
+        self.current += 1;
+        Some(entry.map_err(WajError::from))
+    }
+}
+
+/// A direct child of a directory.
+pub enum Child<E: EntryDef> {
+    /// A entry directly in the directory.
+    Entry(Entry<E>),
+
+    /// A sub directory (path ends with a `/`).
+    ///
+    /// There is no directory entries in waj archive, sub directories are
+    /// deduced from the path of the entries they contain.
+    Directory(String),
+}
+
+/// Iterator on the direct children of a directory.
+///
+/// Created by [`crate::Waj::directory_children`].
+pub struct DirectoryChildren<'a, B: FullBuilderTrait> {
+    properties: &'a AllProperties,
+    builder: RealBuilder<B>,
+    directory: Vec<u8>,
+    current: jbk::EntryIdx,
+    end: jbk::EntryIdx,
+}
+
+impl<'a, B: FullBuilderTrait> DirectoryChildren<'a, B> {
+    pub(crate) fn new(
+        properties: &'a AllProperties,
+        directory: Vec<u8>,
+        start: jbk::EntryIdx,
+        end: jbk::EntryIdx,
+    ) -> Self {
+        Self {
+            properties,
+            builder: RealBuilder::new(properties),
+            directory,
+            current: start,
+            end,
+        }
+    }
+
+    fn next_child(&mut self) -> Result<Option<Child<B::Entry>>, WajError> {
+        while self.current != self.end {
+            let path = path_at(self.properties, self.current)?;
+            let rest = &path[self.directory.len()..];
+            if rest.is_empty() {
+                // The directory itself (as `""` for the root) is not one of its children.
+                self.current += 1;
+                continue;
+            }
+            return Ok(Some(match rest.iter().position(|c| *c == b'/') {
+                None => {
+                    let entry = self
+                        .builder
+                        .create_entry(self.current)?
+                        .expect("self.current is valid");
+                    self.current += 1;
+                    Child::Entry(entry)
+                }
+                Some(slash_idx) => {
+                    // Skip all the entries of the sub directory.
+                    let sub_directory = &path[..self.directory.len() + slash_idx + 1];
+                    self.current = partition_point(self.current, self.end, |idx| {
+                        Ok(path_at(self.properties, idx)?.starts_with(sub_directory))
+                    })?;
+                    Child::Directory(String::from_utf8_lossy(sub_directory).into_owned())
+                }
+            }));
+        }
+        Ok(None)
+    }
+}
+
+impl<B: FullBuilderTrait> Iterator for DirectoryChildren<'_, B> {
+    type Item = Result<Child<B::Entry>, WajError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_child().transpose()
+    }
+}
@@ -1,13 +1,18 @@
-use crate::common::{AllProperties, Builder, Entry};
+use crate::common::{read_hash, url_variants, AllProperties, Builder, Entry, RedirectStatus};
+use crate::encoding::{negotiate, Encoding};
 use crate::error::{BaseError, WajError, WajFormatError};
-use crate::Waj;
+use crate::html::escape as html_escape;
+use crate::waj::MAX_ALIAS_CHAIN;
+use crate::{Child, CommonEntry, SearchResult, Waj};
 use ascii::IntoAsciiString;
 use http_range_header::{parse_range_header, ParsedRanges};
 use jbk::reader::builder::PropertyBuilderTrait;
 use jbk::reader::{ByteRegion, ByteSlice};
 use log::{debug, error, trace, warn};
-use percent_encoding::{percent_decode_str, percent_encode, CONTROLS};
-use std::borrow::Cow;
+use percent_encoding::{
+    percent_decode_str, percent_encode, utf8_percent_encode, AsciiSet, CONTROLS,
+};
+use std::collections::HashSet;
 use std::iter::Iterator;
 use std::path::Path;
 use std::sync::Arc;
@@ -15,33 +20,103 @@ use tiny_http::*;
 
 use super::Router;
 
-fn url_variants(url: &str) -> Vec<Cow<'_, str>> {
-    let mut vec: Vec<Cow<str>> = vec![];
-    vec.push(url.into());
-    let query_string_idx = url.find('?');
-    if let Some(idx) = query_string_idx {
-        vec.push(url[..idx].into())
+/// Characters to encode when we generate links to entries.
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?');
+
+const SEARCH_PATH: &str = "_waj/search";
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Returns the query string if `url` targets the search endpoint.
+fn search_query_string(url: &str) -> Option<&str> {
+    let url = url.strip_prefix('/').unwrap_or(url);
+    let rest = url.strip_prefix(SEARCH_PATH)?;
+    if rest.is_empty() {
+        Some("")
+    } else {
+        rest.strip_prefix('?')
     }
-    let end_idx = match query_string_idx {
-        Some(idx) => idx,
-        None => url.len(),
+}
+
+/// Decode a form encoded (`+` for spaces, percent encoding) query string component.
+fn decode_query_component(component: &str) -> String {
+    percent_decode_str(&component.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Get the value of the parameter `name` in the (raw) query string.
+///
+/// The query string must not be percent decoded before being split in parameters,
+/// as values may contain encoded `&` and `=`.
+fn query_param(query_string: &str, name: &str) -> Option<String> {
+    query_string.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        if decode_query_component(key) == name {
+            Some(decode_query_component(value))
+        } else {
+            None
+        }
+    })
+}
+
+/// Percent decode the path part of `url`. The query string is kept as it is.
+fn decode_url(url: &str) -> Option<String> {
+    let (path, query_string) = match url.split_once('?') {
+        Some((path, query_string)) => (path, Some(query_string)),
+        None => (url, None),
     };
-    if url[..end_idx].ends_with('/') {
-        let mut new_url = String::from(&url[..end_idx]);
-        new_url.push_str("index.html");
-        vec.push(new_url.into());
+    let mut decoded = percent_decode_str(path).decode_utf8().ok()?.into_owned();
+    if let Some(query_string) = query_string {
+        decoded.push('?');
+        decoded.push_str(query_string);
+    }
+    Some(decoded)
+}
+
+fn json_string(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
     }
-    vec
+    output.push('"');
+    output
+}
+
+fn accept_json(request: &Request) -> bool {
+    request.headers().iter().any(|header| {
+        header.field.equiv("Accept") && header.value.as_str().contains("application/json")
+    })
 }
 
 struct ContentEntry {
     pub content_address: jbk::reader::ContentAddress,
     pub mimetype: jbk::SmallBytes,
+    pub mtime: Option<u64>,
+    pub hash: Option<[u8; 32]>,
 }
 
 struct ContentBuilder {
     content_address_property: jbk::reader::builder::ContentProperty,
     content_mimetype_property: jbk::reader::builder::ArrayProperty,
+    content_mtime_property: Option<jbk::reader::builder::IntProperty>,
+    content_hash_property: Option<jbk::reader::builder::ArrayProperty>,
 }
 
 impl Builder for ContentBuilder {
@@ -51,6 +126,8 @@ impl Builder for ContentBuilder {
         Self {
             content_address_property: properties.content_address_property,
             content_mimetype_property: properties.content_mimetype_property.clone(),
+            content_mtime_property: properties.content_mtime_property.clone(),
+            content_hash_property: properties.content_hash_property.clone(),
         }
     }
 
@@ -60,23 +137,43 @@ impl Builder for ContentBuilder {
         self.content_mimetype_property
             .create(reader)?
             .resolve_to_vec(&mut mimetype)?;
+        let mtime = match &self.content_mtime_property {
+            None => None,
+            Some(mtime_property) => Some(mtime_property.create(reader)?).filter(|m| *m != 0),
+        };
+        let hash = match &self.content_hash_property {
+            None => None,
+            Some(hash_property) => read_hash(hash_property, reader)?,
+        };
         Ok(ContentEntry {
             content_address,
             mimetype,
+            mtime,
+            hash,
         })
     }
 }
 
+struct RedirectEntry {
+    pub target: jbk::SmallBytes,
+    pub status: RedirectStatus,
+    pub external: bool,
+}
+
 struct RedirectBuilder {
     target_property: jbk::reader::builder::ArrayProperty,
+    status_property: Option<jbk::reader::builder::IntProperty>,
+    external_property: Option<jbk::reader::builder::IntProperty>,
 }
 
 impl Builder for RedirectBuilder {
-    type Entry = jbk::SmallBytes;
+    type Entry = RedirectEntry;
 
     fn new(properties: &AllProperties) -> Self {
         Self {
             target_property: properties.redirect_target_property.clone(),
+            status_property: properties.redirect_status_property.clone(),
+            external_property: properties.redirect_external_property.clone(),
         }
     }
 
@@ -84,11 +181,62 @@ impl Builder for RedirectBuilder {
         let target_prop = self.target_property.create(reader)?;
         let mut target = jbk::SmallBytes::new();
         target_prop.resolve_to_vec(&mut target)?;
-        Ok(target)
+        let status = match &self.status_property {
+            None => RedirectStatus::default(),
+            Some(status_property) => {
+                RedirectStatus::from_code(status_property.create(reader)? as u16)
+                    .unwrap_or_default()
+            }
+        };
+        let external = match &self.external_property {
+            None => false,
+            Some(external_property) => external_property.create(reader)? != 0,
+        };
+        Ok(RedirectEntry {
+            target,
+            status,
+            external,
+        })
     }
 }
 
-type FullBuilder = (ContentBuilder, RedirectBuilder);
+struct AliasEntry {
+    pub target: jbk::SmallBytes,
+    pub mtime: Option<u64>,
+}
+
+struct AliasBuilder {
+    target_property: Option<jbk::reader::builder::ArrayProperty>,
+    mtime_property: Option<jbk::reader::builder::IntProperty>,
+}
+
+impl Builder for AliasBuilder {
+    type Entry = AliasEntry;
+
+    fn new(properties: &AllProperties) -> Self {
+        Self {
+            target_property: properties.alias_target_property.clone(),
+            mtime_property: properties.alias_mtime_property.clone(),
+        }
+    }
+
+    fn create_entry(&self, _idx: jbk::EntryIdx, reader: &ByteSlice) -> jbk::Result<Self::Entry> {
+        let target_prop = self
+            .target_property
+            .as_ref()
+            .expect("Alias entries only exist in layouts with an alias variant")
+            .create(reader)?;
+        let mut target = jbk::SmallBytes::new();
+        target_prop.resolve_to_vec(&mut target)?;
+        let mtime = match &self.mtime_property {
+            None => None,
+            Some(mtime_property) => Some(mtime_property.create(reader)?).filter(|m| *m != 0),
+        };
+        Ok(AliasEntry { target, mtime })
+    }
+}
+
+type FullBuilder = (ContentBuilder, RedirectBuilder, AliasBuilder, ());
 
 struct Part {
     pub start: u64,
@@ -101,7 +249,89 @@ struct Part {
 // A internal server, local to one thread.
 pub struct WajServer {
     waj: Arc<Waj>,
+    // The archives of the served namespace, from the top layer to the base one.
+    // Only `waj` if not served in a overlay.
+    layers: Vec<Arc<Waj>>,
     etag_value: String,
+    dir_listing: bool,
+}
+
+/// A item of a directory listing.
+struct ListingItem {
+    // Relative to the listed directory. Sub directories end with a `/`.
+    name: String,
+    kind: &'static str,
+    size: Option<u64>,
+    mimetype: Option<String>,
+}
+
+fn get_header<'a>(r: &'a Request, name: &str) -> Option<&'a str> {
+    r.headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+fn http_date(mtime: u64) -> String {
+    httpdate::fmt_http_date(std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime))
+}
+
+/// The `Content-Type` header of `mimetype`.
+///
+/// Mimetypes which can't be a header value (archives created by older versions)
+/// are served as `application/octet-stream`.
+fn content_type_header(mimetype: &str) -> Header {
+    let value = match mimetype.parse() {
+        Ok(value) => value,
+        Err(_) => {
+            warn!("Invalid mimetype {mimetype:?}");
+            "application/octet-stream".parse().unwrap()
+        }
+    };
+    Header {
+        field: "Content-Type".parse().unwrap(),
+        value,
+    }
+}
+
+fn parse_http_date(value: &str) -> Option<u64> {
+    httpdate::parse_http_date(value)
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// Is the content not modified since the `If-Modified-Since` date ?
+///
+/// `If-Modified-Since` is ignored if request contains a `If-None-Match`.
+fn not_modified_since(r: &Request, mtime: Option<u64>) -> bool {
+    if get_header(r, "If-None-Match").is_some() {
+        return false;
+    }
+    match (
+        mtime,
+        get_header(r, "If-Modified-Since").and_then(parse_http_date),
+    ) {
+        (Some(mtime), Some(since)) => mtime <= since,
+        _ => false,
+    }
+}
+
+/// Does the `If-None-Match` header match `etag` ?
+///
+/// `If-None-Match` uses the weak comparison, so `W/` prefixes are ignored.
+fn if_none_match(r: &Request, etag: &str) -> bool {
+    let strip_weak = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    match get_header(r, "If-None-Match") {
+        None => false,
+        Some(tags) => {
+            tags.trim() == "*"
+                || tags
+                    .split(',')
+                    .any(|tag| strip_weak(tag) == strip_weak(etag))
+        }
+    }
 }
 
 fn get_byte_range(r: &Request) -> Option<Result<ParsedRanges, ()>> {
@@ -119,7 +349,66 @@ impl WajServer {
         Ok(WajServer::new(waj, etag_value))
     }
     pub fn new(waj: Arc<Waj>, etag_value: String) -> Self {
-        Self { waj, etag_value }
+        Self {
+            layers: vec![Arc::clone(&waj)],
+            waj,
+            etag_value,
+            dir_listing: false,
+        }
+    }
+
+    pub(crate) fn waj(&self) -> &Arc<Waj> {
+        &self.waj
+    }
+
+    /// Answer search, directory listings and 404 page from all the `layers` of a overlay
+    /// (from the top layer to the base one) instead of from this archive only.
+    pub(crate) fn set_layers(&mut self, layers: Vec<Arc<Waj>>) {
+        self.layers = layers;
+    }
+
+    /// The index of the top most layer having a entry (whiteouts included) at `path`.
+    fn entry_layer(&self, path: &str) -> Option<usize> {
+        self.layers
+            .iter()
+            .position(|waj| waj.get_entry::<((), (), (), ())>(path).is_ok())
+    }
+
+    /// Is there a entry (whiteouts included) answering to the (percent decoded) `url` ?
+    ///
+    /// Search requests never match a entry.
+    pub(crate) fn has_entry(&self, url: &str) -> bool {
+        if search_query_string(url).is_some() {
+            return false;
+        }
+        url_variants(url).iter().any(|url| {
+            let url = url.strip_prefix('/').unwrap_or(url);
+            self.waj.get_entry::<((), (), (), ())>(url).is_ok()
+        })
+    }
+
+    /// Generate a listing of the directory content for urls ending with a `/`
+    /// if there is no corresponding entry (nor `index.html`).
+    pub fn enable_dir_listing(&mut self) {
+        self.dir_listing = true;
+    }
+
+    /// The etag of a content.
+    ///
+    /// A strong etag built from the content hash if we know it,
+    /// else the (weak) etag of the whole archive.
+    ///
+    /// Each encoding of a content is a different representation and so has its own etag.
+    fn content_etag(&self, hash: Option<[u8; 32]>, encoding: Option<Encoding>) -> String {
+        match (hash, encoding) {
+            (Some(hash), None) => format!("\"{}\"", blake3::Hash::from_bytes(hash).to_hex()),
+            (Some(hash), Some(encoding)) => format!(
+                "\"{}-{}\"",
+                blake3::Hash::from_bytes(hash).to_hex(),
+                encoding.name()
+            ),
+            (None, _) => self.etag_value.clone(),
+        }
     }
 
     fn build_response_from_read<R: std::io::Read + Send + 'static>(
@@ -144,8 +433,10 @@ impl WajServer {
         request: &Request,
         bytes: ByteRegion,
         head_request: bool,
+        etag: &str,
         etag_match: bool,
         status_code: u16,
+        mtime: Option<u64>,
     ) -> ResponseBox {
         let with_content = !head_request && !etag_match;
         let content_size = bytes.size().into_u64();
@@ -153,6 +444,9 @@ impl WajServer {
         let byte_range_request = if etag_match {
             // Do not try to parse byte range if we have a etag matching.
             None
+        } else if !Self::if_range_match(request, etag, mtime) {
+            // Content has changed, send the full content.
+            None
         } else {
             get_byte_range(request)
         };
@@ -239,15 +533,35 @@ impl WajServer {
         });
         response.add_header(Header {
             field: "ETag".parse().unwrap(),
-            value: self.etag_value.clone().into_ascii_string().unwrap(),
+            value: etag.into_ascii_string().unwrap(),
         });
         response.add_header(Header {
             field: "Accept-Ranges".parse().unwrap(),
             value: "bytes".parse().unwrap(),
         });
+        if let Some(mtime) = mtime {
+            response.add_header(Header {
+                field: "Last-Modified".parse().unwrap(),
+                value: http_date(mtime).parse().unwrap(),
+            });
+        }
         response
     }
 
+    /// Check the `If-Range` header.
+    ///
+    /// Returns true if there is no `If-Range` header or if it matches the content.
+    /// Dates must match exactly the modification time and weak etag never match.
+    fn if_range_match(request: &Request, etag: &str, mtime: Option<u64>) -> bool {
+        match get_header(request, "If-Range") {
+            None => true,
+            Some(if_range) => match parse_http_date(if_range) {
+                Some(date) => mtime == Some(date),
+                None => !if_range.starts_with("W/") && if_range == etag,
+            },
+        }
+    }
+
     /// Build a response from a content entry.
     ///
     /// The tricky part here is that we can have a found entry without a content
@@ -260,9 +574,11 @@ impl WajServer {
         request: &Request,
         bytes: jbk::reader::MayMissPack<ByteRegion>,
         head_request: bool,
+        etag: &str,
         etag_match: bool,
         status_code: u16,
         mimetype: &str,
+        mtime: Option<u64>,
     ) -> Result<ResponseBox, BaseError> {
         match bytes {
             jbk::reader::MayMissPack::MISSING(pack_info) => {
@@ -297,10 +613,7 @@ impl WajServer {
                     !head_request && !etag_match,
                     status_code,
                 );
-                response.add_header(Header {
-                    field: "Content-Type".parse().unwrap(),
-                    value: mimetype.parse().unwrap(),
-                });
+                response.add_header(content_type_header(mimetype));
                 response.add_header(Header {
                     field: "Cache-Control".parse().unwrap(),
                     value: "max-age=0, no-cache".parse().unwrap(),
@@ -312,18 +625,91 @@ impl WajServer {
                     request,
                     bytes,
                     head_request,
+                    etag,
                     etag_match,
                     status_code,
+                    mtime,
                 );
-                response.add_header(Header {
-                    field: "Content-Type".parse().unwrap(),
-                    value: mimetype.parse().unwrap(),
-                });
+                response.add_header(content_type_header(mimetype));
                 Ok(response)
             }
         }
     }
 
+    /// Build the response for a content entry stored at `path`.
+    ///
+    /// Select the precompressed variant to send according to `Accept-Encoding`.
+    fn handle_content(
+        &self,
+        request: &Request,
+        path: &str,
+        e: &ContentEntry,
+        head_request: bool,
+    ) -> Result<ResponseBox, WajError> {
+        let variants = self.waj.precompressed_variants(path)?;
+        let encoding = get_header(request, "Accept-Encoding").and_then(|accept| {
+            negotiate(
+                accept,
+                &variants.iter().map(|(e, _)| *e).collect::<Vec<_>>(),
+            )
+        });
+        let content_address = match encoding {
+            None => e.content_address,
+            Some(encoding) => {
+                variants
+                    .iter()
+                    .find(|(e, _)| *e == encoding)
+                    .expect("encoding is selected from variants")
+                    .1
+            }
+        };
+        let bytes = self
+            .waj
+            .get_bytes(content_address)?
+            .and_then(|m| m.transpose())
+            .ok_or(WajFormatError("Content address not valid"))?;
+        let etag = self.content_etag(e.hash, encoding);
+        let not_modified = if_none_match(request, &etag) || not_modified_since(request, e.mtime);
+        let mut response = self.build_content_response(
+            request,
+            bytes,
+            head_request,
+            &etag,
+            not_modified,
+            if not_modified { 304 } else { 200 },
+            &String::from_utf8_lossy(&e.mimetype),
+            e.mtime,
+        )?;
+        if let Some(encoding) = encoding {
+            response.add_header(Header {
+                field: "Content-Encoding".parse().unwrap(),
+                value: encoding.name().parse().unwrap(),
+            });
+        }
+        if !variants.is_empty() {
+            response.add_header(Header {
+                field: "Vary".parse().unwrap(),
+                value: "Accept-Encoding".parse().unwrap(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Get the content shared by a alias to `target` (and its path), following alias chains.
+    fn alias_content(&self, target: &[u8]) -> Option<(String, ContentEntry)> {
+        let mut target = String::from_utf8_lossy(target).into_owned();
+        for _ in 0..MAX_ALIAS_CHAIN {
+            match self.waj.get_entry::<FullBuilder>(&target) {
+                Ok(Entry::Content(e)) => return Some((target, e)),
+                Ok(Entry::Alias(alias)) => {
+                    target = String::from_utf8_lossy(&alias.target).into_owned()
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
     /// Handle a get/head request for a url
     ///
     /// Mostly search for the entry, and generate corresponding response or 404.
@@ -332,8 +718,11 @@ impl WajServer {
         request: &Request,
         url: &str,
         head_request: bool,
-        etag_match: bool,
-    ) -> Result<ResponseBox, BaseError> {
+    ) -> Result<ResponseBox, WajError> {
+        if let Some(query_string) = search_query_string(url) {
+            return self.handle_search(request, query_string, head_request);
+        }
+
         // Search for entry... Using some variation around url (remove querystring, add index.html...)
         for url in url_variants(url) {
             let url = url.strip_prefix('/').unwrap_or(&url);
@@ -341,68 +730,288 @@ impl WajServer {
                 trace!(" => {url}");
                 match e {
                     Entry::Content(e) => {
-                        let bytes = self
-                            .waj
-                            .get_bytes(e.content_address)?
-                            .and_then(|m| m.transpose())
-                            .ok_or(WajFormatError("Content address not valid"))?;
-                        return self.build_content_response(
-                            request,
-                            bytes,
-                            head_request,
-                            etag_match,
-                            if etag_match { 304 } else { 200 },
-                            &String::from_utf8_lossy(&e.mimetype),
-                        );
+                        return self.handle_content(request, url, &e, head_request);
                     }
                     Entry::Redirect(r) => {
-                        let mut response = Response::empty(StatusCode(302));
-                        let location = format!("{}", percent_encode(&r, CONTROLS));
+                        let mut response = Response::empty(StatusCode(r.status.code()));
+                        // External targets are absolute urls, already encoded.
+                        let location = if r.external {
+                            String::from_utf8_lossy(&r.target).into_owned()
+                        } else {
+                            format!("{}", percent_encode(&r.target, CONTROLS))
+                        };
+                        let value = match location.parse() {
+                            Ok(value) => value,
+                            Err(_) => {
+                                warn!("{url} redirects to invalid location {location}");
+                                return Ok(Response::empty(StatusCode(500)).boxed());
+                            }
+                        };
                         response.add_header(Header {
                             field: "Location".parse().unwrap(),
-                            value: location.parse().unwrap(),
+                            value,
                         });
                         return Ok(response.boxed());
                     }
+                    Entry::Alias(alias) => {
+                        if let Some((target, mut e)) = self.alias_content(&alias.target) {
+                            // The alias is served with its own modification time.
+                            if alias.mtime.is_some() {
+                                e.mtime = alias.mtime;
+                            }
+                            return self.handle_content(request, &target, &e, head_request);
+                        }
+                        let target = String::from_utf8_lossy(&alias.target);
+                        warn!("{url} is an alias to {target} which doesn't lead to a content");
+                    }
+                    Entry::Whiteout(()) => {
+                        // The path is hidden, don't try other variants.
+                        trace!("{url} is whiteout");
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.dir_listing {
+            let path = url.split_once('?').map_or(url, |(path, _)| path);
+            let path = path.strip_prefix('/').unwrap_or(path);
+            if path.is_empty() || path.ends_with('/') {
+                if let Some(response) = self.handle_dir_listing(request, path, head_request)? {
+                    return Ok(response);
                 }
             }
         }
 
         // No entry found. Return 404. If we have one in the Waj use it, else return empty 404.
         warn!("{url} not found");
-        if let Ok(Entry::Content(e)) = self.waj.get_entry::<FullBuilder>("404.html") {
-            let bytes = self
-                .waj
+        let page_404 = self.entry_layer("404.html").map(|layer| {
+            let waj = &self.layers[layer];
+            (waj, waj.get_entry::<FullBuilder>("404.html"))
+        });
+        if let Some((waj, Ok(Entry::Content(e)))) = page_404 {
+            let bytes = waj
                 .get_bytes(e.content_address)?
                 .and_then(|m| m.transpose())
                 .ok_or(WajFormatError("Content address not valid"))?;
 
             if let jbk::reader::MayMissPack::FOUND(bytes) = bytes {
-                let mut response =
-                    self.build_response_from_bytes(request, bytes, head_request, etag_match, 404);
-                response.add_header(Header {
-                    field: "Content-Type".parse().unwrap(),
-                    value: String::from_utf8_lossy(&e.mimetype).parse().unwrap(),
-                });
+                let etag = self.content_etag(e.hash, None);
+                let etag_match = if_none_match(request, &etag);
+                let mut response = self.build_response_from_bytes(
+                    request,
+                    bytes,
+                    head_request,
+                    &etag,
+                    etag_match,
+                    404,
+                    e.mtime,
+                );
+                response.add_header(content_type_header(&String::from_utf8_lossy(&e.mimetype)));
                 return Ok(response);
             }
         }
         Ok(Response::empty(StatusCode(404)).boxed())
     }
 
+    /// Describe a direct child of a directory found in the layer `layer` for the listing.
+    ///
+    /// Returns None for whiteouts and for entries hidden by a upper layer.
+    fn listing_item(
+        &self,
+        layer: usize,
+        directory: &str,
+        child: Child<(
+            crate::Content,
+            crate::Redirect,
+            crate::Alias,
+            crate::Whiteout,
+        )>,
+    ) -> Result<Option<ListingItem>, WajError> {
+        let waj = &self.layers[layer];
+        let visible = |path: &str| self.entry_layer(path) == Some(layer);
+        let (path, kind, content) = match child {
+            Child::Entry(Entry::Whiteout(_)) => return Ok(None),
+            Child::Directory(path) => (path, "directory", None),
+            Child::Entry(Entry::Content(e)) => (
+                String::from_utf8_lossy(e.path()).into_owned(),
+                "content",
+                Some(e),
+            ),
+            Child::Entry(Entry::Redirect(e)) => (
+                String::from_utf8_lossy(e.path()).into_owned(),
+                "redirect",
+                None,
+            ),
+            Child::Entry(Entry::Alias(e)) => {
+                let path = String::from_utf8_lossy(e.path()).into_owned();
+                if !visible(&path) {
+                    return Ok(None);
+                }
+                let content = waj.alias_content(&e)?.map(|(_, content)| content);
+                (path, "alias", content)
+            }
+        };
+        if kind != "directory" && !visible(&path) {
+            return Ok(None);
+        }
+        let (size, mimetype) = match content {
+            None => (None, None),
+            Some(e) => {
+                let size = match waj.get_bytes(e.content())?.and_then(|m| m.transpose()) {
+                    Some(jbk::reader::MayMissPack::FOUND(bytes)) => Some(bytes.size().into_u64()),
+                    _ => None,
+                };
+                (
+                    size,
+                    Some(String::from_utf8_lossy(e.mimetype()).into_owned()),
+                )
+            }
+        };
+        Ok(Some(ListingItem {
+            name: path[directory.len()..].to_owned(),
+            kind,
+            size,
+            mimetype,
+        }))
+    }
+
+    /// Answer a listing of the direct children of `directory`.
+    ///
+    /// Answer a html page, or json if asked with the `Accept` header.
+    /// Returns None if there is no entry in the directory.
+    ///
+    /// In a overlay, the children of all the layers are listed. A sub directory
+    /// is listed if any layer has entries in it.
+    fn handle_dir_listing(
+        &self,
+        request: &Request,
+        directory: &str,
+        head_request: bool,
+    ) -> Result<Option<ResponseBox>, WajError> {
+        let mut names = HashSet::new();
+        let mut items = vec![];
+        for (layer, waj) in self.layers.iter().enumerate() {
+            for child in waj.directory_children::<crate::FullBuilder>(directory)? {
+                if let Some(item) = self.listing_item(layer, directory, child?)? {
+                    if names.insert(item.name.clone()) {
+                        items.push(item);
+                    }
+                }
+            }
+        }
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        if items.is_empty() {
+            return Ok(None);
+        }
+        let (body, mimetype) = if accept_json(request) {
+            (dir_listing_json(directory, &items), "application/json")
+        } else {
+            (
+                dir_listing_html(directory, &items),
+                "text/html; charset=utf-8",
+            )
+        };
+        let size = body.len();
+        let mut response = Self::build_response_from_read(
+            std::io::Cursor::new(body),
+            Some(size),
+            !head_request,
+            200,
+        );
+        response.add_header(content_type_header(mimetype));
+        response.add_header(Header {
+            field: "Cache-Control".parse().unwrap(),
+            value: "max-age=86400, must-revalidate".parse().unwrap(),
+        });
+        Ok(Some(response))
+    }
+
+    /// Search `query` in all the layers.
+    ///
+    /// Results of entries hidden by a upper layer are dropped.
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, WajError> {
+        let mut results = vec![];
+        for (layer, waj) in self.layers.iter().enumerate() {
+            results.extend(
+                waj.search(query, limit)?
+                    .into_iter()
+                    .filter(|result| self.entry_layer(result.path()) == Some(layer)),
+            );
+        }
+        results.sort_by(|a, b| b.score().total_cmp(&a.score()));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    /// Handle a request on the search endpoint.
+    ///
+    /// Answer a html page, or json if asked with `format=json` or the `Accept` header.
+    fn handle_search(
+        &self,
+        request: &Request,
+        query_string: &str,
+        head_request: bool,
+    ) -> Result<ResponseBox, WajError> {
+        if !self.layers.iter().any(|waj| waj.has_full_text_index()) {
+            return Ok(Response::empty(StatusCode(404)).boxed());
+        }
+        let query = query_param(query_string, "q").unwrap_or_default();
+        let limit = query_param(query_string, "limit")
+            .and_then(|l| l.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .min(MAX_SEARCH_LIMIT);
+        let results = if query.trim().is_empty() {
+            vec![]
+        } else {
+            self.search(&query, limit)?
+        };
+
+        let as_json = match query_param(query_string, "format") {
+            Some(format) => format == "json",
+            None => accept_json(request),
+        };
+        let (body, mimetype) = if as_json {
+            (search_results_json(&query, &results), "application/json")
+        } else {
+            (
+                search_results_html(&query, &results),
+                "text/html; charset=utf-8",
+            )
+        };
+        let size = body.len();
+        let mut response = Self::build_response_from_read(
+            std::io::Cursor::new(body),
+            Some(size),
+            !head_request,
+            200,
+        );
+        response.add_header(content_type_header(mimetype));
+        response.add_header(Header {
+            field: "Cache-Control".parse().unwrap(),
+            value: "max-age=0, no-cache".parse().unwrap(),
+        });
+        Ok(response)
+    }
+
     /// Handle a request.
     ///
     /// This is mainly a wrapper around `handle_get` as we respond only to get/head request.
     /// The main work here is to:
     /// - Handle error (by returning a 500)
     /// - Handle get vs head (by requesting response without content)
-    /// - Handle etag by requesting response without content if etag match and answering a 304.
     ///
     /// Cache header is not handle here as it depends of the response itself.
     pub fn handle(&self, request: Request, url: &str, request_id: usize) {
         trace!("Get req {request:?}");
 
-        let url = percent_decode_str(url).decode_utf8().unwrap();
+        let url = match decode_url(url) {
+            Some(url) => url,
+            None => {
+                request.respond(Response::empty(StatusCode(400))).unwrap();
+                return;
+            }
+        };
 
         let now = std::time::Instant::now();
 
@@ -417,13 +1026,7 @@ impl WajServer {
             }
         };
 
-        let etag_match = if let Some(request_etag) = get_etag_from_headers(request.headers()) {
-            request_etag == self.etag_value
-        } else {
-            false
-        };
-
-        let ret = self.handle_get(&request, &url, head_request, etag_match);
+        let ret = self.handle_get(&request, &url, head_request);
 
         let elapsed_time = now.elapsed();
 
@@ -444,13 +1047,94 @@ impl WajServer {
     }
 }
 
-fn get_etag_from_headers(headers: &[Header]) -> Option<String> {
-    for header in headers {
-        if header.field.equiv("if-none-match") {
-            return Some(header.value.to_string());
+fn dir_listing_json(directory: &str, items: &[ListingItem]) -> String {
+    let items = items
+        .iter()
+        .map(|item| {
+            format!(
+                r#"{{"name":{},"type":"{}","size":{},"mimetype":{}}}"#,
+                json_string(&item.name),
+                item.kind,
+                item.size
+                    .map_or_else(|| "null".to_owned(), |s| s.to_string()),
+                item.mimetype
+                    .as_deref()
+                    .map_or_else(|| "null".to_owned(), json_string)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{"path":{},"entries":[{}]}}"#,
+        json_string(directory),
+        items
+    )
+}
+
+fn dir_listing_html(directory: &str, items: &[ListingItem]) -> String {
+    let mut body = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Index of /{0}</title></head><body>\
+         <h1>Index of /{0}</h1><table><thead><tr><th>Name</th><th>Size</th><th>Type</th></tr></thead><tbody>",
+        html_escape(directory)
+    );
+    if !directory.is_empty() {
+        body.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>");
+    }
+    for item in items {
+        // Links are relative to the listed directory.
+        body.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+            utf8_percent_encode(&item.name, PATH_ENCODE_SET),
+            html_escape(&item.name),
+            item.size.map(|s| s.to_string()).unwrap_or_default(),
+            html_escape(item.mimetype.as_deref().unwrap_or(item.kind))
+        ));
+    }
+    body.push_str("</tbody></table></body></html>");
+    body
+}
+
+fn search_results_json(query: &str, results: &[SearchResult]) -> String {
+    let results = results
+        .iter()
+        .map(|r| {
+            format!(
+                r#"{{"path":{},"score":{},"snippet":{}}}"#,
+                json_string(r.path()),
+                r.score(),
+                json_string(r.snippet())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{"query":{},"results":[{}]}}"#,
+        json_string(query),
+        results
+    )
+}
+
+fn search_results_html(query: &str, results: &[SearchResult]) -> String {
+    let mut body = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Search: {0}</title></head><body>\
+         <form action=\"search\"><input type=\"search\" name=\"q\" value=\"{0}\"><button>Search</button></form>",
+        html_escape(query)
+    );
+    if !query.trim().is_empty() {
+        body.push_str(&format!("<p>{} result(s)</p><ul>", results.len()));
+        for result in results {
+            // The search page is under `_waj/`, links are relative to the archive root.
+            body.push_str(&format!(
+                "<li><a href=\"../{}\">{}</a><p>{}</p></li>",
+                utf8_percent_encode(result.path(), PATH_ENCODE_SET),
+                html_escape(result.path()),
+                html_escape(result.snippet())
+            ));
         }
+        body.push_str("</ul>");
     }
-    None
+    body.push_str("</body></html>");
+    body
 }
 
 impl Router for WajServer {
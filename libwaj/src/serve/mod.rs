@@ -53,11 +53,50 @@ impl SubPathRouter {
 
 impl Router for SubPathRouter {
     fn route(&self, request: &Request) -> Option<(&WajServer, String)> {
-        let path = percent_decode_str(request.url()).decode_utf8().ok()?;
-
-        let path = &path[1..];
+        // The left part is percent decoded by the handler.
+        let path = request.url().strip_prefix('/')?;
         let (first_part, left_part) = path.split_once('/').unwrap_or((path, ""));
-        Some((self.0.get(first_part)?, left_part.into()))
+        let first_part = percent_decode_str(first_part).decode_utf8().ok()?;
+        Some((self.0.get(first_part.as_ref())?, left_part.into()))
+    }
+}
+
+/// Serve several archives as one namespace.
+///
+/// A request is answered by the top most layer having a entry for it.
+/// A whiteout entry hides the entries of the same path in the layers below.
+/// Requests matching no entry (search, directory listing, 404) are answered by
+/// merging all the layers, without the entries hidden by upper layers.
+pub struct OverlayRouter(Vec<WajServer>);
+
+impl OverlayRouter {
+    /// Create a overlay of `layers`, from the base layer to the top one.
+    pub fn new(mut layers: Vec<WajServer>) -> Self {
+        let wajs: Vec<_> = layers
+            .iter()
+            .rev()
+            .map(|layer| Arc::clone(layer.waj()))
+            .collect();
+        for layer in &mut layers {
+            layer.set_layers(wajs.clone());
+        }
+        Self(layers)
+    }
+}
+
+impl Router for OverlayRouter {
+    fn route(&self, request: &Request) -> Option<(&WajServer, String)> {
+        // Only the path is decoded, the query string may contain encoded `?` or `&`.
+        let url = request.url();
+        let path = url.split_once('?').map_or(url, |(path, _)| path);
+        let path = percent_decode_str(path).decode_utf8().ok()?;
+        let layer = self
+            .0
+            .iter()
+            .rev()
+            .find(|layer| layer.has_entry(&path))
+            .or_else(|| self.0.first())?;
+        Some((layer, request.url().into()))
     }
 }
 
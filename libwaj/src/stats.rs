@@ -0,0 +1,234 @@
+use crate::error::WajError;
+use crate::walk::{Operator, Walker};
+use crate::{Alias, Content, FullBuilder, Redirect, Waj, Whiteout};
+use std::collections::{btree_map, BTreeMap};
+
+/// Statistics about a content pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackStats {
+    pub pack_id: u16,
+
+    /// Number of content entries stored in the pack.
+    pub contents: u64,
+
+    /// Total (uncompressed) size of the contents stored in the pack.
+    pub size: u64,
+
+    /// Size of the pack itself (compressed contents and cluster tables).
+    pub compressed_size: u64,
+
+    /// The compression algorithm of the pack, if recorded in the archive.
+    pub compression: Option<String>,
+
+    /// The location of the pack, empty if the pack is in the container file.
+    pub location: String,
+
+    pub missing: bool,
+}
+
+impl PackStats {
+    pub fn is_missing(&self) -> bool {
+        self.missing
+    }
+}
+
+/// Statistics about the contents of a mimetype.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimetypeStats {
+    pub mimetype: String,
+
+    /// Number of content entries of this mimetype.
+    pub count: u64,
+
+    /// Total (uncompressed) size of those contents.
+    pub size: u64,
+}
+
+/// Statistics about a waj archive.
+///
+/// Created by [`crate::Waj::stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub uuid: String,
+    pub contents: u64,
+    pub redirects: u64,
+    pub aliases: u64,
+    pub whiteouts: u64,
+
+    /// Total (uncompressed) size of the contents (contents in missing packs are not counted).
+    pub content_size: u64,
+
+    /// The content packs, sorted by pack id.
+    pub packs: Vec<PackStats>,
+
+    /// The mimetypes, sorted by decreasing count.
+    pub mimetypes: Vec<MimetypeStats>,
+}
+
+impl Stats {
+    pub fn entries(&self) -> u64 {
+        self.contents + self.redirects + self.aliases + self.whiteouts
+    }
+
+    pub fn missing_packs(&self) -> impl Iterator<Item = &PackStats> {
+        self.packs.iter().filter(|p| p.is_missing())
+    }
+
+    /// The compressed size of the contents (size of the found packs).
+    pub fn compressed_size(&self) -> u64 {
+        self.packs
+            .iter()
+            .filter(|p| !p.is_missing())
+            .map(|p| p.compressed_size)
+            .sum()
+    }
+
+    /// The ratio between the compressed size and the size of the contents.
+    ///
+    /// `None` if there is no content.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.content_size == 0 {
+            None
+        } else {
+            Some(self.compressed_size() as f64 / self.content_size as f64)
+        }
+    }
+
+    /// The `n` first mimetypes by total size.
+    pub fn top_mimetypes_by_size(&self, n: usize) -> Vec<&MimetypeStats> {
+        let mut mimetypes: Vec<_> = self.mimetypes.iter().collect();
+        mimetypes.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| a.mimetype.cmp(&b.mimetype))
+        });
+        mimetypes.truncate(n);
+        mimetypes
+    }
+}
+
+#[derive(Default)]
+struct StatsContext {
+    contents: u64,
+    redirects: u64,
+    aliases: u64,
+    whiteouts: u64,
+    content_size: u64,
+    packs: BTreeMap<u16, PackStats>,
+    mimetypes: BTreeMap<String, MimetypeStats>,
+}
+
+struct StatsCollector<'a> {
+    waj: &'a Waj,
+    compression: Option<String>,
+}
+
+impl Operator<StatsContext, FullBuilder> for StatsCollector<'_> {
+    fn on_start(&self, _context: &mut StatsContext) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_stop(&self, _context: &mut StatsContext) -> jbk::Result<()> {
+        Ok(())
+    }
+
+    fn on_content(&self, context: &mut StatsContext, entry: &Content) -> jbk::Result<()> {
+        context.contents += 1;
+        let content_address = entry.content();
+        let pack_id = content_address.pack_id.into_u16();
+        let pack = match context.packs.entry(pack_id) {
+            btree_map::Entry::Occupied(e) => e.into_mut(),
+            btree_map::Entry::Vacant(e) => {
+                let pack_info = self
+                    .waj
+                    .get_manifest_pack()
+                    .get_content_pack_info(content_address.pack_id)?;
+                e.insert(PackStats {
+                    pack_id,
+                    contents: 0,
+                    size: 0,
+                    compressed_size: pack_info.pack_size.into_u64(),
+                    compression: self.compression.clone(),
+                    location: String::from_utf8_lossy(&pack_info.pack_location).into_owned(),
+                    missing: false,
+                })
+            }
+        };
+        pack.contents += 1;
+        let size = match self
+            .waj
+            .get_bytes(content_address)?
+            .and_then(|m| m.transpose())
+        {
+            Some(jbk::reader::MayMissPack::FOUND(bytes)) => bytes.size().into_u64(),
+            Some(jbk::reader::MayMissPack::MISSING(_)) => {
+                pack.missing = true;
+                0
+            }
+            None => 0,
+        };
+        pack.size += size;
+        context.content_size += size;
+        let mimetype = String::from_utf8_lossy(entry.mimetype()).into_owned();
+        let mimetype_stats = context
+            .mimetypes
+            .entry(mimetype.clone())
+            .or_insert_with(|| MimetypeStats {
+                mimetype,
+                count: 0,
+                size: 0,
+            });
+        mimetype_stats.count += 1;
+        mimetype_stats.size += size;
+        Ok(())
+    }
+
+    fn on_redirect(&self, context: &mut StatsContext, _entry: &Redirect) -> jbk::Result<()> {
+        context.redirects += 1;
+        Ok(())
+    }
+
+    fn on_alias(&self, context: &mut StatsContext, _entry: &Alias) -> jbk::Result<()> {
+        context.aliases += 1;
+        Ok(())
+    }
+
+    fn on_whiteout(&self, context: &mut StatsContext, _entry: &Whiteout) -> jbk::Result<()> {
+        context.whiteouts += 1;
+        Ok(())
+    }
+}
+
+impl Waj {
+    /// Compute statistics about the archive.
+    ///
+    /// All entries are read, but not the contents themselves.
+    /// Pack locations and sizes are read from the container manifest.
+    pub fn stats(&self) -> Result<Stats, WajError> {
+        let mut walker = Walker::new(self, StatsContext::default());
+        let creation_info = self.creation_info()?;
+        walker.run(&StatsCollector {
+            waj: self,
+            compression: creation_info
+                .get(crate::metadata::creation_info::COMPRESSION)
+                .map(str::to_owned),
+        })?;
+        let context = walker.into_context();
+        let mut mimetypes: Vec<_> = context.mimetypes.into_values().collect();
+        mimetypes.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.mimetype.cmp(&b.mimetype))
+        });
+        Ok(Stats {
+            uuid: self.uuid().to_string(),
+            contents: context.contents,
+            redirects: context.redirects,
+            aliases: context.aliases,
+            whiteouts: context.whiteouts,
+            content_size: context.content_size,
+            packs: context.packs.into_values().collect(),
+            mimetypes,
+        })
+    }
+}
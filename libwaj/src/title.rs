@@ -0,0 +1,123 @@
+use crate::common::{partition_point, ReadEntry, TitleProperties};
+use crate::error::BaseError;
+use jbk::reader::builder::PropertyBuilderTrait;
+use jbk::reader::Range;
+
+/// Normalize a title to be used as key in the title index.
+///
+/// Normalized titles are lowercased and their whitespaces collapsed,
+/// so search is case insensitive.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A entry of the title index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleEntry {
+    title: String,
+    path: String,
+}
+
+impl TitleEntry {
+    /// The title, as found in the html page.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The path of the entry having this title.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+struct TitleBuilder<'a> {
+    properties: &'a TitleProperties,
+}
+
+impl jbk::reader::builder::BuilderTrait for TitleBuilder<'_> {
+    type Entry = TitleEntry;
+    type Error = BaseError;
+
+    fn create_entry(&self, idx: jbk::EntryIdx) -> Result<Option<Self::Entry>, Self::Error> {
+        self.properties
+            .store
+            .get_entry_reader(idx)
+            .map(|reader| {
+                let mut title = jbk::SmallBytes::new();
+                self.properties
+                    .title_property
+                    .create(&reader)?
+                    .resolve_to_vec(&mut title)?;
+                let mut path = jbk::SmallBytes::new();
+                self.properties
+                    .path_property
+                    .create(&reader)?
+                    .resolve_to_vec(&mut path)?;
+                Ok(TitleEntry {
+                    title: String::from_utf8_lossy(&title).into_owned(),
+                    path: String::from_utf8_lossy(&path).into_owned(),
+                })
+            })
+            .transpose()
+    }
+}
+
+pub(crate) struct TitleIndex {
+    index: jbk::reader::Index,
+    properties: TitleProperties,
+}
+
+impl TitleIndex {
+    pub fn new(container: &jbk::reader::Container) -> Result<Option<Self>, BaseError> {
+        let index = match container
+            .get_directory_pack()
+            .get_index_from_name("waj_titles")?
+        {
+            None => return Ok(None),
+            Some(index) => index,
+        };
+        let properties = TitleProperties::new(
+            index.get_store(container.get_entry_storage())?,
+            container.get_value_storage(),
+        )?;
+        Ok(Some(Self { index, properties }))
+    }
+
+    fn key(&self, idx: jbk::EntryIdx) -> jbk::Result<jbk::SmallBytes> {
+        let reader = self
+            .properties
+            .store
+            .get_entry_reader(idx)
+            .expect("idx should be valid");
+        let mut key = jbk::SmallBytes::new();
+        self.properties
+            .key_property
+            .create(&reader)?
+            .resolve_to_vec(&mut key)?;
+        Ok(key)
+    }
+
+    /// Get the titles starting with `prefix`, sorted by normalized title.
+    pub fn with_prefix(
+        &self,
+        prefix: &str,
+        max_results: Option<usize>,
+    ) -> Result<Vec<TitleEntry>, BaseError> {
+        let prefix = normalize_title(prefix);
+        let prefix = prefix.as_bytes();
+        let start = self.index.offset();
+        let end = start + self.index.count();
+        let first = partition_point(start, end, |idx| Ok(self.key(idx)?.as_slice() < prefix))?;
+        let last = partition_point(first, end, |idx| Ok(self.key(idx)?.starts_with(prefix)))?;
+        let builder = TitleBuilder {
+            properties: &self.properties,
+        };
+        ReadEntry::new_with_bounds(first, last, &builder)
+            .take(max_results.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
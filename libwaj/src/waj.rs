@@ -1,15 +1,36 @@
 use crate::error::{BaseError, WajError, WajFormatError};
+use crate::CommonEntry;
 
-use super::common::{AllProperties, Comparator, Entry, FullBuilderTrait, RealBuilder};
+use super::common::{
+    url_variants, AllProperties, Comparator, Entry, FullBuilderTrait, MetadataProperties,
+    ReadEntry, RealBuilder,
+};
+use super::encoding::{Encoding, EncodingIndex};
+use super::entry_reader::EntryReader;
+use super::fts::{build_snippet, tokenize, FtsIndex, SearchResult};
+use super::metadata::{Metadata, MetadataBuilder};
+use super::prefix::{prefix_bounds, DirectoryChildren, PrefixEntries};
+use super::title::{normalize_title, TitleEntry, TitleIndex};
 use jbk::reader::Range;
+use std::collections::BTreeMap;
+use std::io::Read;
 use std::path::Path;
 
 pub struct Waj {
     container: jbk::reader::Container,
     pub(crate) root_index: jbk::reader::Index,
     pub(crate) properties: AllProperties,
+    title_index: Option<TitleIndex>,
+    fts_index: Option<FtsIndex>,
+    encoding_index: Option<EncodingIndex>,
 }
 
+// Only the beginning of the content is used to build search snippets.
+const SNIPPET_SEARCH_SIZE: u64 = 256 * 1024;
+
+// Maximum number of aliases followed to reach the shared content.
+pub(crate) const MAX_ALIAS_CHAIN: usize = 10;
+
 impl std::ops::Deref for Waj {
     type Target = jbk::reader::Container;
     fn deref(&self) -> &Self::Target {
@@ -35,11 +56,17 @@ impl Waj {
             .get_index_from_name("waj_entries")?
             .ok_or(WajFormatError("No `waj_entries` in the archive"))?;
         let properties = create_properties(&container, &root_index)?;
+        let title_index = TitleIndex::new(&container)?;
+        let fts_index = FtsIndex::new(&container)?;
+        let encoding_index = EncodingIndex::new(&container)?;
 
         Ok(Self {
             container,
             root_index,
             properties,
+            title_index,
+            fts_index,
+            encoding_index,
         })
     }
 
@@ -68,4 +95,253 @@ impl Waj {
             }
         }
     }
+
+    /// Get the entry served for `url`, as `waj serve` does.
+    ///
+    /// `url` as is, `url` without its query string and `url + "index.html"` (if `url` ends with a `/`)
+    /// are searched in that order. Returns the path of the found entry with the entry.
+    pub fn get_entry_from_url<B>(&self, url: &str) -> Result<(String, Entry<B::Entry>), WajError>
+    where
+        B: FullBuilderTrait,
+    {
+        let url = url.strip_prefix('/').unwrap_or(url);
+        for variant in url_variants(url) {
+            match self.get_entry::<B>(&variant) {
+                Ok(entry) => return Ok((variant.into_owned(), entry)),
+                Err(WajError::PathNotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(WajError::PathNotFound(url.to_string()))
+    }
+
+    /// Get the content shared by `alias`, with its path.
+    ///
+    /// Chains of aliases are followed (up to 10 aliases).
+    /// Returns `None` if the alias doesn't lead to a content.
+    pub fn alias_content(
+        &self,
+        alias: &crate::Alias,
+    ) -> Result<Option<(String, crate::Content)>, WajError> {
+        self.follow_aliases(String::from_utf8_lossy(alias.target()).into_owned())
+    }
+
+    pub(crate) fn follow_aliases(
+        &self,
+        mut target: String,
+    ) -> Result<Option<(String, crate::Content)>, WajError> {
+        for _ in 0..MAX_ALIAS_CHAIN {
+            match self.get_entry::<crate::FullBuilder>(&target) {
+                Ok(Entry::Content(content)) => return Ok(Some((target, content))),
+                Ok(Entry::Alias(alias)) => {
+                    target = String::from_utf8_lossy(alias.target()).into_owned()
+                }
+                Ok(_) | Err(WajError::PathNotFound(_)) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get a reader on the content of the entry at `path`.
+    ///
+    /// Aliases are resolved to the content they share. Redirects are not followed.
+    pub fn read_entry(&self, path: &str) -> Result<EntryReader, WajError> {
+        match self.get_entry::<crate::FullBuilder>(path)? {
+            Entry::Content(e) => self.read_content(&e),
+            Entry::Alias(a) => match self.alias_content(&a)? {
+                Some((_, e)) => self.read_content(&e),
+                None => Err(WajError::NotAContent(path.to_owned())),
+            },
+            Entry::Redirect(_) | Entry::Whiteout(_) => Err(WajError::NotAContent(path.to_owned())),
+        }
+    }
+
+    /// Get a reader on the content of a content entry already read.
+    pub fn read_content(&self, content: &crate::Content) -> Result<EntryReader, WajError> {
+        let bytes = self
+            .get_bytes(content.content())?
+            .and_then(|m| m.transpose())
+            .ok_or(WajFormatError("Content address not valid"))?;
+        match bytes {
+            jbk::reader::MayMissPack::MISSING(_) => Err(WajError::MissingPack(
+                String::from_utf8_lossy(content.path()).into_owned(),
+            )),
+            jbk::reader::MayMissPack::FOUND(bytes) => Ok(EntryReader::new(bytes)),
+        }
+    }
+
+    /// Iterate on the entries whose path starts with `prefix`, sorted by path.
+    ///
+    /// Only the entries matching the prefix are read.
+    pub fn entries_with_prefix<B>(&self, prefix: &str) -> Result<PrefixEntries<B>, WajError>
+    where
+        B: FullBuilderTrait,
+    {
+        let (start, end) = prefix_bounds(&self.properties, &self.root_index, prefix.as_bytes())?;
+        Ok(PrefixEntries::new(&self.properties, start, end))
+    }
+
+    /// Iterate on the direct children of the directory `directory`, sorted by path.
+    ///
+    /// Entries in sub directories are not returned, the sub directories are returned instead.
+    /// `""` is the root directory.
+    pub fn directory_children<B>(
+        &self,
+        directory: &str,
+    ) -> Result<DirectoryChildren<'_, B>, WajError>
+    where
+        B: FullBuilderTrait,
+    {
+        let mut directory = directory.as_bytes().to_vec();
+        if !directory.is_empty() && !directory.ends_with(b"/") {
+            directory.push(b'/');
+        }
+        let (start, end) = prefix_bounds(&self.properties, &self.root_index, &directory)?;
+        Ok(DirectoryChildren::new(
+            &self.properties,
+            directory,
+            start,
+            end,
+        ))
+    }
+
+    /// Get the archive level metadata.
+    ///
+    /// Archives created without metadata return an empty `Metadata`.
+    pub fn metadata(&self) -> Result<Metadata, WajError> {
+        self.read_metadata_index("waj_metadata")
+    }
+
+    /// Get the info recorded by the creator (compression, ...).
+    ///
+    /// Those are not part of the user metadata.
+    pub(crate) fn creation_info(&self) -> Result<Metadata, WajError> {
+        self.read_metadata_index("waj_creation_info")
+    }
+
+    fn read_metadata_index(&self, index_name: &str) -> Result<Metadata, WajError> {
+        let index = match self
+            .container
+            .get_directory_pack()
+            .get_index_from_name(index_name)?
+        {
+            None => return Ok(Metadata::default()),
+            Some(index) => index,
+        };
+        let properties = MetadataProperties::new(
+            index.get_store(self.container.get_entry_storage())?,
+            self.container.get_value_storage(),
+        )?;
+        let builder = MetadataBuilder::new(properties);
+        let values = ReadEntry::new(&index, &builder).collect::<Result<BTreeMap<_, _>, _>>()?;
+        Ok(Metadata::new(values))
+    }
+
+    /// Search entries by title.
+    ///
+    /// Returns the entries whose title starts with `prefix` (case insensitive),
+    /// sorted by title. At most `max_results` entries are returned.
+    /// Archives without title index return no entry.
+    pub fn titles_with_prefix(
+        &self,
+        prefix: &str,
+        max_results: Option<usize>,
+    ) -> Result<Vec<TitleEntry>, WajError> {
+        match &self.title_index {
+            None => Ok(vec![]),
+            Some(title_index) => Ok(title_index.with_prefix(prefix, max_results)?),
+        }
+    }
+
+    /// Get the entry having exactly the title `title` (case insensitive).
+    ///
+    /// If several entries share the same title, the first one (by path) is returned.
+    pub fn get_entry_by_title<B>(&self, title: &str) -> Result<Entry<B::Entry>, WajError>
+    where
+        B: FullBuilderTrait,
+    {
+        // Exact match, if any, is the first entry starting with `title`.
+        let normalized = normalize_title(title);
+        let found = self
+            .titles_with_prefix(title, Some(1))?
+            .into_iter()
+            .find(|t| normalize_title(t.title()) == normalized);
+        match found {
+            None => Err(WajError::PathNotFound(format!(
+                "Cannot found entry with title {title}"
+            ))),
+            Some(t) => self.get_entry::<B>(t.path()),
+        }
+    }
+
+    /// Get the precompressed variants of the content at `path`.
+    ///
+    /// Archives created without precompressed variants return no variant.
+    pub fn precompressed_variants(
+        &self,
+        path: &str,
+    ) -> Result<Vec<(Encoding, jbk::ContentAddress)>, WajError> {
+        match &self.encoding_index {
+            None => Ok(vec![]),
+            Some(encoding_index) => Ok(encoding_index.variants(path)?),
+        }
+    }
+
+    /// Does the archive contain a full text index ?
+    pub fn has_full_text_index(&self) -> bool {
+        self.fts_index.is_some()
+    }
+
+    /// Search entries matching `query` in the full text index.
+    ///
+    /// Results are sorted by decreasing score. At most `max_results` are returned.
+    /// Archives without full text index return no result.
+    pub fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, WajError> {
+        let fts_index = match &self.fts_index {
+            None => return Ok(vec![]),
+            Some(fts_index) => fts_index,
+        };
+        let mut words: Vec<String> = tokenize(query).collect();
+        words.sort();
+        words.dedup();
+        fts_index
+            .search(&self.container, &words, max_results)?
+            .into_iter()
+            .map(|(path, score)| {
+                let snippet = self.snippet(&path, &words)?;
+                Ok(SearchResult {
+                    path,
+                    score,
+                    snippet,
+                })
+            })
+            .collect()
+    }
+
+    fn snippet(&self, path: &str, words: &[String]) -> Result<String, WajError> {
+        let content_address = match self.get_entry::<crate::FullBuilder>(path)? {
+            Entry::Content(e) => e.content(),
+            Entry::Alias(a) => match self.alias_content(&a)? {
+                Some((_, e)) => e.content(),
+                None => return Ok(String::new()),
+            },
+            Entry::Redirect(_) | Entry::Whiteout(_) => return Ok(String::new()),
+        };
+        let bytes = self
+            .get_bytes(content_address)?
+            .and_then(|m| m.transpose())
+            .ok_or(WajFormatError("Content address not valid"))?;
+        match bytes {
+            jbk::reader::MayMissPack::MISSING(_) => Ok(String::new()),
+            jbk::reader::MayMissPack::FOUND(bytes) => {
+                let mut data = vec![];
+                bytes
+                    .stream()
+                    .take(SNIPPET_SEARCH_SIZE)
+                    .read_to_end(&mut data)?;
+                Ok(build_snippet(&crate::html::extract_text(&data), words))
+            }
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::error::WajError;
 
 use super::common::*;
+use super::prefix::prefix_bounds;
 use super::Waj;
 use jbk::reader::Range;
 
@@ -17,6 +18,22 @@ pub trait Operator<Context, Builder: FullBuilderTrait> {
         context: &mut Context,
         entry: &<Builder::Entry as EntryDef>::Redirect,
     ) -> jbk::Result<()>;
+    /// Called for the alias entries. Aliases are ignored by default.
+    fn on_alias(
+        &self,
+        _context: &mut Context,
+        _entry: &<Builder::Entry as EntryDef>::Alias,
+    ) -> jbk::Result<()> {
+        Ok(())
+    }
+    /// Called for the whiteout entries. Whiteouts are ignored by default.
+    fn on_whiteout(
+        &self,
+        _context: &mut Context,
+        _entry: &<Builder::Entry as EntryDef>::Whiteout,
+    ) -> jbk::Result<()> {
+        Ok(())
+    }
 }
 
 pub struct Walker<'a, Context> {
@@ -29,31 +46,63 @@ impl<'a, Context> Walker<'a, Context> {
         Self { waj, context }
     }
 
+    /// Consume the walker and get back the context.
+    pub fn into_context(self) -> Context {
+        self.context
+    }
+
     pub fn run<B>(&mut self, op: &dyn Operator<Context, B>) -> Result<(), WajError>
     where
         B: FullBuilderTrait,
     {
         let builder = RealBuilder::<B>::new(&self.waj.properties);
 
+        let start = self.waj.root_index.offset();
+        let end = start + self.waj.root_index.count();
+
+        op.on_start(&mut self.context)?;
+        self._run(start, end, &builder, op)?;
+        Ok(op.on_stop(&mut self.context)?)
+    }
+
+    /// Same as `run` but only walk the entries whose path starts with `prefix`.
+    pub fn run_with_prefix<B>(
+        &mut self,
+        prefix: &str,
+        op: &dyn Operator<Context, B>,
+    ) -> Result<(), WajError>
+    where
+        B: FullBuilderTrait,
+    {
+        let builder = RealBuilder::<B>::new(&self.waj.properties);
+        let (start, end) = prefix_bounds(
+            &self.waj.properties,
+            &self.waj.root_index,
+            prefix.as_bytes(),
+        )?;
+
         op.on_start(&mut self.context)?;
-        self._run(&self.waj.root_index, &builder, op)?;
+        self._run(start, end, &builder, op)?;
         Ok(op.on_stop(&mut self.context)?)
     }
 
-    fn _run<R: Range, B>(
+    fn _run<B>(
         &mut self,
-        range: &R,
+        start: jbk::EntryIdx,
+        end: jbk::EntryIdx,
         builder: &RealBuilder<B>,
         op: &dyn Operator<Context, B>,
     ) -> Result<(), WajError>
     where
         B: FullBuilderTrait,
     {
-        let read_entry = ReadEntry::new(range, builder);
+        let read_entry = ReadEntry::new_with_bounds(start, end, builder);
         for entry in read_entry {
             match entry? {
                 Entry::Content(e) => op.on_content(&mut self.context, &e)?,
                 Entry::Redirect(e) => op.on_redirect(&mut self.context, &e)?,
+                Entry::Alias(e) => op.on_alias(&mut self.context, &e)?,
+                Entry::Whiteout(e) => op.on_whiteout(&mut self.context, &e)?,
             }
         }
         Ok(())
@@ -0,0 +1,68 @@
+use libwaj::create::{EntryKind, EntryTrait};
+use libwaj::error::CreatorError;
+use std::borrow::Cow;
+use std::io::Read;
+
+mod utils;
+use utils::*;
+
+struct Alias {
+    path: String,
+    target: String,
+}
+
+impl EntryTrait for Alias {
+    fn kind(&self) -> Result<Option<EntryKind>, CreatorError> {
+        Ok(Some(EntryKind::Alias(self.target.clone())))
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.path)
+    }
+}
+
+#[test]
+fn test_add_content() {
+    let waj_file = temp_path("test_add_content.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    creator
+        .add_content_with_mtime(
+            "index.html",
+            "<html><head><title>Generated</title></head></html>".as_bytes(),
+            "text/html",
+            1_000_000,
+        )
+        .unwrap();
+    creator
+        .add_content_bytes("style.css", "body {}", "text/css; charset=utf-8")
+        .unwrap();
+    creator
+        .add_entry(&Alias {
+            path: "home.html".into(),
+            target: "index.html".into(),
+        })
+        .unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let mut content = String::new();
+    waj.read_entry("style.css")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "body {}");
+    match waj.get_entry::<libwaj::FullBuilder>("index.html").unwrap() {
+        libwaj::Entry::Content(c) => {
+            assert_eq!(c.mimetype(), b"text/html");
+            assert_eq!(c.mtime(), Some(1_000_000));
+        }
+        _ => panic!("index.html must be a content"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("home.html").unwrap() {
+        libwaj::Entry::Alias(a) => assert_eq!(a.target(), b"index.html"),
+        _ => panic!("home.html must be a alias"),
+    }
+    let titles = waj.titles_with_prefix("Gen", None).unwrap();
+    assert_eq!(titles.len(), 1);
+    assert_eq!(titles[0].path(), "index.html");
+}
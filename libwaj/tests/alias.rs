@@ -0,0 +1,79 @@
+mod utils;
+
+use libwaj::CommonEntry;
+use utils::*;
+
+#[test]
+fn test_duplicate_as_alias() {
+    let content = "<html><body>Same content</body></html>";
+    let waj_file = create_waj(
+        "test_alias.waj",
+        &[
+            ("a.html", content),
+            ("b.html", content),
+            // Same content but different mimetype.
+            ("c.txt", content),
+        ],
+        |_| {},
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let a = waj.get_entry::<libwaj::FullBuilder>("a.html").unwrap();
+    let b = waj.get_entry::<libwaj::FullBuilder>("b.html").unwrap();
+    // Files are walked in filesystem order, so we don't know which one is the alias.
+    let (content, alias) = match (a, b) {
+        (libwaj::Entry::Content(c), libwaj::Entry::Alias(a)) => (c, a),
+        (libwaj::Entry::Alias(a), libwaj::Entry::Content(c)) => (c, a),
+        _ => panic!("One of a.html and b.html must be a alias to the other"),
+    };
+    assert_eq!(alias.target(), content.path());
+
+    assert!(matches!(
+        waj.get_entry::<libwaj::FullBuilder>("c.txt").unwrap(),
+        libwaj::Entry::Content(_)
+    ));
+}
+
+#[test]
+fn test_alias_chain() {
+    let waj_file = temp_path("test_alias_chain.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    creator.enable_full_text_index();
+    let content = "<html><head><title>Same page</title></head><body>Shared words</body></html>";
+    creator
+        .add_content_bytes("a.html", content, "text/html")
+        .unwrap();
+    // Deduplicated as a alias of a.html.
+    creator
+        .add_content_bytes("b.html", content, "text/html")
+        .unwrap();
+    // c.html -> b.html -> a.html
+    creator
+        .add_entry(&libwaj::create::AliasEntry {
+            path: "c.html".into(),
+            target: "b.html".into(),
+            mtime: Some(1_000_000),
+        })
+        .unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let alias = match waj.get_entry::<libwaj::FullBuilder>("c.html").unwrap() {
+        libwaj::Entry::Alias(alias) => alias,
+        _ => panic!("c.html must be a alias"),
+    };
+    assert_eq!(alias.mtime(), Some(1_000_000));
+    let (path, _) = waj.alias_content(&alias).unwrap().unwrap();
+    assert_eq!(path, "a.html");
+    let mut read = String::new();
+    std::io::Read::read_to_string(&mut waj.read_entry("c.html").unwrap(), &mut read).unwrap();
+    assert_eq!(read, content);
+
+    // Only the first entry of the shared content is indexed.
+    let titles = waj.titles_with_prefix("same", None).unwrap();
+    assert_eq!(titles.len(), 1);
+    assert_eq!(titles[0].path(), "a.html");
+    let results = waj.search("shared", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].path(), "a.html");
+}
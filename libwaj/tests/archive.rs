@@ -0,0 +1,169 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+mod utils;
+use utils::*;
+
+fn check_archive(waj_file: &Path) {
+    let waj = libwaj::Waj::new(waj_file).unwrap();
+    match waj.get_entry::<libwaj::FullBuilder>("index.html").unwrap() {
+        libwaj::Entry::Content(c) => assert_eq!(c.mimetype(), b"text/html"),
+        _ => panic!("index.html must be a content"),
+    }
+    let mut content = String::new();
+    waj.read_entry("docs/page")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "<html>Page</html>");
+    match waj.get_entry::<libwaj::FullBuilder>("docs/page").unwrap() {
+        libwaj::Entry::Content(c) => assert_eq!(c.mimetype(), b"text/html"),
+        _ => panic!("docs/page must be a content"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("home.html").unwrap() {
+        libwaj::Entry::Redirect(r) => assert_eq!(r.target(), b"index.html"),
+        _ => panic!("home.html must be a redirect"),
+    }
+    // Symlinks to directories are not added.
+    assert!(waj
+        .get_entry::<libwaj::FullBuilder>("documentation")
+        .is_err());
+    assert_eq!(waj.stats().unwrap().entries(), 3);
+}
+
+fn build_tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut add_member = |path: &str, entry_type: tar::EntryType, target: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        if !target.is_empty() {
+            header.set_link_name(target).unwrap();
+        }
+        builder.append_data(&mut header, path, data).unwrap();
+    };
+    add_member("site/", tar::EntryType::Directory, "", b"");
+    add_member(
+        "site/index.html",
+        tar::EntryType::Regular,
+        "",
+        b"<html>Index</html>",
+    );
+    add_member("site/home.html", tar::EntryType::Symlink, "index.html", b"");
+    add_member("site/documentation", tar::EntryType::Symlink, "docs", b"");
+    add_member(
+        "site/docs/page",
+        tar::EntryType::Regular,
+        "",
+        b"<html>Page</html>",
+    );
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_tar() {
+    let tar_file = temp_path("test_tar.tar");
+    std::fs::write(&tar_file, build_tar()).unwrap();
+    let waj_file = temp_path("test_tar.waj");
+    let mut creator = new_creator(&waj_file, "site".as_ref());
+    creator.add_from_tar(&tar_file).unwrap();
+    creator.finalize().unwrap();
+    check_archive(&waj_file);
+}
+
+#[test]
+fn test_tar_gz_reader() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&build_tar()).unwrap();
+    let tar_gz = encoder.finish().unwrap();
+    let waj_file = temp_path("test_tar_gz_reader.waj");
+    let mut creator = new_creator(&waj_file, "site".as_ref());
+    creator
+        .add_from_tar_reader(std::io::Cursor::new(tar_gz))
+        .unwrap();
+    creator.finalize().unwrap();
+    check_archive(&waj_file);
+}
+
+#[test]
+fn test_zip() {
+    let zip_file = temp_path("test_zip.zip");
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&zip_file).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    writer.add_directory("site/", options).unwrap();
+    writer.start_file("site/index.html", options).unwrap();
+    writer.write_all(b"<html>Index</html>").unwrap();
+    writer
+        .add_symlink("site/home.html", "index.html", options)
+        .unwrap();
+    writer
+        .add_symlink("site/documentation", "docs", options)
+        .unwrap();
+    writer.start_file("site/docs/page", options).unwrap();
+    writer.write_all(b"<html>Page</html>").unwrap();
+    writer.finish().unwrap();
+
+    let waj_file = temp_path("test_zip.waj");
+    let mut creator = new_creator(&waj_file, "site".as_ref());
+    creator.add_from_zip(&zip_file).unwrap();
+    creator.finalize().unwrap();
+    check_archive(&waj_file);
+}
+
+#[test]
+fn test_tar_members() {
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut add_member = |path: &str, entry_type: tar::EntryType, target: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        if !target.is_empty() {
+            header.set_link_name(target).unwrap();
+        }
+        builder.append_data(&mut header, path, data).unwrap();
+    };
+    add_member(
+        "site/index.html",
+        tar::EntryType::Regular,
+        "",
+        b"<html>Old</html>",
+    );
+    // Outside of the stripped prefix, skipped.
+    add_member("other/readme", tar::EntryType::Regular, "", b"Readme");
+    add_member(
+        "site/copy.html",
+        tar::EntryType::Link,
+        "site/index.html",
+        b"",
+    );
+    // The last member wins.
+    add_member(
+        "site/index.html",
+        tar::EntryType::Regular,
+        "",
+        b"<html>New</html>",
+    );
+    let tar = builder.into_inner().unwrap();
+
+    let waj_file = temp_path("test_tar_members.waj");
+    let mut creator = new_creator(&waj_file, "site".as_ref());
+    creator
+        .add_from_tar_reader(std::io::Cursor::new(tar))
+        .unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let mut content = String::new();
+    waj.read_entry("index.html")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "<html>New</html>");
+    match waj.get_entry::<libwaj::FullBuilder>("copy.html").unwrap() {
+        libwaj::Entry::Alias(a) => assert_eq!(a.target(), b"index.html"),
+        _ => panic!("copy.html must be a alias"),
+    }
+    assert_eq!(waj.stats().unwrap().entries(), 2);
+}
@@ -0,0 +1,62 @@
+mod utils;
+
+use libwaj::{Issue, RedirectStatus};
+use utils::*;
+
+#[test]
+fn test_check() {
+    let waj_file = create_waj(
+        "test_check.waj",
+        &[("page.html", "<html></html>")],
+        |creator| {
+            let redirects = [
+                ("ok", "page.html"),
+                ("dangling", "nothing.html"),
+                ("loop_a", "loop_b"),
+                ("loop_b", "loop_a"),
+                ("chain_1", "chain_2"),
+                ("chain_2", "chain_3"),
+                ("chain_3", "page.html"),
+            ];
+            for (path, target) in redirects {
+                creator
+                    .add_redirect(path, target, RedirectStatus::Found, false)
+                    .unwrap();
+            }
+            creator
+                .add_redirect(
+                    "external",
+                    "https://example.com",
+                    RedirectStatus::Found,
+                    true,
+                )
+                .unwrap();
+        },
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let report = waj.check(10).unwrap();
+    assert_eq!(report.entries, 9);
+    assert_eq!(
+        report.issues,
+        vec![
+            Issue::DanglingRedirect {
+                path: "dangling".into(),
+                target: "nothing.html".into()
+            },
+            Issue::RedirectLoop {
+                path: "loop_a".into()
+            },
+            Issue::RedirectLoop {
+                path: "loop_b".into()
+            },
+        ]
+    );
+
+    let report = waj.check(2).unwrap();
+    assert!(report.issues.contains(&Issue::RedirectChainTooLong {
+        path: "chain_1".into(),
+        length: 2
+    }));
+    assert!(!report.is_ok());
+}
@@ -0,0 +1,59 @@
+mod utils;
+
+use libwaj::create::FsCreator;
+use libwaj::{ChangeKind, Difference, RedirectStatus};
+use utils::*;
+
+fn add_redirects(creator: &mut FsCreator, redirects: &[(&str, &str)]) {
+    for (path, target) in redirects {
+        creator
+            .add_redirect(path, target, RedirectStatus::Found, false)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_diff() {
+    let old_file = create_waj(
+        "test_diff_old.waj",
+        &[
+            ("same.html", "<html>Same</html>"),
+            ("changed.html", "<html>Old</html>"),
+            ("removed.html", "<html>Removed</html>"),
+            ("type.txt", "Some text"),
+        ],
+        |creator| add_redirects(creator, &[("redirect", "same.html"), ("kind", "same.html")]),
+    );
+    let new_file = create_waj(
+        "test_diff_new.waj",
+        &[
+            ("same.html", "<html>Same</html>"),
+            ("changed.html", "<html>New</html>"),
+            ("added.html", "<html>Added</html>"),
+            ("type.css", "Some text"),
+            ("kind", "Now a content"),
+        ],
+        |creator| {
+            add_redirects(
+                creator,
+                &[("redirect", "changed.html"), ("type.txt", "type.css")],
+            )
+        },
+    );
+    let old = libwaj::Waj::new(&old_file).unwrap();
+    let new = libwaj::Waj::new(&new_file).unwrap();
+
+    assert_eq!(
+        old.diff(&new).unwrap(),
+        vec![
+            Difference::Added("added.html".into()),
+            Difference::Changed("changed.html".into(), vec![ChangeKind::Content]),
+            Difference::Changed("kind".into(), vec![ChangeKind::Kind]),
+            Difference::Changed("redirect".into(), vec![ChangeKind::Redirect]),
+            Difference::Removed("removed.html".into()),
+            Difference::Added("type.css".into()),
+            Difference::Changed("type.txt".into(), vec![ChangeKind::Kind]),
+        ]
+    );
+    assert!(old.diff(&old).unwrap().is_empty());
+}
@@ -0,0 +1,76 @@
+use libwaj::create::RedirectEntry;
+use std::path::Path;
+
+mod utils;
+use utils::*;
+
+#[cfg(unix)]
+#[test]
+fn test_extract_symlink_outside() {
+    let waj_file = temp_path("test_extract_symlink_outside.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    // `a` points outside of the output directory, `a/evil` would be written through it.
+    creator
+        .add_entry(&RedirectEntry {
+            path: "a".into(),
+            target: "../escape".into(),
+            status: Default::default(),
+            external: false,
+        })
+        .unwrap();
+    creator
+        .add_content_bytes("a/evil", "evil", "text/plain")
+        .unwrap();
+    creator
+        .add_entry(&RedirectEntry {
+            path: "b".into(),
+            target: "a/evil".into(),
+            status: Default::default(),
+            external: false,
+        })
+        .unwrap();
+    creator.finalize().unwrap();
+
+    let tmp_dir =
+        tempfile::TempDir::with_prefix_in("extract_", env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let escape_dir = tmp_dir.path().join("escape");
+    std::fs::create_dir(&escape_dir).unwrap();
+    let out_dir = tmp_dir.path().join("out");
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let report = waj
+        .extract(&out_dir, &[], libwaj::RedirectMode::Symlink)
+        .unwrap();
+    assert_eq!(report.skipped, vec!["a".to_owned()]);
+    assert_eq!(report.extracted, 2);
+    assert!(!escape_dir.join("evil").exists());
+    assert!(!out_dir.join("a").is_symlink());
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("a/evil")).unwrap(),
+        "evil"
+    );
+    assert_eq!(
+        std::fs::read_link(out_dir.join("b")).unwrap(),
+        Path::new("a/evil")
+    );
+
+    // A symlink left by a previous extraction is not followed.
+    std::fs::remove_dir_all(out_dir.join("a")).unwrap();
+    std::os::unix::fs::symlink(&escape_dir, out_dir.join("a")).unwrap();
+    let report = waj
+        .extract(&out_dir, &["a/".to_owned()], libwaj::RedirectMode::Symlink)
+        .unwrap();
+    assert_eq!(report.skipped, vec!["a/evil".to_owned()]);
+    assert!(!escape_dir.join("evil").exists());
+
+    // Overlapping paths extract entries once.
+    let out_dir = tmp_dir.path().join("out2");
+    let report = waj
+        .extract(
+            &out_dir,
+            &["a".to_owned(), "a/evil".to_owned()],
+            libwaj::RedirectMode::Symlink,
+        )
+        .unwrap();
+    assert_eq!(report.extracted, 1);
+}
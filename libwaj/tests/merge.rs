@@ -0,0 +1,96 @@
+mod utils;
+
+use libwaj::create::{ConflictPolicy, FsCreator};
+use libwaj::{Entry, FullBuilder, RedirectStatus};
+use std::io::Read;
+use utils::*;
+
+fn add_main_page(creator: &mut FsCreator) {
+    creator
+        .add_redirect("", "index.html", RedirectStatus::Found, false)
+        .unwrap();
+}
+
+fn read(waj: &libwaj::Waj, path: &str) -> String {
+    let mut content = String::new();
+    waj.read_entry(path)
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    content
+}
+
+#[test]
+fn test_merge() {
+    let a_file = create_waj(
+        "test_merge_a.waj",
+        &[("index.html", "<html>A</html>"), ("common.css", "a")],
+        add_main_page,
+    );
+    let b_file = create_waj(
+        "test_merge_b.waj",
+        &[("index.html", "<html>B</html>"), ("common.css", "b")],
+        add_main_page,
+    );
+    let a = libwaj::Waj::new(&a_file).unwrap();
+    let b = libwaj::Waj::new(&b_file).unwrap();
+
+    for (policy, expected) in [
+        (ConflictPolicy::FirstWins, "a"),
+        (ConflictPolicy::LastWins, "b"),
+    ] {
+        let merged_file = temp_path("test_merge.waj");
+        let mut creator = new_creator(&merged_file, "".as_ref());
+        creator
+            .merge(&[(&a, ""), (&b, ""), (&b, "b/")], policy)
+            .unwrap();
+        creator.finalize().unwrap();
+
+        let merged = libwaj::Waj::new(&merged_file).unwrap();
+        assert_eq!(read(&merged, "common.css"), expected);
+        assert_eq!(read(&merged, "b/index.html"), "<html>B</html>");
+        assert!(matches!(
+            merged.get_entry::<FullBuilder>("b/").unwrap(),
+            Entry::Redirect(_)
+        ));
+    }
+
+    let merged_file = temp_path("test_merge_error.waj");
+    let mut creator = new_creator(&merged_file, "".as_ref());
+    assert!(matches!(
+        creator.merge(&[(&a, ""), (&b, "")], ConflictPolicy::Error),
+        Err(libwaj::error::CreatorError::Conflict(_))
+    ));
+}
+
+#[test]
+fn test_merge_after_add() {
+    let a_file = create_waj(
+        "test_merge_after_add_a.waj",
+        &[("index.html", "<html>A</html>"), ("common.css", "a")],
+        add_main_page,
+    );
+    let a = libwaj::Waj::new(&a_file).unwrap();
+    let merged_file = temp_path("test_merge_after_add.waj");
+    let mut creator = new_creator(&merged_file, "".as_ref());
+    creator
+        .add_content_bytes("common.css", "added", "text/css")
+        .unwrap();
+    creator.add_metadata("title", "Merged");
+    creator
+        .merge(&[(&a, "")], ConflictPolicy::LastWins)
+        .unwrap();
+    creator.finalize().unwrap();
+
+    let merged = libwaj::Waj::new(&merged_file).unwrap();
+    assert_eq!(read(&merged, "common.css"), "added");
+    assert_eq!(read(&merged, "index.html"), "<html>A</html>");
+    assert_eq!(
+        merged
+            .entries_with_prefix::<FullBuilder>("common.css")
+            .unwrap()
+            .count(),
+        1
+    );
+    assert_eq!(merged.metadata().unwrap().title(), Some("Merged"));
+}
@@ -0,0 +1,64 @@
+mod utils;
+
+use libwaj::CommonEntry;
+use utils::*;
+
+fn entry_path(entry: &libwaj::FullEntry) -> String {
+    let path = match entry {
+        libwaj::Entry::Content(e) => e.path(),
+        libwaj::Entry::Redirect(e) => e.path(),
+        libwaj::Entry::Alias(e) => e.path(),
+        libwaj::Entry::Whiteout(e) => e.path(),
+    };
+    String::from_utf8_lossy(path).into_owned()
+}
+
+#[test]
+fn test_prefix() {
+    let files: Vec<_> = [
+        "index.html",
+        "docs/a.html",
+        "docs/sub/b.html",
+        "docs/sub/c.html",
+        "docs_old.html",
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, name)| (name, format!("content {i}")))
+    .collect();
+    let waj_file = create_waj("test_prefix.waj", &files, |_| {});
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let paths: Vec<_> = waj
+        .entries_with_prefix::<libwaj::FullBuilder>("docs/")
+        .unwrap()
+        .map(|e| entry_path(&e.unwrap()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec!["docs/a.html", "docs/sub/b.html", "docs/sub/c.html"]
+    );
+
+    assert_eq!(
+        waj.entries_with_prefix::<libwaj::FullBuilder>("nothing")
+            .unwrap()
+            .count(),
+        0
+    );
+
+    let children = |directory: &str| -> Vec<String> {
+        waj.directory_children::<libwaj::FullBuilder>(directory)
+            .unwrap()
+            .map(|child| match child.unwrap() {
+                libwaj::Child::Entry(e) => entry_path(&e),
+                libwaj::Child::Directory(d) => d,
+            })
+            .collect()
+    };
+    assert_eq!(children(""), vec!["docs/", "docs_old.html", "index.html"]);
+    assert_eq!(children("docs"), vec!["docs/a.html", "docs/sub/"]);
+    assert_eq!(
+        children("docs/sub/"),
+        vec!["docs/sub/b.html", "docs/sub/c.html"]
+    );
+}
@@ -0,0 +1,55 @@
+mod utils;
+
+use std::io::{Read, Seek, SeekFrom};
+use utils::*;
+
+#[test]
+fn test_read_entry() {
+    let waj_file = create_waj(
+        "test_read_entry.waj",
+        &[("docs/index.html", "0123456789")],
+        |creator| {
+            creator
+                .add_redirect("old", "docs/", libwaj::RedirectStatus::Found, false)
+                .unwrap();
+        },
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+
+    let mut reader = waj.read_entry("docs/index.html").unwrap();
+    assert_eq!(reader.size(), 10);
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "0123456789");
+
+    reader.seek(SeekFrom::Start(3)).unwrap();
+    let mut part = [0; 4];
+    reader.read_exact(&mut part).unwrap();
+    assert_eq!(&part, b"3456");
+    assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 8);
+    content.clear();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "89");
+
+    assert!(matches!(
+        waj.read_entry("old"),
+        Err(libwaj::error::WajError::NotAContent(_))
+    ));
+    assert!(matches!(
+        waj.read_entry("nothing"),
+        Err(libwaj::error::WajError::PathNotFound(_))
+    ));
+
+    let (path, _) = waj
+        .get_entry_from_url::<libwaj::FullBuilder>("/docs/?query")
+        .unwrap();
+    assert_eq!(path, "docs/index.html");
+    assert_eq!(
+        waj.get_entry_from_url::<libwaj::FullBuilder>("/nothing")
+            .err()
+            .unwrap()
+            .to_string(),
+        "Path nothing not found in archive"
+    );
+}
@@ -0,0 +1,84 @@
+mod utils;
+
+use libwaj::RedirectStatus;
+use utils::*;
+
+#[test]
+fn test_redirect() {
+    let waj_file = create_waj(
+        "test_redirect.waj",
+        &[("new.html", "<html></html>")],
+        |creator| {
+            creator
+                .add_redirect(
+                    "old.html",
+                    "new.html",
+                    RedirectStatus::MovedPermanently,
+                    false,
+                )
+                .unwrap();
+            creator
+                .add_redirect(
+                    "elsewhere",
+                    "https://example.com/",
+                    RedirectStatus::TemporaryRedirect,
+                    true,
+                )
+                .unwrap();
+            // Overlapping redirects are refused.
+            assert!(matches!(
+                creator.add_redirect("old.html", "other.html", RedirectStatus::Found, false),
+                Err(libwaj::error::CreatorError::Conflict(_))
+            ));
+        },
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    match waj.get_entry::<libwaj::FullBuilder>("old.html").unwrap() {
+        libwaj::Entry::Redirect(r) => {
+            assert_eq!(r.target(), b"new.html");
+            assert_eq!(r.status(), RedirectStatus::MovedPermanently);
+            assert!(!r.is_external());
+        }
+        _ => panic!("old.html must be a redirect"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("elsewhere").unwrap() {
+        libwaj::Entry::Redirect(r) => {
+            assert_eq!(r.target(), b"https://example.com/");
+            assert_eq!(r.status().code(), 307);
+            assert!(r.is_external());
+        }
+        _ => panic!("elsewhere must be a redirect"),
+    }
+}
+
+#[test]
+fn test_redirect_target_path() {
+    let waj_file = temp_path("test_redirect_target_path.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    let redirects = [
+        ("a/b/relative.html", "../new.html#top", Some("a/new.html")),
+        ("a/b/absolute.html", "/new.html", Some("new.html")),
+        ("a/b/dir.html", "./sub/", Some("a/b/sub/")),
+        ("a/b/external.html", "https://example.com/", None),
+    ];
+    for (path, target, _) in redirects {
+        creator
+            .add_redirect(
+                path,
+                target,
+                RedirectStatus::Found,
+                target.starts_with("https:"),
+            )
+            .unwrap();
+    }
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    for (path, _, expected) in redirects {
+        match waj.get_entry::<libwaj::FullBuilder>(path).unwrap() {
+            libwaj::Entry::Redirect(r) => assert_eq!(r.target_path().as_deref(), expected),
+            _ => panic!("{path} must be a redirect"),
+        }
+    }
+}
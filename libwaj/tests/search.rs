@@ -0,0 +1,50 @@
+mod utils;
+
+use utils::*;
+
+#[test]
+fn test_full_text_search() {
+    let mut files: Vec<_> = [
+        (
+            "rust.html",
+            "Rust is a systems programming language. Rust is fast.",
+        ),
+        ("python.html", "Python is a programming language."),
+        ("cooking.html", "A recipe of apple pie."),
+    ]
+    .into_iter()
+    .map(|(name, body)| {
+        (
+            name,
+            format!("<html><head><title>{name}</title></head><body><p>{body}</p></body></html>"),
+        )
+    })
+    .collect();
+    files.push(("rust.txt", "Rust is not indexed".into()));
+    let source_dir = source_dir(&files);
+
+    let waj_file = temp_path("test_search.waj");
+    let mut creator = new_creator(&waj_file, source_dir.path());
+    creator.enable_full_text_index();
+    creator.add_from_path(source_dir.path()).unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    assert!(waj.has_full_text_index());
+
+    let results = waj.search("RUST", 10).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].path(), "rust.html");
+    assert!(results[0].snippet().contains("systems programming"));
+
+    let results = waj.search("programming language", 10).unwrap();
+    let paths: Vec<_> = results.iter().map(|r| r.path()).collect();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.contains(&"rust.html"));
+    assert!(paths.contains(&"python.html"));
+
+    let results = waj.search("programming", 1).unwrap();
+    assert_eq!(results.len(), 1);
+
+    assert!(waj.search("unknownword", 10).unwrap().is_empty());
+}
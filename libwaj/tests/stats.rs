@@ -0,0 +1,46 @@
+mod utils;
+
+use utils::*;
+
+#[test]
+fn test_stats() {
+    let waj_file = create_waj(
+        "test_stats.waj",
+        &[
+            ("a.html", "<html>a</html>"),
+            ("b.html", "<html>b</html>"),
+            ("c.html", "<html>b</html>"),
+            ("d.css", "body { color: red; }"),
+        ],
+        |creator| {
+            creator
+                .add_redirect("old.html", "a.html", libwaj::RedirectStatus::Found, false)
+                .unwrap();
+        },
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let stats = waj.stats().unwrap();
+    assert_eq!(stats.uuid, waj.uuid().to_string());
+    // b.html and c.html share the same content, one of them is a alias.
+    assert_eq!(stats.contents, 3);
+    assert_eq!(stats.aliases, 1);
+    assert_eq!(stats.redirects, 1);
+    assert_eq!(stats.entries(), 5);
+    assert_eq!(stats.content_size, 14 + 14 + 20);
+    assert_eq!(stats.missing_packs().count(), 0);
+    assert_eq!(stats.packs.iter().map(|p| p.contents).sum::<u64>(), 3);
+    for pack in &stats.packs {
+        // ConcatMode::OneFile, packs are in the container.
+        assert_eq!(pack.location, "");
+        assert!(pack.compressed_size > 0);
+        assert!(pack.compression.is_some());
+    }
+    assert!(stats.compression_ratio().is_some());
+
+    assert_eq!(stats.mimetypes.len(), 2);
+    assert_eq!(stats.mimetypes[0].mimetype, "text/html");
+    assert_eq!(stats.mimetypes[0].count, 2);
+    assert_eq!(stats.mimetypes[0].size, 28);
+    assert_eq!(stats.top_mimetypes_by_size(1)[0].mimetype, "text/html");
+}
@@ -0,0 +1,59 @@
+mod utils;
+
+use utils::*;
+
+#[test]
+fn test_title_index() {
+    let mut files: Vec<_> = [
+        ("apple.html", "Apple pie"),
+        ("apricot.html", "Apricot"),
+        ("banana.html", "BANANA  split"),
+        ("apple_crumble.html", "Apple crumble"),
+    ]
+    .into_iter()
+    .map(|(name, title)| {
+        (
+            name,
+            format!("<html><head><title>{title}</title></head><body></body></html>"),
+        )
+    })
+    .collect();
+    files.push(("no_title.html", "<html></html>".into()));
+    let waj_file = create_waj("test_title.waj", &files, |_| {});
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    let titles = waj.titles_with_prefix("ap", None).unwrap();
+    let titles: Vec<_> = titles.iter().map(|t| (t.title(), t.path())).collect();
+    assert_eq!(
+        titles,
+        vec![
+            ("Apple crumble", "apple_crumble.html"),
+            ("Apple pie", "apple.html"),
+            ("Apricot", "apricot.html"),
+        ]
+    );
+
+    let titles = waj.titles_with_prefix("Apple", Some(1)).unwrap();
+    assert_eq!(titles.len(), 1);
+    assert_eq!(titles[0].path(), "apple_crumble.html");
+
+    let titles = waj.titles_with_prefix("banana sp", None).unwrap();
+    assert_eq!(titles.len(), 1);
+    assert_eq!(titles[0].title(), "BANANA split");
+
+    assert!(waj.titles_with_prefix("cherry", None).unwrap().is_empty());
+
+    match waj
+        .get_entry_by_title::<libwaj::FullBuilder>("apricot")
+        .unwrap()
+    {
+        libwaj::Entry::Content(e) => {
+            use libwaj::CommonEntry;
+            assert_eq!(e.path(), b"apricot.html")
+        }
+        _ => panic!("apricot.html must be a content"),
+    }
+    assert!(waj
+        .get_entry_by_title::<libwaj::FullBuilder>("apri")
+        .is_err());
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use libwaj::create::FsCreator;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A path `name` in the tmp directory of the target, removed when dropped.
+pub fn temp_path(name: &str) -> tempfile::TempPath {
+    tempfile::TempPath::from_path(Path::new(env!("CARGO_TARGET_TMPDIR")).join(name))
+}
+
+/// A temporary directory containing the `files` (path, content).
+///
+/// The parent directories of the files are created.
+pub fn source_dir(files: &[(&str, impl AsRef<[u8]>)]) -> tempfile::TempDir {
+    let source_dir =
+        tempfile::TempDir::with_prefix_in("source_", env!("CARGO_TARGET_TMPDIR")).unwrap();
+    for (path, content) in files {
+        let path = source_dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    source_dir
+}
+
+/// A creator of `waj_file`, the added files being named relatively to `root`.
+pub fn new_creator(waj_file: &Path, root: &Path) -> FsCreator {
+    FsCreator::new(
+        waj_file.to_str().unwrap(),
+        Box::new(libwaj::create::StripPrefix::new(root.into())),
+        jbk::creator::ConcatMode::OneFile,
+        Arc::new(()),
+        Rc::new(()),
+        Default::default(),
+    )
+    .unwrap()
+}
+
+/// Create the archive `name` with the `files` (path, content).
+///
+/// `add_entries` is called once the files are added, to add other entries.
+pub fn create_waj(
+    name: &str,
+    files: &[(&str, impl AsRef<[u8]>)],
+    add_entries: impl FnOnce(&mut FsCreator),
+) -> tempfile::TempPath {
+    let source_dir = source_dir(files);
+    let waj_file = temp_path(name);
+    let mut creator = new_creator(&waj_file, source_dir.path());
+    creator.add_from_path(source_dir.path()).unwrap();
+    add_entries(&mut creator);
+    creator.finalize().unwrap();
+    waj_file
+}
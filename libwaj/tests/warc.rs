@@ -0,0 +1,154 @@
+use libwaj::RedirectStatus;
+use std::io::{Read, Write};
+
+mod utils;
+use utils::*;
+
+fn warc_record(headers: &[(&str, &str)], block: &[u8]) -> Vec<u8> {
+    let mut record = b"WARC/1.1\r\n".to_vec();
+    for (name, value) in headers {
+        record.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+    }
+    record.extend_from_slice(format!("Content-Length: {}\r\n\r\n", block.len()).as_bytes());
+    record.extend_from_slice(block);
+    record.extend_from_slice(b"\r\n\r\n");
+    record
+}
+
+fn response(id: &str, uri: &str, http: &[u8]) -> Vec<u8> {
+    warc_record(
+        &[
+            ("WARC-Type", "response"),
+            ("WARC-Record-ID", id),
+            ("WARC-Target-URI", uri),
+            ("Content-Type", "application/http; msgtype=response"),
+        ],
+        http,
+    )
+}
+
+fn warc_content() -> Vec<u8> {
+    let mut warc = warc_record(
+        &[
+            ("WARC-Type", "warcinfo"),
+            ("Content-Type", "application/warc-fields"),
+        ],
+        b"software: test\r\n",
+    );
+    warc.extend(warc_record(
+        &[
+            ("WARC-Type", "request"),
+            ("WARC-Target-URI", "https://example.com/"),
+            ("Content-Type", "application/http; msgtype=request"),
+        ],
+        b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n",
+    ));
+    warc.extend(response(
+        "<urn:uuid:1>",
+        "https://example.com/",
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\r\n\
+        <html><head><title>Home</title></head></html>",
+    ));
+    warc.extend(response(
+        "<urn:uuid:2>",
+        "https://example.com/docs/a%20b.txt",
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n\
+        5\r\nHello\r\n7\r\n world!\r\n0\r\n\r\n",
+    ));
+    warc.extend(response(
+        "<urn:uuid:3>",
+        "https://example.com/old",
+        b"HTTP/1.1 301 Moved Permanently\r\nLocation: https://example.com/docs/a%20b.txt\r\n\r\n",
+    ));
+    warc.extend(response(
+        "<urn:uuid:4>",
+        "https://example.com/away",
+        b"HTTP/1.1 302 Found\r\nLocation: https://other.org/page\r\n\r\n",
+    ));
+    warc.extend(response(
+        "<urn:uuid:5>",
+        "https://other.org/page",
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<html></html>",
+    ));
+    warc.extend(response(
+        "<urn:uuid:6>",
+        "https://example.com/missing",
+        b"HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\n\r\n<html></html>",
+    ));
+    warc.extend(warc_record(
+        &[
+            ("WARC-Type", "revisit"),
+            ("WARC-Target-URI", "https://example.com/home"),
+            ("WARC-Refers-To", "<urn:uuid:1>"),
+        ],
+        b"HTTP/1.1 200 OK\r\n\r\n",
+    ));
+    warc
+}
+
+fn check_warc_import(name: &str, warc: &[u8]) {
+    let warc_file = temp_path(&format!("{name}.warc"));
+    std::fs::write(&warc_file, warc).unwrap();
+
+    let waj_file = temp_path(&format!("{name}.waj"));
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    creator.add_from_warc(&warc_file).unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    // The root is stored as `index.html`, as a directory would be.
+    assert!(waj.get_entry::<libwaj::FullBuilder>("").is_err());
+    match waj.get_entry::<libwaj::FullBuilder>("index.html").unwrap() {
+        libwaj::Entry::Content(c) => assert_eq!(c.mimetype(), b"text/html; charset=utf-8"),
+        _ => panic!("The root must be a content"),
+    }
+    let mut content = String::new();
+    waj.read_entry("docs/a b.txt")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "Hello world!");
+    match waj.get_entry::<libwaj::FullBuilder>("old").unwrap() {
+        libwaj::Entry::Redirect(r) => {
+            assert_eq!(r.target(), b"/docs/a b.txt");
+            assert_eq!(r.status(), RedirectStatus::MovedPermanently);
+            assert!(!r.is_external());
+        }
+        _ => panic!("old must be a redirect"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("away").unwrap() {
+        libwaj::Entry::Redirect(r) => {
+            assert_eq!(r.target(), b"https://other.org/page");
+            assert_eq!(r.status(), RedirectStatus::Found);
+            assert!(r.is_external());
+        }
+        _ => panic!("away must be a redirect"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("home").unwrap() {
+        libwaj::Entry::Redirect(r) => assert_eq!(r.target(), b"/index.html"),
+        _ => panic!("home must be a redirect"),
+    }
+    // Other hosts and error responses are not imported.
+    assert!(waj.get_entry::<libwaj::FullBuilder>("page").is_err());
+    assert!(waj.get_entry::<libwaj::FullBuilder>("missing").is_err());
+    assert_eq!(waj.stats().unwrap().entries(), 5);
+}
+
+#[test]
+fn test_warc_import() {
+    check_warc_import("test_warc_import", &warc_content());
+}
+
+#[test]
+fn test_warc_gz_import() {
+    // Warc files are usually made of several gzip members.
+    let warc = warc_content();
+    let (first, second) = warc.split_at(warc.len() / 2);
+    let mut warc_gz = vec![];
+    for part in [first, second] {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(part).unwrap();
+        warc_gz.extend(encoder.finish().unwrap());
+    }
+    check_warc_import("test_warc_gz_import", &warc_gz);
+}
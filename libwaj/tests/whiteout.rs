@@ -0,0 +1,39 @@
+mod utils;
+
+use libwaj::CommonEntry;
+use utils::*;
+
+#[test]
+fn test_whiteout() {
+    let waj_file = create_waj(
+        "test_whiteout.waj",
+        &[("a.html", "<html></html>")],
+        |creator| {
+            creator.add_whiteout("old/page.html").unwrap();
+            // A whiteout can't hide a entry of the same archive.
+            assert!(matches!(
+                creator.add_whiteout("a.html"),
+                Err(libwaj::error::CreatorError::Conflict(_))
+            ));
+        },
+    );
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    match waj
+        .get_entry::<libwaj::FullBuilder>("old/page.html")
+        .unwrap()
+    {
+        libwaj::Entry::Whiteout(w) => assert_eq!(w.path(), b"old/page.html"),
+        _ => panic!("old/page.html must be a whiteout"),
+    }
+    assert!(matches!(
+        waj.read_entry("old/page.html"),
+        Err(libwaj::error::WajError::NotAContent(_))
+    ));
+
+    let stats = waj.stats().unwrap();
+    assert_eq!(stats.contents, 1);
+    assert_eq!(stats.whiteouts, 1);
+    assert_eq!(stats.entries(), 2);
+    assert!(waj.check(10).unwrap().is_ok());
+}
@@ -0,0 +1,179 @@
+use libwaj::RedirectStatus;
+use std::io::Read;
+
+mod utils;
+use utils::*;
+
+enum TestDirent {
+    Content(u16, u32),
+    Redirect(u32),
+}
+
+/// Build a zim file (version 6.1) with one uncompressed cluster.
+fn build_zim() -> Vec<u8> {
+    let mime_types: &[&str] = &["text/html", "text/css", "text/plain"];
+    // Dirents are sorted by namespace and path.
+    let dirents = [
+        (b'C', "docs/page.html", TestDirent::Content(0, 1)),
+        (b'C', "home", TestDirent::Redirect(2)),
+        (b'C', "index.html", TestDirent::Content(0, 0)),
+        (b'C', "style.css", TestDirent::Content(1, 2)),
+        (b'M', "Language", TestDirent::Content(2, 4)),
+        (b'M', "Title", TestDirent::Content(2, 3)),
+        (b'W', "mainPage", TestDirent::Redirect(2)),
+    ];
+    let blobs: &[&[u8]] = &[
+        b"<html><head><title>Index</title></head></html>",
+        b"<html>Page</html>",
+        b"body { color: black }",
+        b"My zim",
+        b"eng",
+    ];
+
+    let mut mime_list = vec![];
+    for mime_type in mime_types {
+        mime_list.extend_from_slice(mime_type.as_bytes());
+        mime_list.push(0);
+    }
+    mime_list.push(0);
+
+    let dirents_pos = 80 + mime_list.len() as u64;
+    let mut dirent_ptrs = vec![];
+    let mut dirents_data = vec![];
+    for (namespace, path, kind) in &dirents {
+        dirent_ptrs.push(dirents_pos + dirents_data.len() as u64);
+        match kind {
+            TestDirent::Content(mime, blob) => {
+                dirents_data.extend_from_slice(&mime.to_le_bytes());
+                dirents_data.extend_from_slice(&[0, *namespace]);
+                dirents_data.extend_from_slice(&0u32.to_le_bytes());
+                dirents_data.extend_from_slice(&0u32.to_le_bytes());
+                dirents_data.extend_from_slice(&blob.to_le_bytes());
+            }
+            TestDirent::Redirect(target) => {
+                dirents_data.extend_from_slice(&0xffffu16.to_le_bytes());
+                dirents_data.extend_from_slice(&[0, *namespace]);
+                dirents_data.extend_from_slice(&0u32.to_le_bytes());
+                dirents_data.extend_from_slice(&target.to_le_bytes());
+            }
+        }
+        dirents_data.extend_from_slice(path.as_bytes());
+        // Empty title
+        dirents_data.extend_from_slice(&[0, 0]);
+    }
+
+    let mut cluster = vec![1u8];
+    let mut offset = (blobs.len() as u32 + 1) * 4;
+    cluster.extend_from_slice(&offset.to_le_bytes());
+    for blob in blobs {
+        offset += blob.len() as u32;
+        cluster.extend_from_slice(&offset.to_le_bytes());
+    }
+    for blob in blobs {
+        cluster.extend_from_slice(blob);
+    }
+
+    let path_ptr_pos = dirents_pos + dirents_data.len() as u64;
+    let title_ptr_pos = path_ptr_pos + 8 * dirents.len() as u64;
+    let cluster_ptr_pos = title_ptr_pos;
+    let cluster_pos = cluster_ptr_pos + 8;
+    let checksum_pos = cluster_pos + cluster.len() as u64;
+
+    let mut zim = vec![];
+    zim.extend_from_slice(&72173914u32.to_le_bytes());
+    zim.extend_from_slice(&6u16.to_le_bytes());
+    zim.extend_from_slice(&1u16.to_le_bytes());
+    zim.extend_from_slice(&[0; 16]);
+    zim.extend_from_slice(&(dirents.len() as u32).to_le_bytes());
+    zim.extend_from_slice(&1u32.to_le_bytes());
+    zim.extend_from_slice(&path_ptr_pos.to_le_bytes());
+    zim.extend_from_slice(&title_ptr_pos.to_le_bytes());
+    zim.extend_from_slice(&cluster_ptr_pos.to_le_bytes());
+    zim.extend_from_slice(&80u64.to_le_bytes());
+    // Main page
+    zim.extend_from_slice(&6u32.to_le_bytes());
+    zim.extend_from_slice(&0xffffffffu32.to_le_bytes());
+    zim.extend_from_slice(&checksum_pos.to_le_bytes());
+    zim.extend(mime_list);
+    zim.extend(dirents_data);
+    for ptr in dirent_ptrs {
+        zim.extend_from_slice(&ptr.to_le_bytes());
+    }
+    zim.extend_from_slice(&cluster_pos.to_le_bytes());
+    zim.extend(cluster);
+    zim.extend_from_slice(&[0; 16]);
+    zim
+}
+
+#[test]
+fn test_zim() {
+    let zim_file = temp_path("test_zim.zim");
+    std::fs::write(&zim_file, build_zim()).unwrap();
+    let waj_file = temp_path("test_zim.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    creator.add_from_zim(&zim_file).unwrap();
+    creator.finalize().unwrap();
+
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    match waj.get_entry::<libwaj::FullBuilder>("style.css").unwrap() {
+        libwaj::Entry::Content(c) => assert_eq!(c.mimetype(), b"text/css"),
+        _ => panic!("style.css must be a content"),
+    }
+    let mut content = String::new();
+    waj.read_entry("docs/page.html")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "<html>Page</html>");
+    match waj.get_entry::<libwaj::FullBuilder>("home").unwrap() {
+        libwaj::Entry::Redirect(r) => assert_eq!(r.target(), b"/index.html"),
+        _ => panic!("home must be a redirect"),
+    }
+    match waj.get_entry::<libwaj::FullBuilder>("").unwrap() {
+        libwaj::Entry::Redirect(r) => {
+            assert_eq!(r.target(), b"/index.html");
+            assert_eq!(r.status(), RedirectStatus::Found);
+        }
+        _ => panic!("The root must be a redirect"),
+    }
+    let metadata = waj.metadata().unwrap();
+    assert_eq!(metadata.get("title"), Some("My zim"));
+    assert_eq!(metadata.get("language"), Some("eng"));
+    // Metadata and well known entries are not added as entries.
+    assert_eq!(waj.stats().unwrap().entries(), 5);
+}
+
+#[test]
+fn test_zim_existing_main_page() {
+    let zim_file = temp_path("test_zim_existing_main_page.zim");
+    std::fs::write(&zim_file, build_zim()).unwrap();
+    let waj_file = temp_path("test_zim_existing_main_page.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    creator
+        .add_redirect("", "/docs/page.html", RedirectStatus::Found, false)
+        .unwrap();
+    creator.add_from_zim(&zim_file).unwrap();
+    creator.finalize().unwrap();
+
+    // The main page of the zim file doesn't replace (nor duplicate) the existing one.
+    let waj = libwaj::Waj::new(&waj_file).unwrap();
+    match waj.get_entry::<libwaj::FullBuilder>("").unwrap() {
+        libwaj::Entry::Redirect(r) => assert_eq!(r.target(), b"/docs/page.html"),
+        _ => panic!("The root must be a redirect"),
+    }
+    assert_eq!(waj.stats().unwrap().entries(), 5);
+}
+
+#[test]
+fn test_zim_truncated() {
+    let zim_file = temp_path("test_zim_truncated.zim");
+    let zim = build_zim();
+    // Cut the checksum, the end of the last cluster is after the end of the file.
+    std::fs::write(&zim_file, &zim[..zim.len() - 20]).unwrap();
+    let waj_file = temp_path("test_zim_truncated.waj");
+    let mut creator = new_creator(&waj_file, "".as_ref());
+    assert!(matches!(
+        creator.add_from_zim(&zim_file),
+        Err(libwaj::error::CreatorError::InvalidZim(_))
+    ));
+}
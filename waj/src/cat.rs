@@ -0,0 +1,123 @@
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueHint};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Maximum number of redirects followed before giving up.
+const MAX_REDIRECTS: usize = 10;
+
+/// A range of bytes, `start-end` (end included) or `start-`.
+#[derive(Clone, Copy)]
+struct ByteRange {
+    start: u64,
+    end: Option<u64>,
+}
+
+fn parse_range(input: &str) -> Result<ByteRange, String> {
+    let (start, end) = input
+        .split_once('-')
+        .ok_or_else(|| format!("`{input}` must be `start-end` or `start-`"))?;
+    let start = start
+        .parse()
+        .map_err(|e| format!("Invalid start `{start}`: {e}"))?;
+    let end = match end {
+        "" => None,
+        end => Some(
+            end.parse()
+                .map_err(|e| format!("Invalid end `{end}`: {e}"))?,
+        ),
+    };
+    if end.is_some_and(|end| end < start) {
+        return Err(format!("End of `{input}` is before its start"));
+    }
+    Ok(ByteRange { start, end })
+}
+
+/// Write the content of a entry on stdout.
+///
+/// The path is resolved as `waj serve` does: without the query string,
+/// and with `index.html` appended for directories.
+#[derive(Parser)]
+pub struct Options {
+    /// Archive to read
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    infile: PathBuf,
+
+    /// Path of the entry
+    #[arg(value_parser)]
+    path: String,
+
+    /// Follow redirects (in the archive).
+    #[arg(short = 'L', long, default_value_t = false, action)]
+    follow_redirects: bool,
+
+    /// Write only the bytes from `start` to `end` (included).
+    ///
+    /// `start-` writes from `start` to the end of the content.
+    #[arg(long, value_parser=parse_range)]
+    range: Option<ByteRange>,
+
+    /// Print the mimetype of the entry on stderr.
+    #[arg(long, default_value_t = false, action)]
+    show_mimetype: bool,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+pub fn cat(options: Options) -> Result<()> {
+    let waj =
+        waj::Waj::new(&options.infile).with_context(|| format!("Opening {:?}", options.infile))?;
+    let (mut path, mut entry) = waj.get_entry_from_url::<waj::FullBuilder>(&options.path)?;
+    let mut nb_redirects = 0;
+    let content = loop {
+        match entry {
+            waj::Entry::Content(content) => break content,
+            waj::Entry::Alias(alias) => match waj.alias_content(&alias)? {
+                Some((_, content)) => break content,
+                None => bail!(
+                    "{path} is a alias to {} which doesn't lead to a content",
+                    String::from_utf8_lossy(alias.target())
+                ),
+            },
+            waj::Entry::Whiteout(_) => bail!("{path} is a whiteout"),
+            waj::Entry::Redirect(redirect) => {
+                let target = String::from_utf8_lossy(redirect.target());
+                if !options.follow_redirects {
+                    bail!("{path} is a redirect to {target} (use --follow-redirects)");
+                }
+                let target_path = match redirect.target_path() {
+                    None => bail!("{path} is a redirect to external url {target}"),
+                    Some(target_path) => target_path,
+                };
+                nb_redirects += 1;
+                if nb_redirects > MAX_REDIRECTS {
+                    bail!("Too many redirects (last target is {target})");
+                }
+                (path, entry) = waj
+                    .get_entry_from_url::<waj::FullBuilder>(&target_path)
+                    .with_context(|| format!("Following redirect {path} to {target}"))?;
+            }
+        }
+    };
+
+    if options.show_mimetype {
+        eprintln!("{}", String::from_utf8_lossy(content.mimetype()));
+    }
+
+    let mut reader = waj.read_content(&content)?;
+    let size = reader.size();
+    let (start, end) = match options.range {
+        None => (0, size),
+        Some(range) => (
+            range.start.min(size),
+            range.end.map_or(size, |end| (end + 1).min(size)),
+        ),
+    };
+    reader.seek(SeekFrom::Start(start))?;
+    std::io::copy(
+        &mut reader.take(end.saturating_sub(start)),
+        &mut std::io::stdout().lock(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+
+/// Check the integrity and the consistency of the archive.
+///
+/// Exit with a non zero status if a problem is found.
+#[derive(Parser)]
+pub struct Options {
+    /// Archive to check
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    infile: PathBuf,
+
+    /// Maximum number of redirects to follow from a redirect to reach a content.
+    #[arg(long, value_parser, default_value_t = 10)]
+    max_redirects: usize,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+pub fn check(options: Options) -> Result<()> {
+    let waj =
+        waj::Waj::new(&options.infile).with_context(|| format!("Opening {:?}", options.infile))?;
+    let report = waj.check(options.max_redirects)?;
+    for issue in &report.issues {
+        println!("{issue}");
+    }
+    if !report.is_ok() {
+        bail!(
+            "{} problems found in {} entries",
+            report.issues.len(),
+            report.entries
+        );
+    }
+    println!("{} entries checked, no problem found", report.entries);
+    Ok(())
+}
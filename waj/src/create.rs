@@ -6,7 +6,7 @@ use std::io::{BufRead, BufReader};
 use std::path::{absolute, Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
-use waj::create::StripPrefix;
+use waj::create::{is_valid_mimetype, StripPrefix};
 
 #[derive(Parser)]
 pub struct Options {
@@ -49,6 +49,60 @@ pub struct Options {
     #[arg(short = 'L', long = "file-list", group = "input", verbatim_doc_comment, value_hint=ValueHint::FilePath)]
     file_list: Option<PathBuf>,
 
+    /// Do not add the files and directories matching the pattern (gitignore syntax).
+    ///
+    /// Can be specified several times.
+    /// Patterns are matched against the paths in the archive (after `STRIP_PREFIX` removal),
+    /// for files as for the entries of `--from-tar`, `--from-zip`, `--from-zim` and `--from-warc`.
+    /// `.wajignore` files found in the added directories are also honoured, as `.gitignore` files.
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Only add the files matching the pattern (gitignore syntax).
+    ///
+    /// Can be specified several times.
+    /// Patterns are matched against the paths in the archive (after `STRIP_PREFIX` removal).
+    #[arg(long, value_name = "PATTERN")]
+    include: Vec<String>,
+
+    /// Add hidden files and directories (whose name starts with a `.`).
+    #[arg(long, default_value_t = false, action)]
+    hidden: bool,
+
+    /// Add the members of the tar file (may be gzip compressed). Use `-` to read the tar from stdin.
+    ///
+    /// Can be specified several times.
+    /// Members are named as files (`STRIP_PREFIX` is removed), symlinks are stored as redirects.
+    /// Relative path are relative to the current working dir.
+    #[arg(long, value_name = "TAR_FILE", value_hint=ValueHint::FilePath)]
+    from_tar: Vec<PathBuf>,
+
+    /// Add the members of the zip file.
+    ///
+    /// Can be specified several times.
+    /// Members are named as files (`STRIP_PREFIX` is removed), symlinks are stored as redirects.
+    /// Relative path are relative to the current working dir.
+    #[arg(long, value_name = "ZIP_FILE", value_hint=ValueHint::FilePath)]
+    from_zip: Vec<PathBuf>,
+
+    /// Add the entries of the ZIM file.
+    ///
+    /// Can be specified several times.
+    /// Redirects of the ZIM file are stored as redirects, its metadata as archive metadata
+    /// (`--meta` values take precedence) and its main page as a redirect from the root.
+    /// Relative path are relative to the current working dir.
+    #[arg(long, value_name = "ZIM_FILE", value_hint=ValueHint::FilePath)]
+    from_zim: Vec<PathBuf>,
+
+    /// Add the http responses stored in the WARC file (may be gzip compressed).
+    ///
+    /// Can be specified several times.
+    /// Each response is stored under the path of its url (`index.html` for directory urls).
+    /// Revisit records and 3xx responses are stored as redirects.
+    /// Relative path are relative to the current working dir.
+    #[arg(long, value_name = "WARC_FILE", value_hint=ValueHint::FilePath)]
+    from_warc: Vec<PathBuf>,
+
     #[command(flatten)]
     concat_mode: Option<jbk::cmd_utils::ConcatMode>,
 
@@ -63,13 +117,144 @@ pub struct Options {
     #[arg(short, long, required = false, default_value_t = false, action)]
     force: bool,
 
-    #[arg(short, long, required = false)]
+    #[arg(short, long, required = false, conflicts_with = "from_zim")]
     main: Option<String>,
 
+    /// Build a full text index of the html content.
+    ///
+    /// The index allows searching the archive (`/_waj/search?q=` endpoint of `waj serve`).
+    #[arg(long, default_value_t = false, action)]
+    fts: bool,
+
+    /// Store precompressed variants of the text contents (html, css, js, svg, json).
+    ///
+    /// Comma separated list of encodings (`gzip`, `br`).
+    /// `waj serve` sends the best variant accepted by the client (`Accept-Encoding`).
+    #[arg(long, value_name = "ENCODINGS", value_delimiter = ',')]
+    precompress: Vec<waj::Encoding>,
+
+    /// Add a metadata to the archive.
+    ///
+    /// Can be specified several times.
+    /// Well known keys are `title`, `description`, `language`, `creator`, `publisher`,
+    /// `date`, `license`, `source`, `illustration` and `favicon`.
+    /// `illustration` and `favicon` are paths of entries in the archive.
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_metadata)]
+    metadata: Vec<(String, String)>,
+
+    /// Read metadata from META_FILE.
+    ///
+    /// Each (non empty) line must be in the form `KEY=VALUE`. Lines starting with `#` are ignored.
+    /// Metadata given with `--meta` take precedence over the ones in META_FILE.
+    #[arg(long, value_hint=ValueHint::FilePath)]
+    meta_file: Option<PathBuf>,
+
+    /// Use MIMETYPE for the files with the extension EXT.
+    ///
+    /// Can be specified several times.
+    /// MIMETYPE is stored as is and may have parameters (`--mime "htm=text/html; charset=iso-8859-1"`).
+    #[arg(long = "mime", value_name = "EXT=MIMETYPE", value_parser = parse_mime)]
+    mime_types: Vec<(String, String)>,
+
+    /// Read the mimetypes of extensions from MIME_FILE.
+    ///
+    /// Each (non empty) line must be in the form `EXT=MIMETYPE`. Lines starting with `#` are ignored.
+    /// Mimetypes given with `--mime` take precedence over the ones in MIME_FILE.
+    #[arg(long, value_hint=ValueHint::FilePath)]
+    mime_file: Option<PathBuf>,
+
+    /// Use MIMETYPE for the files matching PATTERN (gitignore syntax).
+    ///
+    /// Can be specified several times, the last matching rule wins.
+    /// Patterns are matched against the paths in the archive and take precedence over extensions.
+    #[arg(long, value_name = "PATTERN=MIMETYPE", value_parser = parse_mime)]
+    mime_rule: Vec<(String, String)>,
+
+    /// Check the references (`href`, `src`, `srcset`, css `url()`) of the html and css contents.
+    ///
+    /// References to paths without entry in the archive are reported on stderr.
+    #[arg(long, default_value_t = false, action)]
+    check_links: bool,
+
+    /// Check the references as `--check-links` and fail if one of them is broken.
+    #[arg(long, default_value_t = false, action)]
+    fail_on_broken_links: bool,
+
+    /// Add a whiteout entry at PATH.
+    ///
+    /// Can be specified several times.
+    /// When served in a overlay (`waj serve --overlay`), a whiteout hides the entry PATH
+    /// of the archives below.
+    #[arg(long, value_name = "PATH")]
+    whiteout: Vec<String>,
+
+    /// Add a redirect from PATH to TARGET.
+    ///
+    /// Can be specified several times.
+    /// TARGET is a path in the archive, or a external url if it starts with `http://` or `https://`.
+    #[arg(long, value_name = "PATH=TARGET", value_parser = parse_redirect)]
+    redirect: Vec<(String, String)>,
+
+    /// Http status code (301, 302, 303, 307 or 308) of the redirects added with `--redirect` and `--main`.
+    #[arg(long, value_name = "CODE", default_value = "302")]
+    redirect_status: waj::RedirectStatus,
+
     #[arg(from_global)]
     verbose: u8,
 }
 
+fn parse_metadata(input: &str) -> Result<(String, String)> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(anyhow!(
+            "Metadata must be in the form KEY=VALUE (got `{input}`)"
+        )),
+    }
+}
+
+fn parse_redirect(input: &str) -> Result<(String, String)> {
+    match input.split_once('=') {
+        Some((path, target)) if !path.trim().is_empty() && !target.trim().is_empty() => Ok((
+            path.trim().trim_start_matches('/').to_owned(),
+            target.trim().to_owned(),
+        )),
+        _ => Err(anyhow!(
+            "Redirect must be in the form PATH=TARGET (got `{input}`)"
+        )),
+    }
+}
+
+fn parse_mime(input: &str) -> Result<(String, String)> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() && is_valid_mimetype(value.trim()) => {
+            Ok((key.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(anyhow!(
+            "Mimetype must be in the form KEY=TYPE/SUBTYPE (got `{input}`)"
+        )),
+    }
+}
+
+/// Read the `KEY=VALUE` lines of `file`, parsed with `parse`.
+fn read_key_value_file(
+    file: &Path,
+    parse: fn(&str) -> Result<(String, String)>,
+) -> Result<Vec<(String, String)>> {
+    let reader = File::open(file).with_context(|| format!("Cannot open {}", file.display()))?;
+    BufReader::new(reader)
+        .lines()
+        .filter(|l| match l {
+            Ok(l) => !l.trim().is_empty() && !l.trim_start().starts_with('#'),
+            Err(_) => true,
+        })
+        .map(|l| -> Result<(String, String)> {
+            parse(&l?).with_context(|| format!("Invalid line in {}", file.display()))
+        })
+        .collect()
+}
+
 fn check_input_paths_exist(file_list: &[PathBuf]) -> Result<()> {
     // Check that input files actually exists
     for file in file_list.iter() {
@@ -83,7 +268,7 @@ fn check_input_paths_exist(file_list: &[PathBuf]) -> Result<()> {
     Ok(())
 }
 
-fn check_output_path_writable(out_file: &Path, force: bool) -> Result<()> {
+pub(crate) fn check_output_path_writable(out_file: &Path, force: bool) -> Result<()> {
     let out_file = absolute(out_file)?;
     if !out_file.parent().unwrap().is_dir() {
         Err(anyhow!(
@@ -100,13 +285,13 @@ fn check_output_path_writable(out_file: &Path, force: bool) -> Result<()> {
     }
 }
 
-struct ProgressBar {
+pub(crate) struct ProgressBar {
     comp_clusters: indicatif::ProgressBar,
     uncomp_clusters: indicatif::ProgressBar,
 }
 
 impl ProgressBar {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         let style = indicatif::ProgressStyle::with_template(
             "{prefix} : {wide_bar:.cyan/blue} {pos:4} / {len:4}",
         )
@@ -147,7 +332,7 @@ impl jbk::creator::Progress for ProgressBar {
     }
 }
 
-struct CachedSize(Cell<u64>);
+pub(crate) struct CachedSize(Cell<u64>);
 
 impl jbk::creator::CacheProgress for CachedSize {
     fn cached_data(&self, size: jbk::Size) {
@@ -156,7 +341,7 @@ impl jbk::creator::CacheProgress for CachedSize {
 }
 
 impl CachedSize {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self(Cell::new(0))
     }
 }
@@ -188,6 +373,69 @@ pub fn create(options: Options) -> Result<()> {
         .map(std::path::absolute)
         .transpose()?;
 
+    let is_stdin = |path: &PathBuf| path.as_os_str() == "-";
+    if options.from_tar.iter().filter(|p| is_stdin(p)).count() > 1 {
+        return Err(anyhow!("stdin (`-`) can be read only once"));
+    }
+    let tar_files = options
+        .from_tar
+        .iter()
+        .map(|p| {
+            if is_stdin(p) {
+                Ok(p.clone())
+            } else {
+                absolute(p)
+            }
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    check_input_paths_exist(
+        &tar_files
+            .iter()
+            .filter(|p| !is_stdin(p))
+            .cloned()
+            .collect::<Vec<_>>(),
+    )?;
+
+    let zip_files = options
+        .from_zip
+        .iter()
+        .map(absolute)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    check_input_paths_exist(&zip_files)?;
+
+    let zim_files = options
+        .from_zim
+        .iter()
+        .map(absolute)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    check_input_paths_exist(&zim_files)?;
+
+    let warc_files = options
+        .from_warc
+        .iter()
+        .map(std::path::absolute)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    check_input_paths_exist(&warc_files)?;
+
+    let mut metadata = match &options.meta_file {
+        Some(meta_file) => read_key_value_file(meta_file, parse_metadata)?,
+        None => vec![],
+    };
+    metadata.extend(options.metadata.iter().cloned());
+
+    let mut mime_types = match &options.mime_file {
+        Some(mime_file) => read_key_value_file(mime_file, parse_mime)?,
+        None => vec![],
+    };
+    mime_types.extend(options.mime_types.iter().cloned());
+    let mut mime_rules = waj::create::MimeRules::new();
+    for (extension, mime_type) in &mime_types {
+        mime_rules.add_extension(extension, mime_type)?;
+    }
+    for (pattern, mime_type) in &options.mime_rule {
+        mime_rules.add_pattern(pattern, mime_type)?;
+    }
+
     if let Some(base_dir) = &options.base_dir {
         std::env::set_current_dir(base_dir)?;
     };
@@ -208,6 +456,23 @@ pub fn create(options: Options) -> Result<()> {
         options.compression,
     )?;
 
+    let mut path_filter = waj::create::PathFilter::new(&options.exclude, &options.include)?;
+    path_filter.set_hidden(options.hidden);
+    creator.set_path_filter(path_filter);
+    creator.set_mime_rules(mime_rules);
+
+    if options.fts {
+        creator.enable_full_text_index();
+    }
+
+    if !options.precompress.is_empty() {
+        creator.set_precompress(options.precompress.clone());
+    }
+
+    if options.check_links || options.fail_on_broken_links {
+        creator.enable_link_check(options.fail_on_broken_links);
+    }
+
     let files_to_add = if let Some(file_list) = file_list {
         let file = File::open(&file_list)
             .with_context(|| format!("Cannot open {}", file_list.display()))?;
@@ -235,8 +500,46 @@ pub fn create(options: Options) -> Result<()> {
         creator.add_from_path(&infile)?;
     }
 
+    // Added before the archives, the main page of a zim file is not added then.
     if let Some(main_page) = options.main {
-        creator.add_redirect("", &main_page)?;
+        creator.add_redirect("", &main_page, options.redirect_status, false)?;
+    }
+
+    for tar_file in &tar_files {
+        if is_stdin(tar_file) {
+            creator.add_from_tar_reader(std::io::stdin().lock())?;
+        } else {
+            creator.add_from_tar(tar_file)?;
+        }
+    }
+
+    for zip_file in &zip_files {
+        creator.add_from_zip(zip_file)?;
+    }
+
+    for zim_file in &zim_files {
+        creator.add_from_zim(zim_file)?;
+    }
+
+    for warc_file in &warc_files {
+        creator.add_from_warc(warc_file)?;
+    }
+
+    for (path, target) in &options.redirect {
+        let external = target.starts_with("http://") || target.starts_with("https://");
+        creator.add_redirect(path, target, options.redirect_status, external)?;
+    }
+
+    for path in &options.whiteout {
+        creator.add_whiteout(path)?;
+    }
+
+    for (key, value) in metadata {
+        creator.add_metadata(&key, &value);
+    }
+
+    for broken_link in creator.broken_links() {
+        eprintln!("{broken_link}");
     }
 
     let ret = creator.finalize();
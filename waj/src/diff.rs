@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+
+/// Show the differences between two archives.
+#[derive(Parser)]
+pub struct Options {
+    /// The old archive
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    old: PathBuf,
+
+    /// The new archive
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    new: PathBuf,
+
+    /// Print the differences as json.
+    #[arg(long, default_value_t = false, action)]
+    json: bool,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+fn to_json(differences: &[waj::Difference]) -> serde_json::Value {
+    let paths = |f: fn(&waj::Difference) -> bool| {
+        differences
+            .iter()
+            .filter(|d| f(d))
+            .map(|d| d.path())
+            .collect::<Vec<_>>()
+    };
+    serde_json::json!({
+        "added": paths(|d| matches!(d, waj::Difference::Added(_))),
+        "removed": paths(|d| matches!(d, waj::Difference::Removed(_))),
+        "changed": differences.iter().filter_map(|d| match d {
+            waj::Difference::Changed(path, changes) => Some(serde_json::json!({
+                "path": path,
+                "changes": changes.iter().map(|c| c.name()).collect::<Vec<_>>(),
+            })),
+            _ => None,
+        }).collect::<Vec<_>>(),
+    })
+}
+
+pub fn diff(options: Options) -> Result<()> {
+    let old = waj::Waj::new(&options.old).with_context(|| format!("Opening {:?}", options.old))?;
+    let new = waj::Waj::new(&options.new).with_context(|| format!("Opening {:?}", options.new))?;
+    let differences = old.diff(&new)?;
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&differences))?);
+        return Ok(());
+    }
+    for difference in &differences {
+        match difference {
+            waj::Difference::Added(path) => println!("+ {path}"),
+            waj::Difference::Removed(path) => println!("- {path}"),
+            waj::Difference::Changed(path, changes) => {
+                let changes: Vec<_> = changes.iter().map(|c| c.name()).collect();
+                println!("M {path} ({})", changes.join(", "))
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum RedirectKind {
+    Symlink,
+    Html,
+}
+
+impl From<RedirectKind> for waj::RedirectMode {
+    fn from(kind: RedirectKind) -> Self {
+        match kind {
+            RedirectKind::Symlink => Self::Symlink,
+            RedirectKind::Html => Self::Html,
+        }
+    }
+}
+
+/// Extract the content of the archive in a directory.
+#[derive(Parser)]
+pub struct Options {
+    /// Archive to extract
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    infile: PathBuf,
+
+    /// Directory in which extract the archive.
+    #[arg(short = 'C', long, value_parser, value_hint=ValueHint::DirPath, default_value = ".")]
+    outdir: PathBuf,
+
+    /// Extract only the entries whose path starts with one of PATHS.
+    #[arg(value_parser)]
+    paths: Vec<String>,
+
+    /// How to write redirects.
+    ///
+    /// External redirects are always written as html pages.
+    #[arg(long, value_parser, default_value = "symlink")]
+    redirect: RedirectKind,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+pub fn extract(options: Options) -> Result<()> {
+    let waj =
+        waj::Waj::new(&options.infile).with_context(|| format!("Opening {:?}", options.infile))?;
+    let report = waj
+        .extract(&options.outdir, &options.paths, options.redirect.into())
+        .with_context(|| format!("Extracting in {:?}", options.outdir))?;
+    for path in &report.missing {
+        eprintln!("Missing content: {path}");
+    }
+    for path in &report.skipped {
+        eprintln!("Skipped entry: {path:?}");
+    }
+    if !report.missing.is_empty() {
+        bail!(
+            "{} entries have their content missing",
+            report.missing.len()
+        );
+    }
+    Ok(())
+}
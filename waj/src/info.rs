@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+
+/// Number of mimetypes shown in the text output.
+const TOP_MIMETYPES: usize = 10;
+
+/// Print information and statistics about the archive.
+#[derive(Parser)]
+pub struct Options {
+    /// Archive to inspect
+    #[arg(value_parser, value_hint=ValueHint::FilePath)]
+    infile: PathBuf,
+
+    /// Print the information as json.
+    #[arg(long, default_value_t = false, action)]
+    json: bool,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+fn to_json(stats: &waj::Stats, file_size: u64) -> serde_json::Value {
+    serde_json::json!({
+        "uuid": stats.uuid,
+        "file_size": file_size,
+        "entries": {
+            "total": stats.entries(),
+            "contents": stats.contents,
+            "redirects": stats.redirects,
+            "aliases": stats.aliases,
+            "whiteouts": stats.whiteouts,
+        },
+        "content_size": stats.content_size,
+        "compressed_size": stats.compressed_size(),
+        "compression_ratio": stats.compression_ratio(),
+        "packs": stats.packs.iter().map(|p| serde_json::json!({
+            "id": p.pack_id,
+            "contents": p.contents,
+            "size": p.size,
+            "compressed_size": p.compressed_size,
+            "compression": p.compression,
+            "missing": p.is_missing(),
+            "location": p.location,
+        })).collect::<Vec<_>>(),
+        "mimetypes": stats.mimetypes.iter().map(|m| serde_json::json!({
+            "mimetype": m.mimetype,
+            "count": m.count,
+            "size": m.size,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn print_text(stats: &waj::Stats, file_size: u64) {
+    println!("UUID: {}", stats.uuid);
+    // Whiteouts are only found in overlay layers, don't mention them otherwise.
+    let whiteouts = if stats.whiteouts != 0 {
+        format!(", {} whiteouts", stats.whiteouts)
+    } else {
+        String::new()
+    };
+    println!(
+        "Entries: {} ({} contents, {} redirects, {} aliases{})",
+        stats.entries(),
+        stats.contents,
+        stats.redirects,
+        stats.aliases,
+        whiteouts
+    );
+    println!("Content size: {} bytes", stats.content_size);
+    println!("Compressed size: {} bytes", stats.compressed_size());
+    println!("File size: {} bytes", file_size);
+    if let Some(ratio) = stats.compression_ratio() {
+        println!("Compression ratio: {ratio:.2}");
+    }
+    println!("Packs:");
+    for pack in &stats.packs {
+        let location = if pack.location.is_empty() {
+            "in container".to_owned()
+        } else {
+            format!("at {}", pack.location)
+        };
+        if pack.is_missing() {
+            println!(
+                "  {}: {} contents, MISSING (expected {location})",
+                pack.pack_id, pack.contents
+            );
+        } else {
+            println!(
+                "  {}: {} contents, {} bytes, {} bytes compressed with {}, {location}",
+                pack.pack_id,
+                pack.contents,
+                pack.size,
+                pack.compressed_size,
+                pack.compression.as_deref().unwrap_or("unknown compression")
+            );
+        }
+    }
+    println!("Top mimetypes by count:");
+    for mimetype in stats.mimetypes.iter().take(TOP_MIMETYPES) {
+        println!("  {}: {}", mimetype.mimetype, mimetype.count);
+    }
+    println!("Top mimetypes by size:");
+    for mimetype in stats.top_mimetypes_by_size(TOP_MIMETYPES) {
+        println!("  {}: {} bytes", mimetype.mimetype, mimetype.size);
+    }
+}
+
+pub fn info(options: Options) -> Result<()> {
+    let waj =
+        waj::Waj::new(&options.infile).with_context(|| format!("Opening {:?}", options.infile))?;
+    let stats = waj.stats()?;
+    let file_size = std::fs::metadata(&options.infile)?.len();
+    if options.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&to_json(&stats, file_size))?
+        );
+    } else {
+        print_text(&stats, file_size);
+    }
+    for pack in stats.missing_packs() {
+        log::warn!("Pack {} is missing", pack.pack_id);
+    }
+    Ok(())
+}
@@ -9,6 +9,20 @@ pub struct Options {
     #[arg(value_parser, value_hint=ValueHint::FilePath)]
     infile: PathBuf,
 
+    /// List only the entries whose path starts with PREFIX.
+    #[arg(value_parser)]
+    prefix: Option<String>,
+
+    /// List only the direct children of the directory PREFIX.
+    ///
+    /// Sub directories are listed with a trailing `/`.
+    #[arg(short, long, default_value_t = false, action)]
+    directory: bool,
+
+    /// List the archive metadata instead of the entries.
+    #[arg(long, default_value_t = false, action)]
+    metadata: bool,
+
     #[arg(from_global)]
     verbose: u8,
 }
@@ -33,11 +47,45 @@ impl waj::walk::Operator<(), waj::FullBuilder> for Lister {
         println!("{}", path);
         Ok(())
     }
+    fn on_alias(&self, _context: &mut (), entry: &waj::Alias) -> jbk::Result<()> {
+        let path = String::from_utf8_lossy(entry.path());
+        println!("{}", path);
+        Ok(())
+    }
+    fn on_whiteout(&self, _context: &mut (), entry: &waj::Whiteout) -> jbk::Result<()> {
+        let path = String::from_utf8_lossy(entry.path());
+        println!("{}", path);
+        Ok(())
+    }
 }
 
 pub fn list(options: Options) -> Result<()> {
     let waj =
         waj::Waj::new(&options.infile).with_context(|| format!("Opening {:?}", options.infile))?;
+    if options.metadata {
+        for (key, value) in waj.metadata()?.iter() {
+            println!("{key}: {value}");
+        }
+        return Ok(());
+    }
+    let prefix = options.prefix.as_deref().unwrap_or_default();
+    if options.directory {
+        for child in waj.directory_children::<waj::FullBuilder>(prefix)? {
+            match child? {
+                waj::Child::Directory(path) => println!("{path}"),
+                waj::Child::Entry(entry) => {
+                    let path = match &entry {
+                        waj::Entry::Content(e) => e.path(),
+                        waj::Entry::Redirect(e) => e.path(),
+                        waj::Entry::Alias(e) => e.path(),
+                        waj::Entry::Whiteout(e) => e.path(),
+                    };
+                    println!("{}", String::from_utf8_lossy(path));
+                }
+            }
+        }
+        return Ok(());
+    }
     let mut walker = waj::walk::Walker::new(&waj, ());
-    Ok(walker.run(&Lister)?)
+    Ok(walker.run_with_prefix(prefix, &Lister)?)
 }
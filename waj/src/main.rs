@@ -1,5 +1,11 @@
+mod cat;
+mod check;
 mod create;
+mod diff;
+mod extract;
+mod info;
 mod list;
+mod merge;
 mod serve;
 
 use anyhow::Result;
@@ -32,8 +38,14 @@ struct Cli {
         help_heading = "Advanced",
         value_parser([
             "",
+            "cat",
+            "check",
             "create",
+            "diff",
+            "extract",
+            "info",
             "list",
+            "merge",
             "serve",
         ])
     )]
@@ -56,6 +68,24 @@ enum Commands {
 
     #[command(arg_required_else_help = true)]
     List(list::Options),
+
+    #[command(arg_required_else_help = true)]
+    Extract(extract::Options),
+
+    #[command(arg_required_else_help = true, visible_alias = "get")]
+    Cat(cat::Options),
+
+    #[command(arg_required_else_help = true)]
+    Info(info::Options),
+
+    #[command(arg_required_else_help = true)]
+    Check(check::Options),
+
+    #[command(arg_required_else_help = true)]
+    Diff(diff::Options),
+
+    #[command(arg_required_else_help = true)]
+    Merge(merge::Options),
 }
 
 /// Serve the waj archive on the web.
@@ -99,8 +129,14 @@ fn run() -> Result<()> {
     if let Some(what) = args.generate_man_page {
         let command = match what.as_str() {
             "" => Cli::command(),
+            "cat" => cat::Options::command(),
+            "check" => check::Options::command(),
             "create" => create::Options::command(),
+            "diff" => diff::Options::command(),
+            "extract" => extract::Options::command(),
+            "info" => info::Options::command(),
             "list" => list::Options::command(),
+            "merge" => merge::Options::command(),
             "serve" => Serve::command(),
             _ => return Ok(Cli::command().print_help()?),
         };
@@ -122,6 +158,12 @@ fn run() -> Result<()> {
             Commands::Create(options) => create::create(options),
             Commands::Serve(options) => serve::serve(options),
             Commands::List(options) => list::list(options),
+            Commands::Extract(options) => extract::extract(options),
+            Commands::Cat(options) => cat::cat(options),
+            Commands::Info(options) => info::info(options),
+            Commands::Check(options) => check::check(options),
+            Commands::Diff(options) => diff::diff(options),
+            Commands::Merge(options) => merge::merge(options),
         },
     }
 }
@@ -0,0 +1,128 @@
+use crate::create::{check_output_path_writable, CachedSize, ProgressBar};
+use anyhow::{Context, Result};
+use clap::{Parser, ValueHint};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Policy {
+    FirstWins,
+    LastWins,
+    Error,
+}
+
+impl From<Policy> for waj::create::ConflictPolicy {
+    fn from(policy: Policy) -> Self {
+        match policy {
+            Policy::FirstWins => Self::FirstWins,
+            Policy::LastWins => Self::LastWins,
+            Policy::Error => Self::Error,
+        }
+    }
+}
+
+/// A input archive and the prefix under which its entries are mounted.
+#[derive(Clone)]
+struct Input {
+    prefix: String,
+    path: PathBuf,
+}
+
+fn parse_input(input: &str) -> Result<Input, String> {
+    let (prefix, path) = match input.split_once('=') {
+        None => ("", input),
+        Some((prefix, path)) => (prefix.trim_matches('/'), path),
+    };
+    if path.is_empty() {
+        return Err(format!("No archive in `{input}`"));
+    }
+    Ok(Input {
+        prefix: if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{prefix}/")
+        },
+        path: path.into(),
+    })
+}
+
+/// Merge several archives into one.
+#[derive(Parser)]
+pub struct Options {
+    /// File path of the archive to create.
+    #[arg(short, long, value_parser, value_hint=ValueHint::FilePath)]
+    outfile: jbk::Utf8PathBuf,
+
+    /// Archives to merge, as `ARCHIVE` or `PREFIX=ARCHIVE`.
+    ///
+    /// Entries of a archive given with a `PREFIX` are mounted under the directory `PREFIX`.
+    #[arg(value_parser=parse_input, required = true, value_name = "[PREFIX=]ARCHIVE")]
+    infiles: Vec<Input>,
+
+    /// What to do when several archives contain the same path (or metadata key).
+    #[arg(long, value_parser, default_value = "first-wins")]
+    policy: Policy,
+
+    #[command(flatten)]
+    concat_mode: Option<jbk::cmd_utils::ConcatMode>,
+
+    /// Set compression algorithm to use
+    #[arg(short, long, value_parser=jbk::cmd_utils::compression_arg_parser, required=false, default_value="zstd")]
+    compression: jbk::creator::Compression,
+
+    #[arg(short, long, required = false, default_value_t = false, action)]
+    force: bool,
+
+    /// Build a full text index of the html content.
+    #[arg(long, default_value_t = false, action)]
+    fts: bool,
+
+    /// Store precompressed variants of the text contents (html, css, js, svg, json).
+    ///
+    /// Comma separated list of encodings (`gzip`, `br`).
+    /// Variants already present in the input archives are reused.
+    #[arg(long, value_name = "ENCODINGS", value_delimiter = ',')]
+    precompress: Vec<waj::Encoding>,
+
+    #[arg(from_global)]
+    verbose: u8,
+}
+
+pub fn merge(options: Options) -> Result<()> {
+    check_output_path_writable(options.outfile.as_std_path(), options.force)?;
+
+    let inputs = options
+        .infiles
+        .iter()
+        .map(|input| {
+            waj::Waj::new(&input.path)
+                .with_context(|| format!("Opening {:?}", input.path))
+                .map(|waj| (waj, input.prefix.as_str()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut creator = waj::create::FsCreator::new(
+        &options.outfile,
+        Box::new(waj::create::StripPrefix::new(PathBuf::new())),
+        match options.concat_mode {
+            None => jbk::creator::ConcatMode::OneFile,
+            Some(e) => e.into(),
+        },
+        Arc::new(ProgressBar::new()),
+        Rc::new(CachedSize::new()),
+        options.compression,
+    )?;
+
+    if options.fts {
+        creator.enable_full_text_index();
+    }
+
+    if !options.precompress.is_empty() {
+        creator.set_precompress(options.precompress.clone());
+    }
+
+    let inputs: Vec<_> = inputs.iter().map(|(waj, prefix)| (waj, *prefix)).collect();
+    creator.merge(&inputs, options.policy.into())?;
+    Ok(creator.finalize()?)
+}
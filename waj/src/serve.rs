@@ -29,6 +29,17 @@ pub struct Options {
     #[arg(long, value_parser, default_value = "path")]
     router: RouterKind,
 
+    /// Serve the archives as layers of one namespace, the first one being the base.
+    ///
+    /// Each archive hides the entries of the same path in the archives before it.
+    /// A whiteout entry (`waj create --whiteout`) hides a path without replacing it.
+    #[arg(long, conflicts_with = "router")]
+    overlay: bool,
+
+    /// List the content of directories without index.html
+    #[arg(long)]
+    dir_listing: bool,
+
     #[arg(from_global)]
     verbose: u8,
 }
@@ -54,23 +65,26 @@ fn input_files<P: AsRef<Path>>(user_input: &[P]) -> Result<Vec<PathBuf>> {
         .collect::<Result<Vec<_>, _>>()?)
 }
 
-pub fn serve(options: Options) -> Result<()> {
-    info!(
-        "Serve archive {:?} at {:?}",
-        options.infiles, options.address,
-    );
+fn open_server(path: &Path, options: &Options) -> Result<waj::WajServer> {
+    let mut waj_server =
+        waj::WajServer::open(path).with_context(|| format!("Opening {:?}", path))?;
+    if options.dir_listing {
+        waj_server.enable_dir_listing();
+    }
+    Ok(waj_server)
+}
+
+fn route_input_files(options: &Options) -> Result<Box<dyn waj::Router>> {
     let input_files = input_files(&options.infiles)?;
-    let router = if input_files.len() == 1 {
+    Ok(if input_files.len() == 1 {
         let inputfile = &input_files[0];
-        let waj_server =
-            waj::WajServer::open(inputfile).with_context(|| format!("Opening {:?}", inputfile))?;
+        let waj_server = open_server(inputfile, options)?;
         Box::new(waj_server) as Box<dyn waj::Router>
     } else {
         let input_files_key_map = input_files
             .iter()
             .map(|f| -> anyhow::Result<_> {
-                let waj_server =
-                    waj::WajServer::open(f).with_context(|| format!("Opening {:?}", f))?;
+                let waj_server = open_server(f, options)?;
                 Ok((
                     f.file_name().unwrap().to_string_lossy().to_string(),
                     waj_server,
@@ -85,6 +99,24 @@ pub fn serve(options: Options) -> Result<()> {
                 Box::new(waj::SubPathRouter::new(input_files_key_map)) as Box<dyn waj::Router>
             }
         }
+    })
+}
+
+pub fn serve(options: Options) -> Result<()> {
+    info!(
+        "Serve archive {:?} at {:?}",
+        options.infiles, options.address,
+    );
+    let router = if options.overlay {
+        // Directories are not expanded as layer order matters.
+        let layers = options
+            .infiles
+            .iter()
+            .map(|f| open_server(f, &options))
+            .collect::<Result<Vec<_>>>()?;
+        Box::new(waj::OverlayRouter::new(layers)) as Box<dyn waj::Router>
+    } else {
+        route_input_files(&options)?
     };
     let server = waj::Server::new(router);
 
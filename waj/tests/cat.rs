@@ -0,0 +1,49 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_cat() -> Result {
+    let source_dir = temp_tree!(4, {
+        dir "docs" {
+            custom "index.html" ("<html><body>0123456789</body></html>")
+        },
+        link "old.html" -> "docs/index.html"
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    cmd!("waj", "cat", &waj_file, "docs/")
+        .check_output(Some(b"<html><body>0123456789</body></html>"), Some(b""));
+    cmd!(
+        "waj",
+        "get",
+        &waj_file,
+        "/docs/index.html?q=1",
+        "--range",
+        "12-21"
+    )
+    .check_output(Some(b"0123456789"), Some(b""));
+    cmd!(
+        "waj",
+        "cat",
+        &waj_file,
+        "old.html",
+        "-L",
+        "--show-mimetype",
+        "--range",
+        "22-"
+    )
+    .check_output(Some(b"</body></html>"), Some(b"text/html\n"));
+    cmd!("waj", "cat", &waj_file, "old.html").check_fail(
+        b"",
+        b"Error : old.html is a redirect to docs/index.html (use --follow-redirects)\n",
+    );
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
@@ -0,0 +1,37 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_check(source_dir: SharedTestDir) -> Result {
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let output = cmd!("waj", "check", &waj_file).output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.ends_with("entries checked, no problem found\n"));
+    Ok(())
+}
+
+#[test]
+fn test_check_dangling_redirect() -> Result {
+    let source_dir = temp_tree!(6, {
+        custom "a.html" ("<html><body>A</body></html>"),
+        link "b.html" -> "missing.html"
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    cmd!("waj", "check", &waj_file).check_fail(
+        b"b.html: redirect to non existing missing.html\n",
+        b"Error : 1 problems found in 2 entries\n",
+    );
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
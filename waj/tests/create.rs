@@ -96,5 +96,282 @@ fn test_crate_existant_output(source_dir: SharedTestDir) -> Result {
     Ok(())
 }
 
+#[test]
+fn test_create_metadata(source_dir: SharedTestDir) -> Result {
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    let meta_file = waj_file.with_file_name("meta.txt");
+    std::fs::write(
+        &meta_file,
+        "# Some metadata\ntitle=Title from file\n\nlanguage = eng\n",
+    )?;
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "-C",
+        source_dir.parent().unwrap(),
+        "--strip-prefix",
+        source_dir.file_name().unwrap(),
+        source_dir.file_name().unwrap(),
+        "--meta-file",
+        &meta_file,
+        "--meta",
+        "title=A test archive",
+        "--meta",
+        "creator=Me"
+    )
+    .check_output(Some(b""), Some(b""));
+
+    cmd!("waj", "list", "--metadata", &waj_file).check_output(
+        Some(b"creator: Me\nlanguage: eng\ntitle: A test archive\n"),
+        Some(b""),
+    );
+    Ok(())
+}
+
+#[test]
+fn test_create_broken_links() -> Result {
+    let source_dir = temp_tree!(2, {
+        custom "index.html" ("<html><body><a href=\"docs/\">Docs</a> <a href=\"missing.html#top\">Missing</a> \
+            <a href=\"https://example.com/\">Out</a></body></html>"),
+        dir "docs" {
+            custom "index.html" ("<html><head><link rel=\"stylesheet\" href=\"/style.css\"></head></html>")
+        },
+        custom "style.css" ("body { background: url('img/bg.png') }")
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    let create_cmd = |fail_option: &str| {
+        cmd!(
+            "waj",
+            "create",
+            "--outfile",
+            &waj_file,
+            "--force",
+            "-C",
+            source_dir.parent().unwrap(),
+            "--strip-prefix",
+            source_dir.file_name().unwrap(),
+            fail_option,
+            source_dir.file_name().unwrap()
+        )
+    };
+    create_cmd("--check-links").check_output(
+        Some(b""),
+        Some(
+            b"index.html: broken link to missing.html#top\nstyle.css: broken link to img/bg.png\n",
+        ),
+    );
+    create_cmd("--fail-on-broken-links").check_fail(
+        b"",
+        b"index.html: broken link to missing.html#top\nstyle.css: broken link to img/bg.png\n\
+        Error : 2 broken link(s) found\n",
+    );
+    Ok(())
+}
+
+#[test]
+fn test_create_exclude() -> Result {
+    let source_dir = temp_tree!(3, {
+        custom "index.html" ("<html></html>"),
+        custom "app.js" ("var a;"),
+        custom "app.js.map" ("{}"),
+        custom ".DS_Store" ("junk"),
+        dir ".git" {
+            custom "HEAD" ("ref: refs/heads/main")
+        },
+        dir "drafts" {
+            custom ".wajignore" ("*\n!public.html"),
+            custom "draft.html" ("<html></html>"),
+            custom "public.html" ("<html></html>")
+        }
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    let list_entries = |options: &[&str]| -> Result<Vec<String>> {
+        let mut create_cmd = cmd!(
+            "waj",
+            "create",
+            "--outfile",
+            &waj_file,
+            "--force",
+            "-C",
+            source_dir.parent().unwrap(),
+            "--strip-prefix",
+            source_dir.file_name().unwrap()
+        );
+        create_cmd.args(options);
+        create_cmd.arg(source_dir.file_name().unwrap());
+        assert!(create_cmd.status()?.success());
+        let output = cmd!("waj", "list", &waj_file).output()?.stdout;
+        let mut entries: Vec<String> = String::from_utf8(output)?
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        entries.sort();
+        Ok(entries)
+    };
+    assert_eq!(
+        list_entries(&["--exclude", "*.map"])?,
+        vec!["app.js", "drafts/public.html", "index.html"]
+    );
+    assert_eq!(
+        list_entries(&["--hidden"])?,
+        vec![
+            ".DS_Store",
+            ".git/HEAD",
+            "app.js",
+            "app.js.map",
+            "drafts/public.html",
+            "index.html"
+        ]
+    );
+    assert_eq!(
+        list_entries(&["--include", "*.html"])?,
+        vec!["drafts/public.html", "index.html"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_create_exclude_from_tar() -> Result {
+    let source_dir = temp_tree!(3, {
+        custom "index.html" ("<html></html>"),
+        custom "app.js" ("var a;"),
+        custom "app.js.map" ("{}"),
+        dir ".git" {
+            custom "HEAD" ("ref: refs/heads/main")
+        }
+    });
+    let tar_file = tempfile::Builder::new()
+        .suffix(".tar")
+        .tempfile_in(env!("CARGO_TARGET_TMPDIR"))?;
+    let mut builder = tar::Builder::new(std::fs::File::create(tar_file.path())?);
+    builder.append_dir_all("site", source_dir.path())?;
+    builder.into_inner()?;
+    temp_waj!(waj_file);
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "--strip-prefix",
+        "site",
+        "--from-tar",
+        tar_file.path(),
+        "--exclude",
+        "*.map"
+    )
+    .check_output(Some(b""), Some(b""));
+    let output = cmd!("waj", "list", &waj_file).output()?.stdout;
+    let mut entries: Vec<String> = String::from_utf8(output)?
+        .lines()
+        .map(str::to_owned)
+        .collect();
+    entries.sort();
+    assert_eq!(entries, vec!["app.js", "index.html"]);
+    Ok(())
+}
+
+#[test]
+fn test_create_mime() -> Result {
+    let source_dir = temp_tree!(4, {
+        custom "page.htm" ("<html></html>"),
+        custom "notes.txt" ("notes"),
+        dir "legacy" {
+            custom "page.htm" ("<html></html>"),
+            custom "data" ("data")
+        }
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "-C",
+        source_dir.parent().unwrap(),
+        "--strip-prefix",
+        source_dir.file_name().unwrap(),
+        "--mime",
+        "htm=text/html; charset=utf-8",
+        "--mime",
+        "txt=text/x-notes",
+        "--mime-rule",
+        "legacy/=text/html; charset=iso-8859-1",
+        source_dir.file_name().unwrap()
+    )
+    .check_output(Some(b""), Some(b""));
+    for (path, mimetype) in [
+        ("page.htm", "text/html; charset=utf-8\n"),
+        ("notes.txt", "text/x-notes\n"),
+        ("legacy/page.htm", "text/html; charset=iso-8859-1\n"),
+        ("legacy/data", "text/html; charset=iso-8859-1\n"),
+    ] {
+        let output = cmd!("waj", "cat", &waj_file, path, "--show-mimetype").output()?;
+        assert_eq!(String::from_utf8(output.stderr)?, mimetype);
+    }
+    let invalid_mime = cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "--force",
+        "--mime",
+        "htm",
+        source_dir
+    )
+    .status()?;
+    assert!(!invalid_mime.success());
+    Ok(())
+}
+
+#[test]
+fn test_create_sniff_mime() -> Result {
+    let addr = "localhost:5060";
+    let source_dir = temp_tree!(1, {
+        custom "image" (b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR"),
+        custom "page" ("<!doctype html><html><body>Page</body></html>"),
+        custom "notes" ("Just some text, mentioning html.")
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "-C",
+        source_dir.parent().unwrap(),
+        "--strip-prefix",
+        source_dir.file_name().unwrap(),
+        source_dir.file_name().unwrap()
+    )
+    .check_output(Some(b""), Some(b""));
+
+    let mut command = cmd!("waj", "serve", &waj_file, "-a", &addr);
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    for (path, mimetype) in [
+        ("image", "image/png"),
+        ("page", "text/html"),
+        ("notes", "text/plain"),
+    ] {
+        let response = client.get(&client.url(path))?;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["Content-Type"].to_str()?, mimetype);
+    }
+    Ok(())
+}
+
 #[main]
 fn main() {}
@@ -0,0 +1,53 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_diff() -> Result {
+    let old_dir = temp_tree!(1, {
+        custom "a.txt" ("Same"),
+        custom "b.txt" ("Old b"),
+        custom "c.txt" ("Removed c")
+    });
+    let new_dir = temp_tree!(1, {
+        custom "a.txt" ("Same"),
+        custom "b.txt" ("New b"),
+        custom "d.txt" ("Added d")
+    });
+    temp_waj!(old_file, "old.waj");
+    temp_waj!(new_file, "new.waj");
+    build_waj_file(old_dir.path(), &old_file);
+    build_waj_file(new_dir.path(), &new_file);
+
+    cmd!("waj", "diff", &old_file, &old_file).check_output(Some(b""), Some(b""));
+    cmd!("waj", "diff", &old_file, &new_file)
+        .check_output(Some(b"M b.txt (content)\n- c.txt\n+ d.txt\n"), Some(b""));
+    cmd!("waj", "diff", "--json", &old_file, &new_file).check_output(
+        Some(
+            br#"{
+  "added": [
+    "d.txt"
+  ],
+  "changed": [
+    {
+      "changes": [
+        "content"
+      ],
+      "path": "b.txt"
+    }
+  ],
+  "removed": [
+    "c.txt"
+  ]
+}
+"#,
+        ),
+        Some(b""),
+    );
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
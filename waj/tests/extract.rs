@@ -0,0 +1,73 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_extract(source_dir: SharedTestDir) -> Result {
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let out_dir = tempfile::TempDir::with_prefix_in("extract_", env!("CARGO_TARGET_TMPDIR"))?;
+    cmd!("waj", "extract", &waj_file, "-C", out_dir.path()).check_output(Some(b""), Some(b""));
+
+    assert!(extract_diff(out_dir.path(), source_dir)?);
+    Ok(())
+}
+
+#[test]
+fn test_extract_paths(source_dir: SharedTestDir) -> Result {
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let out_dir = tempfile::TempDir::with_prefix_in("extract_", env!("CARGO_TARGET_TMPDIR"))?;
+    cmd!(
+        "waj",
+        "extract",
+        &waj_file,
+        "-C",
+        out_dir.path(),
+        "sub_dir_a/"
+    )
+    .check_output(Some(b""), Some(b""));
+
+    assert!(extract_diff(
+        out_dir.path().join("sub_dir_a"),
+        source_dir.join("sub_dir_a")
+    )?);
+    assert!(!out_dir.path().join("sub_dir_b").exists());
+    Ok(())
+}
+
+#[test]
+fn test_extract_redirect_as_html() -> Result {
+    let source_dir = temp_tree!(3, {
+        custom "a.html" ("<html><body>Content</body></html>"),
+        link "b.html" -> "a.html"
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let out_dir = tempfile::TempDir::with_prefix_in("extract_", env!("CARGO_TARGET_TMPDIR"))?;
+    cmd!(
+        "waj",
+        "extract",
+        &waj_file,
+        "-C",
+        out_dir.path(),
+        "--redirect",
+        "html"
+    )
+    .check_output(Some(b""), Some(b""));
+
+    let stub = std::fs::read_to_string(out_dir.path().join("b.html"))?;
+    assert!(stub.contains(r#"<meta http-equiv="refresh" content="0; url=a.html">"#));
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
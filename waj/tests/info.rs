@@ -0,0 +1,38 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_info() -> Result {
+    let source_dir = temp_tree!(5, {
+        custom "a.html" ("<html><body>A</body></html>"),
+        custom "b.css" ("body {}"),
+        link "c.html" -> "a.html"
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let output = cmd!("waj", "info", &waj_file).output()?;
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout)?;
+    assert!(output.contains("Entries: 3 (2 contents, 1 redirects, 0 aliases)\n"));
+    assert!(output.contains("Content size: 34 bytes\n"));
+    assert!(output.contains("  text/html: 1\n"));
+    assert!(output.contains("Compression ratio: "));
+    assert!(output.contains(" bytes compressed with zstd, in container\n"));
+
+    let output = cmd!("waj", "info", "--json", &waj_file).output()?;
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout)?;
+    assert!(output.contains(r#""contents": 2,"#));
+    assert!(output.contains(r#""missing": false,"#));
+    assert!(output.contains(r#""compression": "zstd","#));
+    assert!(output.contains(r#""location": """#));
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
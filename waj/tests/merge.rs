@@ -0,0 +1,68 @@
+mod utils;
+
+use rustest::{test, Result};
+use std::path::Path;
+use utils::*;
+
+#[test]
+fn test_merge() -> Result {
+    let a_dir = temp_tree!(1, {
+        custom "index.html" ("<html>A</html>"),
+        custom "common.css" ("a")
+    });
+    let b_dir = temp_tree!(1, {
+        custom "index.html" ("<html>B</html>"),
+        custom "common.css" ("b")
+    });
+    temp_waj!(a_file, "a.waj");
+    temp_waj!(b_file, "b.waj");
+    build_waj_file_with_args(a_dir.path(), &a_file, &["--meta", "title=A"]);
+    build_waj_file_with_args(b_dir.path(), &b_file, &["--meta", "title=B"]);
+
+    for (policy, expected) in [("first-wins", "A"), ("last-wins", "B")] {
+        temp_waj!(merged_file, "merged.waj");
+        cmd!(
+            "waj",
+            "merge",
+            "--outfile",
+            &merged_file,
+            "--policy",
+            policy,
+            &a_file,
+            &b_file,
+            format!("b={}", b_file.display())
+        )
+        .check_output(Some(b""), Some(b""));
+
+        cmd!("waj", "list", &merged_file).check_output(
+            Some(b"b/common.css\nb/index.html\ncommon.css\nindex.html\n"),
+            Some(b""),
+        );
+        cmd!("waj", "cat", &merged_file, "index.html").check_output(
+            Some(format!("<html>{expected}</html>").as_bytes()),
+            Some(b""),
+        );
+        cmd!("waj", "cat", &merged_file, "b/index.html")
+            .check_output(Some(b"<html>B</html>"), Some(b""));
+        cmd!("waj", "list", "--metadata", &merged_file)
+            .check_output(Some(format!("title: {expected}\n").as_bytes()), Some(b""));
+    }
+
+    temp_waj!(merged_file, "merged.waj");
+    let output = cmd!(
+        "waj",
+        "merge",
+        "--outfile",
+        &merged_file,
+        "--policy",
+        "error",
+        &a_file,
+        &b_file
+    )
+    .output()?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[rustest::main]
+fn main() {}
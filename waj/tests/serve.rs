@@ -4,21 +4,6 @@ use rustest::{fixture, test, Result};
 use std::path::Path;
 use utils::*;
 
-fn build_waj_file(directory: &Path, outfile: &Path) {
-    cmd!(
-        "waj",
-        "create",
-        "--outfile",
-        outfile,
-        "-C",
-        directory.parent().unwrap(),
-        "--strip-prefix",
-        directory.file_name().unwrap(),
-        directory.file_name().unwrap()
-    )
-    .check_output(Some(b""), Some(b""));
-}
-
 #[fixture(scope=global)]
 fn BaseWajFile(source_dir: SharedTestDir) -> TmpWaj {
     let source_dir = source_dir.path();
@@ -142,6 +127,326 @@ fn test_multi_serve_path(source_dir: SharedTestDir, waj_file: BaseWajFile) -> Re
     Ok(())
 }
 
+#[test]
+fn test_serve_search() -> Result {
+    let addr = "localhost:5054";
+    let source_dir = temp_tree!(2, {
+        custom "page.html" ("<html><title>Page</title><body>Some searchable content</body></html>"),
+        custom "other.html" ("<html><title>Other</title><body>Nothing here</body></html>")
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "--fts",
+        "-C",
+        source_dir.parent().unwrap(),
+        "--strip-prefix",
+        source_dir.file_name().unwrap(),
+        source_dir.file_name().unwrap()
+    )
+    .check_output(Some(b""), Some(b""));
+
+    let mut command = cmd!("waj", "serve", &waj_file, "-a", &addr);
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    let mut response = client.get(&client.url("_waj/search?q=searchable&format=json"))?;
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut().read_to_string()?;
+    assert!(body.starts_with(r#"{"query":"searchable","results":[{"path":"page.html","#));
+
+    let mut response = client.get(&client.url("_waj/search?q=searchable"))?;
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut().read_to_string()?;
+    assert!(body.contains(r#"<a href="../page.html">page.html</a>"#));
+
+    // Encoded `+`, `&` and `=` are part of the query, not separators.
+    let mut response =
+        client.get(&client.url("_waj/search?q=c%2B%2B+searchable%26a%3Db&format=json"))?;
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut().read_to_string()?;
+    assert!(body.starts_with(r#"{"query":"c++ searchable&a=b","results":["#));
+    Ok(())
+}
+
+#[test]
+fn test_serve_precompress() -> Result {
+    let addr = "localhost:5055";
+    let source_dir = temp_tree!(2, {
+        custom "page.html" ("<html><body>".to_owned() + &"Some compressible content. ".repeat(100) + "</body></html>"),
+        custom "image.png" ((0..=255).collect::<Vec<u8>>())
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        &waj_file,
+        "--precompress",
+        "gzip,br",
+        "-C",
+        source_dir.parent().unwrap(),
+        "--strip-prefix",
+        source_dir.file_name().unwrap(),
+        source_dir.file_name().unwrap()
+    )
+    .check_output(Some(b""), Some(b""));
+
+    let mut command = cmd!("waj", "serve", &waj_file, "-a", &addr);
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    let content_encoding = |path: &str, accept_encoding: &str| -> Result<Option<String>> {
+        let response = ureq::head(&client.url(path))
+            .header("Accept-Encoding", accept_encoding)
+            .call()?;
+        assert_eq!(response.status(), 200);
+        Ok(response
+            .headers()
+            .get("Content-Encoding")
+            .map(|v| v.to_str().unwrap().to_owned()))
+    };
+    assert_eq!(
+        content_encoding("page.html", "gzip, br")?,
+        Some("br".into())
+    );
+    assert_eq!(content_encoding("page.html", "gzip")?, Some("gzip".into()));
+    assert_eq!(content_encoding("page.html", "identity")?, None);
+    assert_eq!(content_encoding("image.png", "gzip, br")?, None);
+
+    let response = ureq::head(&client.url("page.html")).call()?;
+    assert_eq!(response.headers()["Vary"].to_str()?, "Accept-Encoding");
+    Ok(())
+}
+
+#[test]
+fn test_serve_alias() -> Result {
+    let addr = "localhost:5056";
+    let source_dir = temp_tree!(2, {
+        custom "a.html" ("<html><body>Same content</body></html>"),
+        custom "b.html" ("<html><body>Same content</body></html>")
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let mut command = cmd!("waj", "serve", &waj_file, "-a", &addr);
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    for path in ["a.html", "b.html"] {
+        let mut response = client.get(&client.url(path))?;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.body_mut().read_to_string()?,
+            "<html><body>Same content</body></html>"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_serve_dir_listing() -> Result {
+    let addr = "localhost:5057";
+    let source_dir = temp_tree!(2, {
+        dir "docs" {
+            custom "a.txt" ("Some text"),
+            dir "sub" {
+                custom "b.txt" ("Other text")
+            }
+        }
+    });
+    let source_dir = source_dir.path();
+    temp_waj!(waj_file);
+    build_waj_file(source_dir, &waj_file);
+
+    let mut command = cmd!("waj", "serve", &waj_file, "-a", &addr, "--dir-listing");
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    let mut response = client.get(&client.url("docs/"))?;
+    assert_eq!(response.status(), 200);
+    let body = response.body_mut().read_to_string()?;
+    assert!(body.contains(r#"<a href="a.txt">a.txt</a>"#));
+    assert!(body.contains(r#"<a href="sub/">sub/</a>"#));
+    assert!(body.contains(r#"<a href="../">../</a>"#));
+
+    let mut response = ureq::get(&client.url("docs/"))
+        .header("Accept", "application/json")
+        .call()?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.body_mut().read_to_string()?,
+        r#"{"path":"docs/","entries":[{"name":"a.txt","type":"content","size":9,"mimetype":"text/plain"},{"name":"sub/","type":"directory","size":null,"mimetype":null}]}"#
+    );
+
+    let response = client.get(&client.url("nothing/"))?;
+    assert_eq!(response.status(), 404);
+    Ok(())
+}
+
+#[test]
+fn test_serve_overlay() -> Result {
+    let addr = "localhost:5058";
+    let base_dir = temp_tree!(2, {
+        custom "a.txt" ("Base a"),
+        custom "b.txt" ("Base b"),
+        custom "c.txt" ("Base c")
+    });
+    let patch_dir = temp_tree!(2, {
+        custom "404.html" ("<html>Not here</html>"),
+        custom "a.txt" ("Patched a"),
+        custom "d.txt" ("New d")
+    });
+    temp_waj!(base_waj);
+    build_waj_file(base_dir.path(), &base_waj);
+    temp_waj!(patch_waj);
+    build_waj_file_with_args(patch_dir.path(), &patch_waj, &["--whiteout", "b.txt"]);
+
+    let mut command = cmd!(
+        "waj",
+        "serve",
+        "--overlay",
+        &base_waj,
+        &patch_waj,
+        "-a",
+        &addr,
+        "--dir-listing"
+    );
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    for (path, expected) in [
+        ("a.txt", "Patched a"),
+        ("c.txt", "Base c"),
+        ("d.txt", "New d"),
+        // The query string is not part of the path.
+        ("a.txt?v=1", "Patched a"),
+        ("d.txt?q=%3F", "New d"),
+    ] {
+        let mut response = client.get(&client.url(path))?;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.body_mut().read_to_string()?, expected);
+    }
+    // Requests matching no entry are answered by merging the layers.
+    for path in ["b.txt", "nothing.txt"] {
+        let mut response = client.get(&client.url(path))?;
+        assert_eq!(response.status(), 404);
+        assert_eq!(
+            response.body_mut().read_to_string()?,
+            "<html>Not here</html>"
+        );
+    }
+    let mut response = ureq::get(&client.url(""))
+        .header("Accept", "application/json")
+        .call()?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.body_mut().read_to_string()?,
+        r#"{"path":"","entries":[{"name":"404.html","type":"content","size":21,"mimetype":"text/html"},{"name":"a.txt","type":"content","size":9,"mimetype":"text/plain"},{"name":"c.txt","type":"content","size":6,"mimetype":"text/plain"},{"name":"d.txt","type":"content","size":5,"mimetype":"text/plain"}]}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn test_serve_redirect_status() -> Result {
+    let addr = "localhost:5059";
+    let source_dir = temp_tree!(1, {
+        custom "a.html" ("<html><body>A</body></html>")
+    });
+    let source_dir = source_dir.path();
+    // One layer per status, served as a overlay.
+    let tmp_waj_dir = tempfile::tempdir_in(Path::new(env!("CARGO_TARGET_TMPDIR")))
+        .expect("Creating tmpdir should work");
+    let mut layers = vec![];
+    for status in ["301", "303", "307", "308"] {
+        let waj_file = tmp_waj_dir.path().join(format!("{status}.waj"));
+        cmd!(
+            "waj",
+            "create",
+            "--outfile",
+            &waj_file,
+            "-C",
+            source_dir.parent().unwrap(),
+            "--strip-prefix",
+            source_dir.file_name().unwrap(),
+            "--redirect",
+            format!("r{status}.html=a.html"),
+            "--redirect",
+            format!("ext{status}=https://example.com/a%20b?s={status}"),
+            "--redirect-status",
+            status,
+            source_dir.file_name().unwrap()
+        )
+        .check_output(Some(b""), Some(b""));
+        layers.push(waj_file);
+    }
+
+    let mut command = cmd!(
+        "waj",
+        "serve",
+        "--overlay",
+        &layers[0],
+        &layers[1],
+        &layers[2],
+        &layers[3],
+        "-a",
+        &addr
+    );
+    let mut child = command.spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    tear_down!(CloseServer, || {
+        child.kill().unwrap();
+    });
+
+    let client = Client::new(addr.into());
+    for status in [301, 303, 307, 308] {
+        let response = client.get(&client.url(&format!("r{status}.html")))?;
+        assert_eq!(response.status(), status);
+        assert_eq!(response.headers()["Location"].to_str()?, "a.html");
+
+        // External targets are sent as is.
+        let response = client.get(&client.url(&format!("ext{status}")))?;
+        assert_eq!(response.status(), status);
+        assert_eq!(
+            response.headers()["Location"].to_str()?,
+            format!("https://example.com/a%20b?s={status}")
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn test_list(source_dir: SharedTestDir, waj_file: BaseWajFile) -> Result {
     let mut cmd = cmd!("waj", "list", waj_file.path());
@@ -1,6 +1,8 @@
 mod utils;
 
-use ureq::http::header::{ACCEPT_RANGES, RANGE};
+use ureq::http::header::{
+    ACCEPT_RANGES, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE,
+};
 
 use core::ops::{Deref, Drop};
 use std::path::Path;
@@ -26,18 +28,7 @@ fn BaseWajFile() -> TmpWaj {
         .expect("Creating tmpdir should work");
     let tmp_waj = tmp_waj_dir.path().join("test.waj");
 
-    cmd!(
-        "waj",
-        "create",
-        "--outfile",
-        &tmp_waj,
-        "-C",
-        source_dir.parent().unwrap(),
-        "--strip-prefix",
-        source_dir.file_name().unwrap(),
-        source_dir.file_name().unwrap()
-    )
-    .check_output(Some(b""), Some(b""));
+    build_waj_file(source_dir, &tmp_waj);
     TmpWaj::new(tmp_waj_dir, tmp_waj)
 }
 
@@ -202,5 +193,74 @@ fn test_suffix_byte_range(server: inner::Server) -> rustest::Result {
     Ok(())
 }
 
+#[test]
+fn test_last_modified(server: inner::Server) -> rustest::Result {
+    let response = ureq::head(server.url("ref")).call()?;
+    assert!(
+        response.headers().contains_key(LAST_MODIFIED),
+        "Server does not send Last-Modified"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_if_modified_since(server: inner::Server) -> rustest::Result {
+    let response = ureq::head(server.url("ref")).call()?;
+    let last_modified = response.headers()[LAST_MODIFIED].to_str()?.to_string();
+    let response = ureq::get(server.url("ref"))
+        .header(IF_MODIFIED_SINCE, &last_modified)
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .call()?;
+    assert_eq!(response.status(), 304, "Expected status code is 304");
+
+    let response = ureq::get(server.url("ref"))
+        .header(IF_MODIFIED_SINCE, "Thu, 01 Jan 1970 00:00:00 GMT")
+        .call()?;
+    assert_eq!(response.status(), 200, "Expected status code is 200");
+    Ok(())
+}
+
+#[test]
+fn test_if_range(server: inner::Server) -> rustest::Result {
+    let response = ureq::head(server.url("ref")).call()?;
+    let last_modified = response.headers()[LAST_MODIFIED].to_str()?.to_string();
+    let response = ureq::get(server.url("ref"))
+        .header(RANGE, "bytes=0-99")
+        .header(IF_RANGE, &last_modified)
+        .call()?;
+    assert_eq!(response.status(), 206, "Expected status code is 206");
+
+    let response = ureq::get(server.url("ref"))
+        .header(RANGE, "bytes=0-99")
+        .header(IF_RANGE, "Thu, 01 Jan 1970 00:00:00 GMT")
+        .call()?;
+    assert_eq!(response.status(), 200, "Expected status code is 200");
+    Ok(())
+}
+
+#[test]
+fn test_content_etag(server: inner::Server) -> rustest::Result {
+    let full_content: Vec<u8> = (0..=255).collect();
+    let expected_etag = format!("\"{}\"", blake3::hash(&full_content).to_hex());
+    let response = ureq::head(server.url("ref")).call()?;
+    assert_eq!(response.headers()[ETAG].to_str()?, expected_etag);
+
+    let response = ureq::get(server.url("ref"))
+        .header(IF_NONE_MATCH, &expected_etag)
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .call()?;
+    assert_eq!(response.status(), 304, "Expected status code is 304");
+
+    let response = ureq::get(server.url("ref"))
+        .header(IF_NONE_MATCH, "\"0123\"")
+        .call()?;
+    assert_eq!(response.status(), 200, "Expected status code is 200");
+    Ok(())
+}
+
 #[rustest::main]
 fn main() {}
@@ -254,6 +254,28 @@ pub fn server_diff(client: Client, root: impl AsRef<Path>) -> std::io::Result<bo
     diff_entry(&client, reference, root.as_ref())
 }
 
+/// A directory in which a archive has been extracted.
+pub struct ExtractedDir(pub PathBuf);
+
+impl ContainEqual for ExtractedDir {
+    fn contains(&self, e: &TreeEntry, root: &Path) -> bool {
+        let extracted = self.0.join(e.path().strip_prefix(root).unwrap());
+        match TreeEntry::new(&extracted) {
+            Ok(extracted) => &extracted == e,
+            Err(_) => false,
+        }
+    }
+}
+
+pub fn extract_diff(extracted: impl AsRef<Path>, root: impl AsRef<Path>) -> std::io::Result<bool> {
+    let reference = TreeEntry::new(root.as_ref())?;
+    diff_entry(
+        &ExtractedDir(extracted.as_ref().to_path_buf()),
+        reference,
+        root.as_ref(),
+    )
+}
+
 pub fn diff_entry(
     tested_content: &(impl ContainEqual + Sync),
     reference: TreeEntry,
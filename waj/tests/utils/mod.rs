@@ -403,6 +403,29 @@ impl CheckCommand for Command {
     }
 }
 
+/// Create `outfile` from `directory`, the entries being named relatively to `directory`.
+pub fn build_waj_file(directory: &Path, outfile: &Path) {
+    build_waj_file_with_args(directory, outfile, &[])
+}
+
+/// Same as `build_waj_file`, with `args` added to the `waj create` command line.
+pub fn build_waj_file_with_args(directory: &Path, outfile: &Path, args: &[&str]) {
+    let mut command = cmd!(
+        "waj",
+        "create",
+        "--outfile",
+        outfile,
+        "-C",
+        directory.parent().unwrap(),
+        "--strip-prefix",
+        directory.file_name().unwrap()
+    );
+    command
+        .args(args)
+        .arg(directory.file_name().unwrap())
+        .check_output(Some(b""), Some(b""));
+}
+
 #[macro_export]
 macro_rules! join {
     ($first:tt / $($args:tt)/+) => {